@@ -0,0 +1,79 @@
+#!/usr/bin/env python3
+"""Generates src/emoji_names.rs, the Slack names for emoji that the `emojis` crate's
+GitHub shortcodes don't cover.
+
+Slack's names come from iamcal/emoji-data, which names each emoji after its Unicode
+character name (e.g. `robot_face`) and its CLDR name (e.g. `star-struck`) as well as
+its GitHub shortcode. This rebuilds the first two for every emoji in the `emojis` crate
+the bridge is built with, so rerun it after bumping that crate:
+
+    python3 scripts/emoji_names.py > src/emoji_names.rs
+"""
+
+import json
+import re
+import subprocess
+import sys
+import unicodedata
+from pathlib import Path
+
+VARIATION_SELECTOR = "️"
+ROW = re.compile(
+    r'Emoji \{ emoji: "(?P<emoji>[^"]+)", name: "(?P<name>[^"]+)",.*?'
+    r"skin_tone: (?P<skin_tone>None|Some\(\(\d+, \d+, SkinTone::(?P<tone>\w+)\)\)), "
+    r'aliases: (?:None|Some\(&\[(?P<aliases>[^\]]*)\]\))'
+)
+
+
+def emojis_source():
+    metadata = json.loads(
+        subprocess.check_output(["cargo", "metadata", "--format-version", "1"])
+    )
+    package = next(p for p in metadata["packages"] if p["name"] == "emojis")
+    return Path(package["manifest_path"]).parent / "src" / "gen" / "mod.rs"
+
+
+def slack_name(name):
+    name = name.lower().replace(" ", "_")
+    return name if re.fullmatch(r"[a-z0-9_-]+", name) else None
+
+
+def main():
+    rows = [m.groupdict() for m in ROW.finditer(emojis_source().read_text())]
+    shortcodes = {
+        shortcode
+        for row in rows
+        for shortcode in re.findall(r'"([^"]+)"', row["aliases"] or "")
+    }
+
+    names = {}
+    for row in rows:
+        # Skin tones are added to the base name, e.g. `wave::skin-tone-3`
+        if row["tone"] not in (None, "Default"):
+            continue
+
+        candidates = []
+        codepoints = row["emoji"].replace(VARIATION_SELECTOR, "")
+        if len(codepoints) == 1:
+            candidates.append(unicodedata.name(codepoints, ""))
+        candidates.append(row["name"])
+
+        for candidate in candidates:
+            name = slack_name(candidate)
+            if name and name not in shortcodes and name not in names:
+                names[name] = row["emoji"]
+
+    out = sys.stdout
+    out.write("// Generated by scripts/emoji_names.py, don't edit by hand.\n\n")
+    out.write(
+        "/// Slack names from Unicode character and CLDR names, sorted for binary search.\n"
+    )
+    out.write("#[rustfmt::skip]\n")
+    out.write("pub const SLACK_NAMES: &[(&str, &str)] = &[\n")
+    for name, emoji in sorted(names.items()):
+        out.write(f'    ("{name}", "{emoji}"),\n')
+    out.write("];\n")
+
+
+if __name__ == "__main__":
+    main()
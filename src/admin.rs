@@ -0,0 +1,485 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, Request};
+use axum::http::StatusCode;
+use axum::http::header::AUTHORIZATION;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use poise::serenity_prelude as serenity;
+use redis::RedisError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tracing::error;
+
+use crate::bridge::{self, BridgeChannels, DeadLetter, QueuedEvent};
+use crate::cache::slack_users::SlackUserCache;
+use crate::commands::link::verify_and_join_slack_channel;
+use crate::config::Config;
+use crate::redis::{RedisClient, parse_discord_message};
+use crate::settings::{LinkSettings, LinkState};
+use crate::slack_client::SlackApiClient;
+use crate::sources::{discord, slack};
+
+const DEFAULT_DEAD_LETTER_LIMIT: usize = 100;
+const DEFAULT_BACKFILL_LIMIT: u8 = 50;
+
+/// Link management for ops tooling, mounted under `/admin` when an API token is set.
+/// Everything needs `Authorization: Bearer <token>`.
+pub fn router() -> Router {
+    routes().route_layer(middleware::from_fn(require_token))
+}
+
+/// The API without authentication, for the dashboard to put its own in front of.
+pub fn routes() -> Router {
+    Router::new()
+        .route("/links", get(list_links).post(create_link))
+        .route(
+            "/links/{discord}/{slack}",
+            get(get_link).patch(update_link).delete(delete_link),
+        )
+        .route("/links/{discord}/{slack}/pause", post(pause_link))
+        .route("/links/{discord}/{slack}/resume", post(resume_link))
+        .route("/links/{discord}/{slack}/queue", get(link_queue))
+        .route("/links/{discord}/{slack}/backfill", post(backfill))
+        .route("/mappings/discord/{message_id}", get(discord_mapping))
+        .route("/mappings/slack/{message_ts}", get(slack_mapping))
+        .route(
+            "/dead-letters",
+            get(list_dead_letters).delete(clear_dead_letters),
+        )
+        .route("/dead-letters/replay", post(replay_dead_letters))
+}
+
+async fn require_token(
+    Extension(config): Extension<Arc<Config>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let expected = config.admin.api_token.as_deref().unwrap_or_default();
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if !expected.is_empty() && tokens_match(token, expected) => {
+            next.run(request).await
+        }
+        _ => ApiError::new(StatusCode::UNAUTHORIZED, "missing or invalid bearer token")
+            .into_response(),
+    }
+}
+
+/// Compares without stopping at the first difference, so timing doesn't leak the token.
+pub fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+impl From<RedisError> for ApiError {
+    fn from(e: RedisError) -> Self {
+        error!(error = %e, "Admin API request failed");
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+pub type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// A link as the API shows it. Discord IDs are strings, since they don't fit in a
+/// JavaScript number.
+#[derive(Serialize)]
+pub struct Link {
+    discord_channel_id: String,
+    slack_channel_id: String,
+    state: &'static str,
+    settings: Map<String, Value>,
+}
+
+pub async fn load_link(
+    redis_client: &RedisClient,
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+) -> Result<Link, ApiError> {
+    let state = redis_client
+        .get_link_state(discord_channel_id, slack_channel_id)
+        .await?;
+    let settings = redis_client
+        .get_link_settings(discord_channel_id, slack_channel_id)
+        .await?;
+
+    Ok(Link {
+        discord_channel_id: discord_channel_id.to_string(),
+        slack_channel_id: slack_channel_id.to_string(),
+        state: state.key(),
+        settings: settings.to_json(),
+    })
+}
+
+/// Links that are live or dead but not yet expired, as `list_links` keeps them.
+async fn require_link(
+    redis_client: &RedisClient,
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+) -> Result<LinkState, ApiError> {
+    let linked = redis_client
+        .get_linked_slack_channel(discord_channel_id)
+        .await?
+        .is_some_and(|linked| linked == slack_channel_id);
+    let state = redis_client
+        .get_link_state(discord_channel_id, slack_channel_id)
+        .await?;
+
+    if linked || state == LinkState::Dead {
+        Ok(state)
+    } else {
+        Err(ApiError::new(StatusCode::NOT_FOUND, "no such link"))
+    }
+}
+
+/// Like [`require_link`], for changes that only make sense on a live link.
+async fn require_live_link(
+    redis_client: &RedisClient,
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+) -> Result<(), ApiError> {
+    match require_link(redis_client, discord_channel_id, slack_channel_id).await? {
+        LinkState::Dead => Err(ApiError::new(StatusCode::CONFLICT, "link is dead")),
+        _ => Ok(()),
+    }
+}
+
+async fn list_links(Extension(redis_client): Extension<Arc<RedisClient>>) -> ApiResult<Vec<Link>> {
+    let mut links = Vec::new();
+    for (discord_channel_id, slack_channel_id) in redis_client.list_links().await? {
+        links.push(load_link(&redis_client, discord_channel_id, &slack_channel_id).await?);
+    }
+
+    Ok(Json(links))
+}
+
+async fn get_link(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+) -> ApiResult<Link> {
+    require_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+    Ok(Json(
+        load_link(&redis_client, discord_channel_id, &slack_channel_id).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct NewLink {
+    discord_channel_id: String,
+    slack_channel_id: String,
+    #[serde(default)]
+    settings: Map<String, Value>,
+}
+
+async fn create_link(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(slack_client): Extension<Arc<SlackApiClient>>,
+    Extension(config): Extension<Arc<Config>>,
+    Json(new_link): Json<NewLink>,
+) -> Result<(StatusCode, Json<Link>), ApiError> {
+    let discord_channel_id = new_link
+        .discord_channel_id
+        .parse::<u64>()
+        .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, "invalid discord_channel_id"))?;
+    let slack_channel_id = new_link.slack_channel_id;
+
+    let mut settings = LinkSettings::default();
+    settings
+        .apply_json(&new_link.settings)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    if redis_client
+        .get_linked_slack_channel(discord_channel_id)
+        .await?
+        .is_some()
+        || redis_client
+            .get_linked_discord_channel(&slack_channel_id)
+            .await?
+            .is_some()
+    {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "one of the channels is already linked",
+        ));
+    }
+
+    // Same as the link commands: make sure we can post on the Slack side first
+    let slack_token = config.slack_token();
+    verify_and_join_slack_channel(&slack_client, &slack_token, &slack_channel_id)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+
+    redis_client
+        .link_channels(discord_channel_id, &slack_channel_id)
+        .await?;
+    redis_client
+        .set_link_settings(discord_channel_id, &slack_channel_id, settings)
+        .await?;
+
+    let link = load_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+#[derive(Deserialize)]
+struct LinkUpdate {
+    settings: Map<String, Value>,
+}
+
+async fn update_link(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+    Json(update): Json<LinkUpdate>,
+) -> ApiResult<Link> {
+    require_live_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+
+    let mut settings = redis_client
+        .get_link_settings(discord_channel_id, &slack_channel_id)
+        .await?;
+    settings
+        .apply_json(&update.settings)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    redis_client
+        .set_link_settings(discord_channel_id, &slack_channel_id, settings)
+        .await?;
+
+    Ok(Json(
+        load_link(&redis_client, discord_channel_id, &slack_channel_id).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct DeleteOptions {
+    #[serde(default)]
+    purge: bool,
+}
+
+async fn delete_link(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+    Query(options): Query<DeleteOptions>,
+) -> Result<StatusCode, ApiError> {
+    require_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+    redis_client
+        .unlink_channels(discord_channel_id, &slack_channel_id, options.purge)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize, Default)]
+struct PauseOptions {
+    #[serde(default)]
+    queue: bool,
+}
+
+async fn pause_link(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+    options: Option<Json<PauseOptions>>,
+) -> ApiResult<Link> {
+    require_live_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+
+    let Json(options) = options.unwrap_or_default();
+    let state = if options.queue {
+        LinkState::Queueing
+    } else {
+        LinkState::Paused
+    };
+    redis_client
+        .set_link_state(discord_channel_id, &slack_channel_id, state)
+        .await?;
+
+    Ok(Json(
+        load_link(&redis_client, discord_channel_id, &slack_channel_id).await?,
+    ))
+}
+
+async fn resume_link(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(bridge): Extension<Arc<BridgeChannels>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+) -> ApiResult<Value> {
+    require_live_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+
+    let replayed = bridge::resume_link(
+        &redis_client,
+        &bridge,
+        discord_channel_id,
+        &slack_channel_id,
+    )
+    .await?;
+    Ok(Json(json!({ "replayed": replayed })))
+}
+
+/// Events held back while the link is paused with queueing.
+async fn link_queue(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+) -> ApiResult<Vec<QueuedEvent>> {
+    require_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+
+    Ok(Json(
+        redis_client
+            .peek_queued_events(discord_channel_id, &slack_channel_id)
+            .await?,
+    ))
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Side {
+    Discord,
+    Slack,
+}
+
+#[derive(Deserialize)]
+struct BackfillRequest {
+    from: Side,
+    limit: Option<u8>,
+}
+
+/// Bridges recent history from one side that never made it across.
+#[allow(clippy::too_many_arguments)]
+async fn backfill(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(bridge): Extension<Arc<BridgeChannels>>,
+    Extension(discord_http): Extension<Arc<serenity::Http>>,
+    Extension(slack_client): Extension<Arc<SlackApiClient>>,
+    Extension(user_cache): Extension<Arc<SlackUserCache>>,
+    Extension(config): Extension<Arc<Config>>,
+    Path((discord_channel_id, slack_channel_id)): Path<(u64, String)>,
+    Json(request): Json<BackfillRequest>,
+) -> ApiResult<Value> {
+    require_live_link(&redis_client, discord_channel_id, &slack_channel_id).await?;
+
+    // Both APIs cap a page of history at 100 messages
+    let limit = request.limit.unwrap_or(DEFAULT_BACKFILL_LIMIT).min(100);
+    let result = match request.from {
+        Side::Discord => {
+            discord::backfill(
+                &discord_http,
+                &config,
+                &bridge,
+                &redis_client,
+                discord_channel_id,
+                limit,
+            )
+            .await
+        }
+        Side::Slack => {
+            slack::backfill(
+                &slack_client,
+                &config,
+                &bridge,
+                &redis_client,
+                &user_cache,
+                &slack_channel_id,
+                limit.into(),
+            )
+            .await
+        }
+    };
+
+    match result {
+        Ok(sent) => Ok(Json(json!({ "sent": sent }))),
+        Err(e) => {
+            error!(error = %e, "Backfill failed");
+            Err(ApiError::new(StatusCode::BAD_GATEWAY, e.to_string()))
+        }
+    }
+}
+
+async fn discord_mapping(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path(message_id): Path<u64>,
+) -> ApiResult<Value> {
+    let slack_info = redis_client
+        .get_slack_message(message_id)
+        .await?
+        .ok_or(ApiError::new(StatusCode::NOT_FOUND, "no mapping"))?;
+    let (slack_channel_id, slack_message_ts) = slack_info
+        .split_once(':')
+        .ok_or_else(|| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "invalid mapping"))?;
+
+    Ok(Json(json!({
+        "slack_channel_id": slack_channel_id,
+        "slack_message_ts": slack_message_ts,
+    })))
+}
+
+async fn slack_mapping(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path(message_ts): Path<String>,
+) -> ApiResult<Value> {
+    let discord_info = redis_client
+        .get_discord_message(&message_ts)
+        .await?
+        .ok_or(ApiError::new(StatusCode::NOT_FOUND, "no mapping"))?;
+    let (discord_channel_id, message_ids) = parse_discord_message(&discord_info)
+        .ok_or_else(|| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "invalid mapping"))?;
+
+    Ok(Json(json!({
+        "discord_channel_id": discord_channel_id.to_string(),
+        "discord_message_ids": message_ids.iter().map(u64::to_string).collect::<Vec<_>>(),
+    })))
+}
+
+#[derive(Deserialize)]
+struct DeadLetterOptions {
+    limit: Option<usize>,
+}
+
+/// Events that failed for good, newest first.
+async fn list_dead_letters(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Query(options): Query<DeadLetterOptions>,
+) -> ApiResult<Vec<DeadLetter>> {
+    let limit = options.limit.unwrap_or(DEFAULT_DEAD_LETTER_LIMIT);
+    Ok(Json(redis_client.list_dead_letters(limit).await?))
+}
+
+async fn replay_dead_letters(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(bridge): Extension<Arc<BridgeChannels>>,
+) -> ApiResult<Value> {
+    let replayed = bridge::replay_dead_letters(&redis_client, &bridge).await?;
+    Ok(Json(json!({ "replayed": replayed })))
+}
+
+async fn clear_dead_letters(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+) -> ApiResult<Value> {
+    let cleared = redis_client.take_dead_letters().await?.len();
+    Ok(Json(json!({ "cleared": cleared })))
+}
@@ -1,19 +1,82 @@
-use tokio::sync::mpsc;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone)]
+use poise::futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::{Span, error, field, info, info_span, warn};
+
+use ::redis::RedisResult;
+
+use crate::metrics;
+use crate::redis::RedisClient;
+use crate::settings::{LinkSettings, LinkState};
+use crate::sources::discord::Error;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BridgeEvent {
     pub event_type: EventType,
     pub author_name: String,
     pub author_avatar: String,
+    /// The author's user ID on the platform the event came from, empty when unknown.
+    #[serde(default)]
+    pub author_id: String,
     pub channel_id: String,
     pub team_id: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub filename: String,
+    pub url: String,
+}
+
+/// The message a reply points at, as seen on the platform the reply came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyTo {
+    pub message_id: String,
+    pub author_name: String,
+    pub content: String,
+    pub url: String,
+}
+
+/// Rich content carried alongside message text, such as app layouts or bot embeds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Embed {
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub color: Option<u32>,
+    #[serde(default)]
+    pub fields: Vec<EmbedField>,
+    pub image_url: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub footer: Option<String>,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventType {
     MessageSent {
         message_id: String,
         content: String,
+        attachments: Vec<Attachment>,
+        thread_id: Option<String>,
+        #[serde(default)]
+        reply_to: Option<ReplyTo>,
+        #[serde(default)]
+        embeds: Vec<Embed>,
     },
     MessageDeleted {
         message_id: String,
@@ -21,27 +84,225 @@ pub enum EventType {
     MessageEdited {
         message_id: String,
         new_content: String,
+        #[serde(default)]
+        embeds: Vec<Embed>,
     },
     MessagePinned {
         message_id: String,
-        content: String,
     },
     MessageUnpinned {
         message_id: String,
+    },
+    /// `emoji` is unicode or `<:name:id>` from Discord, and a reaction name from Slack.
+    ReactionAdded {
+        message_id: String,
+        emoji: String,
+    },
+    ReactionRemoved {
+        message_id: String,
+        emoji: String,
+    },
+    /// Someone joined or left the channel.
+    MembershipNotice {
+        content: String,
+    },
+    /// The channel's topic or purpose changed.
+    ChannelNotice {
         content: String,
     },
 }
 
+impl EventType {
+    /// Short name for logs and metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EventType::MessageSent { .. } => "message_sent",
+            EventType::MessageDeleted { .. } => "message_deleted",
+            EventType::MessageEdited { .. } => "message_edited",
+            EventType::MessagePinned { .. } => "message_pinned",
+            EventType::MessageUnpinned { .. } => "message_unpinned",
+            EventType::ReactionAdded { .. } => "reaction_added",
+            EventType::ReactionRemoved { .. } => "reaction_removed",
+            EventType::MembershipNotice { .. } => "membership_notice",
+            EventType::ChannelNotice { .. } => "channel_notice",
+        }
+    }
+
+    /// The message the event is about, as an ID on the platform it came from.
+    pub fn message_id(&self) -> Option<&str> {
+        match self {
+            EventType::MessageSent { message_id, .. }
+            | EventType::MessageDeleted { message_id }
+            | EventType::MessageEdited { message_id, .. }
+            | EventType::MessagePinned { message_id }
+            | EventType::MessageUnpinned { message_id }
+            | EventType::ReactionAdded { message_id, .. }
+            | EventType::ReactionRemoved { message_id, .. } => Some(message_id),
+            EventType::MembershipNotice { .. } | EventType::ChannelNotice { .. } => None,
+        }
+    }
+}
+
+impl BridgeEvent {
+    /// Span for delivering the event, carrying the IDs it was logged with on the way in.
+    /// `link` is filled in once the link is known, and `outcome` if the event is dropped
+    /// or fails.
+    pub fn delivery_span(&self, direction: Direction) -> Span {
+        info_span!(
+            "deliver",
+            direction = direction.label(),
+            event_type = self.event_type.kind(),
+            team = %self.team_id,
+            channel = %self.channel_id,
+            message = self.event_type.message_id(),
+            trace = %trace_id(),
+            link = field::Empty,
+            outcome = field::Empty,
+        )
+    }
+}
+
+/// A short id that ties an error report to the delivery's log lines.
+fn trace_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(NEXT.fetch_add(1, Ordering::Relaxed));
+    format!("{:08x}", hasher.finish() as u32)
+}
+
+/// A stable fingerprint of what a message renders as, used to skip edits that wouldn't
+/// change the bridged copy. FNV-1a, so it survives restarts and upgrades.
+pub fn content_hash(content: &str, embeds: &[Embed]) -> String {
+    let embeds = serde_json::to_string(embeds).unwrap_or_default();
+
+    let hash = content
+        .bytes()
+        .chain([0])
+        .chain(embeds.bytes())
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    format!("{hash:016x}")
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Direction {
+    ToDiscord,
+    ToSlack,
+}
+
+impl Direction {
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::ToDiscord => "to_discord",
+            Direction::ToSlack => "to_slack",
+        }
+    }
+}
+
+/// An event held back while its link was paused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEvent {
+    pub direction: Direction,
+    pub event: BridgeEvent,
+}
+
+/// An event that couldn't be delivered, kept so it can be looked at and replayed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub direction: Direction,
+    pub event: BridgeEvent,
+    pub discord_channel_id: u64,
+    pub slack_channel_id: String,
+    pub reason: String,
+    /// Unix timestamp, in seconds.
+    pub failed_at: u64,
+}
+
+/// Longest wait between tries at a delivery that keeps failing for a reason that may pass.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Why a delivery failed. Permanent failures, like a message that's too long or a channel
+/// that's gone, would fail again and are dead lettered. The rest, like outages and rate
+/// limits, are tried again.
+#[derive(Debug, Clone)]
+pub struct DeliveryFailure {
+    /// One of a fixed few, for the failure metric: `too_long`, `channel_gone`, `rejected`,
+    /// `rate_limited` or `unavailable`.
+    pub kind: &'static str,
+    /// In words for the person whose message it was.
+    pub reason: String,
+    pub permanent: bool,
+    /// How long the platform asked us to wait, when it was a rate limit.
+    pub retry_after: Option<Duration>,
+}
+
+impl DeliveryFailure {
+    pub fn permanent(kind: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            reason: reason.into(),
+            permanent: true,
+            retry_after: None,
+        }
+    }
+
+    pub fn transient(kind: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            reason: reason.into(),
+            permanent: false,
+            retry_after: None,
+        }
+    }
+}
+
+/// Runs `send` until it succeeds or fails permanently. Waits between tries double from a
+/// second up to a minute, unless a rate limit says how long. Events behind this one wait
+/// too, so an outage holds them in order rather than failing each of them.
+pub async fn retry_transient<T, F, Fut>(mut send: F) -> Result<T, DeliveryFailure>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DeliveryFailure>>,
+{
+    let mut delay = Duration::from_secs(1);
+    loop {
+        match send().await {
+            Err(failure) if !failure.permanent => {
+                let wait = failure.retry_after.unwrap_or(delay);
+                warn!(reason = %failure.reason, wait = ?wait, "Delivery failed, trying again");
+                tokio::time::sleep(wait).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct BridgeChannels {
     pub to_discord: mpsc::UnboundedSender<BridgeEvent>,
     pub to_slack: mpsc::UnboundedSender<BridgeEvent>,
 }
 
+impl BridgeChannels {
+    pub fn sender(&self, direction: Direction) -> &mpsc::UnboundedSender<BridgeEvent> {
+        match direction {
+            Direction::ToDiscord => &self.to_discord,
+            Direction::ToSlack => &self.to_slack,
+        }
+    }
+}
+
+/// A consumer's end of the bridge, shared so a restarted consumer picks up where the
+/// last one stopped.
+pub type BridgeReceiver = Arc<Mutex<mpsc::UnboundedReceiver<BridgeEvent>>>;
+
 pub fn create_bridge() -> (
     BridgeChannels,
-    mpsc::UnboundedReceiver<BridgeEvent>, // discord receiver
-    mpsc::UnboundedReceiver<BridgeEvent>, // slack receiver
+    BridgeReceiver, // discord receiver
+    BridgeReceiver, // slack receiver
 ) {
     let (discord_tx, discord_rx) = mpsc::unbounded_channel();
     let (slack_tx, slack_rx) = mpsc::unbounded_channel();
@@ -51,5 +312,217 @@ pub fn create_bridge() -> (
         to_slack: slack_tx,
     };
 
-    (channels, discord_rx, slack_rx)
+    (
+        channels,
+        Arc::new(Mutex::new(discord_rx)),
+        Arc::new(Mutex::new(slack_rx)),
+    )
+}
+
+/// Loads the link's state and settings, returning the settings if the event
+/// should be delivered now. Events on a paused link are dropped or queued, keeping at
+/// most `max_queued_events`.
+pub async fn check_link(
+    redis_client: &RedisClient,
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+    direction: Direction,
+    event: &BridgeEvent,
+    max_queued_events: usize,
+) -> Option<LinkSettings> {
+    let span = Span::current();
+    span.record("link", format!("{discord_channel_id}:{slack_channel_id}"));
+
+    let state = match redis_client
+        .get_link_state(discord_channel_id, slack_channel_id)
+        .await
+    {
+        Ok(state) => state,
+        Err(e) => {
+            error!(error = %e, "Failed to get link state");
+            return None;
+        }
+    };
+
+    match state {
+        LinkState::Active => {}
+        LinkState::Paused => {
+            span.record("outcome", "paused");
+            return None;
+        }
+        LinkState::Dead => {
+            span.record("outcome", "dead");
+            return None;
+        }
+        LinkState::Queueing => {
+            span.record("outcome", "queued");
+            let queued = QueuedEvent {
+                direction,
+                event: event.clone(),
+            };
+
+            match redis_client
+                .queue_event(
+                    discord_channel_id,
+                    slack_channel_id,
+                    &queued,
+                    max_queued_events,
+                )
+                .await
+            {
+                Ok(0) => {}
+                Ok(dropped) => {
+                    warn!(dropped, "Link queue is full, dropped the oldest events");
+                    metrics::queued_events_dropped(dropped);
+                }
+                Err(e) => error!(error = %e, "Failed to queue event"),
+            }
+
+            return None;
+        }
+    }
+
+    match redis_client
+        .get_link_settings(discord_channel_id, slack_channel_id)
+        .await
+    {
+        Ok(settings) if settings.allows(&event.event_type) => Some(settings),
+        Ok(_) => {
+            span.record("outcome", "filtered");
+            None
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to get link settings");
+            None
+        }
+    }
+}
+
+/// Marks the delivery being handled as failed.
+pub fn record_failure() {
+    Span::current().record("outcome", "failed");
+}
+
+/// Keeps an event that failed for good, so it isn't only in the logs.
+pub async fn dead_letter(
+    redis_client: &RedisClient,
+    direction: Direction,
+    event: &BridgeEvent,
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+    reason: &str,
+) {
+    record_failure();
+
+    let dead_letter = DeadLetter {
+        direction,
+        event: event.clone(),
+        discord_channel_id,
+        slack_channel_id: slack_channel_id.to_string(),
+        reason: reason.to_string(),
+        failed_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+
+    if let Err(e) = redis_client.push_dead_letter(&dead_letter).await {
+        error!(error = %e, "Failed to store dead letter");
+    }
+}
+
+/// Sends every dead letter through the bridge again. Returns the number replayed.
+pub async fn replay_dead_letters(
+    redis_client: &RedisClient,
+    bridge: &BridgeChannels,
+) -> RedisResult<usize> {
+    let dead_letters = redis_client.take_dead_letters().await?;
+    let count = dead_letters.len();
+
+    // Oldest first, the order they originally happened in
+    for dead_letter in dead_letters.into_iter().rev() {
+        if let Err(e) = bridge.sender(dead_letter.direction).send(dead_letter.event) {
+            error!(error = %e, "Failed to send bridge event");
+        }
+    }
+
+    Ok(count)
+}
+
+/// Replays the dead letters whenever `carmine replay-dlq` asks, until `stop` is cancelled.
+pub async fn listen_for_replays(
+    redis_client: RedisClient,
+    bridge: BridgeChannels,
+    stop: CancellationToken,
+) -> Result<(), Error> {
+    let mut requests = redis_client.replay_requests().await?;
+
+    loop {
+        tokio::select! {
+            request = requests.next() => {
+                if request.is_none() {
+                    return Err("Replay subscription closed".into());
+                }
+                let replayed = replay_dead_letters(&redis_client, &bridge).await?;
+                info!(replayed, "Replayed dead letters on request");
+            }
+            () = stop.cancelled() => return Ok(()),
+        }
+    }
+}
+
+/// Marks the link active again and replays any events queued while it was paused.
+/// Returns the number of replayed events.
+pub async fn resume_link(
+    redis_client: &RedisClient,
+    bridge: &BridgeChannels,
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+) -> RedisResult<usize> {
+    // Sent straight away, so they're ahead of anything that arrives once the link is live
+    let queued = redis_client
+        .activate_link(discord_channel_id, slack_channel_id)
+        .await?;
+    let count = queued.len();
+
+    for QueuedEvent { direction, event } in queued {
+        if let Err(e) = bridge.sender(direction).send(event) {
+            error!(error = %e, "Failed to send bridge event");
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_and_permanent_ones_are_not() {
+        let tries = AtomicUsize::new(0);
+        let result = retry_transient(|| async {
+            match tries.fetch_add(1, Ordering::Relaxed) {
+                0 => Err(DeliveryFailure {
+                    retry_after: Some(Duration::from_millis(1)),
+                    ..DeliveryFailure::transient("unavailable", "outage")
+                }),
+                _ => Ok("sent"),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "sent");
+        assert_eq!(tries.load(Ordering::Relaxed), 2);
+
+        let tries = AtomicUsize::new(0);
+        let result: Result<(), _> = retry_transient(|| async {
+            tries.fetch_add(1, Ordering::Relaxed);
+            Err(DeliveryFailure::permanent("too_long", "too long"))
+        })
+        .await;
+        assert_eq!(result.unwrap_err().reason, "too long");
+        assert_eq!(tries.load(Ordering::Relaxed), 1);
+    }
 }
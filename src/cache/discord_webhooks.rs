@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude::{
+    self as serenity, CreateWebhook, EditWebhookMessage, ExecuteWebhook, builder::Builder,
+};
+use tokio::sync::RwLock;
+use tracing::{error, warn};
+
+use crate::metrics;
+use crate::redis::RedisClient;
+
+const WEBHOOK_NAME: &str = "carmine";
+
+/// Discord error code for a webhook that no longer exists.
+pub const UNKNOWN_WEBHOOK: isize = 10015;
+/// Discord error code for a channel that already has 15 webhooks.
+const MAX_WEBHOOKS_REACHED: isize = 30007;
+
+/// Enough of a webhook to post through it without fetching it first.
+#[derive(Debug, Clone)]
+pub struct CachedWebhook {
+    pub id: serenity::WebhookId,
+    pub token: String,
+}
+
+impl CachedWebhook {
+    fn from_webhook(webhook: &serenity::Webhook) -> Option<Self> {
+        // The token is only exposed through the webhook's URL, which fails without one
+        let url = webhook.url().ok()?;
+        let (_, token) = url.rsplit_once('/')?;
+
+        Some(Self {
+            id: webhook.id,
+            token: token.to_string(),
+        })
+    }
+
+    fn from_stored(stored: &str) -> Option<Self> {
+        let (id, token) = stored.split_once(':')?;
+
+        Some(Self {
+            id: serenity::WebhookId::new(id.parse().ok()?),
+            token: token.to_string(),
+        })
+    }
+
+    fn to_stored(&self) -> String {
+        format!("{}:{}", self.id, self.token)
+    }
+
+    pub async fn execute(
+        &self,
+        http: &serenity::Http,
+        builder: ExecuteWebhook,
+    ) -> serenity::Result<Option<serenity::Message>> {
+        metrics::api_call("discord", "execute_webhook");
+        builder.execute(http, (self.id, &self.token, true)).await
+    }
+
+    pub async fn edit_message(
+        &self,
+        http: &serenity::Http,
+        message_id: serenity::MessageId,
+        builder: EditWebhookMessage,
+    ) -> serenity::Result<serenity::Message> {
+        metrics::api_call("discord", "edit_webhook_message");
+        builder
+            .execute(http, (self.id, &self.token, message_id))
+            .await
+    }
+}
+
+/// Keeps the webhook used for each channel in memory and in Redis, so bridged messages
+/// don't need to list the channel's webhooks first.
+pub struct WebhookCache {
+    redis_client: RedisClient,
+    webhooks: RwLock<HashMap<u64, CachedWebhook>>,
+}
+
+impl WebhookCache {
+    pub fn new(redis_client: RedisClient) -> Self {
+        Self {
+            redis_client,
+            webhooks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get(&self, http: &serenity::Http, channel_id: u64) -> Option<CachedWebhook> {
+        if let Some(webhook) = self.webhooks.read().await.get(&channel_id) {
+            return Some(webhook.clone());
+        }
+
+        let stored = match self.redis_client.get_discord_webhook(channel_id).await {
+            Ok(stored) => stored.as_deref().and_then(CachedWebhook::from_stored),
+            Err(e) => {
+                error!(error = %e, "Error fetching cached webhook");
+                None
+            }
+        };
+
+        let webhook = match stored {
+            Some(webhook) => webhook,
+            None => {
+                let webhook = find_or_create_webhook(http, channel_id).await?;
+                if let Err(e) = self
+                    .redis_client
+                    .set_discord_webhook(channel_id, &webhook.to_stored())
+                    .await
+                {
+                    error!(error = %e, "Error caching webhook");
+                }
+
+                webhook
+            }
+        };
+
+        self.webhooks
+            .write()
+            .await
+            .insert(channel_id, webhook.clone());
+
+        Some(webhook)
+    }
+
+    /// Forgets a channel's webhook so the next message finds or creates a fresh one.
+    pub async fn invalidate(&self, channel_id: u64) {
+        self.webhooks.write().await.remove(&channel_id);
+
+        if let Err(e) = self.redis_client.delete_discord_webhook(channel_id).await {
+            error!(error = %e, "Error invalidating cached webhook");
+        }
+    }
+}
+
+async fn find_or_create_webhook(http: &serenity::Http, channel_id: u64) -> Option<CachedWebhook> {
+    let channel = serenity::ChannelId::new(channel_id);
+
+    metrics::api_call("discord", "get_channel_webhooks");
+    let webhooks = match channel.webhooks(http).await {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            error!(error = %e, "Failed to get webhooks");
+            return None;
+        }
+    };
+
+    // Check for existing webhook
+    if let Some(existing) = webhooks
+        .iter()
+        .filter(|w| w.name.as_deref() == Some(WEBHOOK_NAME))
+        .find_map(CachedWebhook::from_webhook)
+    {
+        return Some(existing);
+    }
+
+    // Create new webhook
+    metrics::api_call("discord", "create_webhook");
+    match channel
+        .create_webhook(http, CreateWebhook::new(WEBHOOK_NAME))
+        .await
+    {
+        Ok(webhook) => CachedWebhook::from_webhook(&webhook),
+        Err(e) if is_discord_error(&e, MAX_WEBHOOKS_REACHED) => {
+            // Borrow another incoming webhook rather than dropping messages,
+            // the username and avatar are overridden per message anyway
+            let borrowed = webhooks
+                .iter()
+                .filter(|w| w.kind == serenity::WebhookType::Incoming)
+                .find_map(CachedWebhook::from_webhook);
+            if borrowed.is_none() {
+                warn!(
+                    channel = channel_id,
+                    "Channel has reached Discord's webhook limit, remove one so messages can be bridged"
+                );
+            }
+
+            borrowed
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create webhook");
+            None
+        }
+    }
+}
+
+/// Whether a request failed with the given Discord JSON error code.
+pub fn is_discord_error(error: &serenity::Error, code: isize) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+            if response.error.code == code
+    )
+}
@@ -0,0 +1,4 @@
+pub mod discord_webhooks;
+pub mod slack_channels;
+pub mod slack_emoji;
+pub mod slack_users;
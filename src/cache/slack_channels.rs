@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use slack_morphism::prelude::*;
+use tokio::sync::RwLock;
+
+use crate::slack_client::SlackApiClient;
+
+/// How long a fetched channel list is reused before asking Slack again.
+const CHANNEL_LIST_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone)]
+pub struct SlackChannel {
+    pub id: String,
+    pub name: String,
+}
+
+/// Caches the result of `conversations.list` so autocomplete doesn't hit Slack on every keystroke.
+/// Private channels and group DMs only show up once the bot has been invited to them.
+#[derive(Default)]
+pub struct SlackChannelCache {
+    cached: RwLock<Option<(Instant, Arc<[SlackChannel]>)>>,
+}
+
+impl SlackChannelCache {
+    pub async fn channels(
+        &self,
+        slack_client: &SlackApiClient,
+        slack_token: &SlackApiToken,
+    ) -> ClientResult<Arc<[SlackChannel]>> {
+        if let Some((fetched_at, channels)) = &*self.cached.read().await
+            && fetched_at.elapsed() < CHANNEL_LIST_TTL
+        {
+            return Ok(channels.clone());
+        }
+
+        self.refresh(slack_client, slack_token).await
+    }
+
+    async fn refresh(
+        &self,
+        slack_client: &SlackApiClient,
+        slack_token: &SlackApiToken,
+    ) -> ClientResult<Arc<[SlackChannel]>> {
+        let channels: Arc<[SlackChannel]> = fetch_channels(slack_client, slack_token).await?.into();
+        *self.cached.write().await = Some((Instant::now(), channels.clone()));
+
+        Ok(channels)
+    }
+
+    /// Finds a channel by ID or by name, with or without a leading `#`. A miss refreshes the
+    /// list once in case the channel was created or we were invited since it was fetched.
+    pub async fn find(
+        &self,
+        slack_client: &SlackApiClient,
+        slack_token: &SlackApiToken,
+        query: &str,
+    ) -> ClientResult<Option<SlackChannel>> {
+        let query = query.trim().trim_start_matches('#');
+        let lookup = |channels: &[SlackChannel]| {
+            channels
+                .iter()
+                .find(|c| c.id == query)
+                .or_else(|| channels.iter().find(|c| c.name.eq_ignore_ascii_case(query)))
+                .cloned()
+        };
+
+        if let Some(channel) = lookup(&self.channels(slack_client, slack_token).await?) {
+            return Ok(Some(channel));
+        }
+
+        Ok(lookup(&self.refresh(slack_client, slack_token).await?))
+    }
+}
+
+async fn fetch_channels(
+    slack_client: &SlackApiClient,
+    slack_token: &SlackApiToken,
+) -> ClientResult<Vec<SlackChannel>> {
+    let session = slack_client.open_session(slack_token);
+
+    let mut channels = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut request = SlackApiConversationsListRequest::new()
+            .with_exclude_archived(true)
+            .with_limit(1000)
+            .with_types(vec![
+                SlackConversationType::Public,
+                SlackConversationType::Private,
+                SlackConversationType::Mpim,
+            ]);
+        if let Some(cursor) = cursor {
+            request = request.with_cursor(cursor);
+        }
+
+        let response = session.conversations_list(&request).await?;
+        channels.extend(response.channels.into_iter().filter_map(|channel| {
+            Some(SlackChannel {
+                id: channel.id.to_string(),
+                name: channel.name?,
+            })
+        }));
+
+        match response.response_metadata.and_then(|m| m.next_cursor) {
+            Some(next_cursor) if !next_cursor.0.is_empty() => cursor = Some(next_cursor),
+            _ => break,
+        }
+    }
+
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(channels)
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use slack_morphism::prelude::*;
+use tokio::sync::RwLock;
+use tracing::error;
+
+use crate::slack_client::SlackApiClient;
+
+/// How long a fetched emoji list is reused before asking Slack again.
+const EMOJI_LIST_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// How long to wait before asking again after `emoji.list` failed.
+const EMOJI_LIST_RETRY: Duration = Duration::from_secs(30);
+
+/// Custom emoji names mapped to their image URLs.
+pub type SlackEmoji = Arc<HashMap<String, String>>;
+
+/// Caches the workspace's custom emoji from `emoji.list`, as name to image URL.
+#[derive(Default)]
+pub struct SlackEmojiCache {
+    /// The list, and when to fetch it again.
+    cached: RwLock<Option<(Instant, SlackEmoji)>>,
+}
+
+impl SlackEmojiCache {
+    /// Returns the custom emoji, or an empty list if Slack can't be reached.
+    pub async fn emoji(
+        &self,
+        slack_client: &SlackApiClient,
+        slack_token: &SlackApiToken,
+    ) -> SlackEmoji {
+        if let Some((refresh_at, emoji)) = &*self.cached.read().await
+            && Instant::now() < *refresh_at
+        {
+            return emoji.clone();
+        }
+
+        let session = slack_client.open_session(slack_token);
+        let (emoji, ttl) = match session.emoji_list().await {
+            Ok(response) => (Arc::new(resolve_aliases(response.emoji)), EMOJI_LIST_TTL),
+            Err(e) => {
+                error!(error = %e, "Failed to list Slack emoji");
+                // Keep serving a stale list rather than nothing, and try again soon
+                let stale = self
+                    .cached
+                    .read()
+                    .await
+                    .as_ref()
+                    .map(|(_, emoji)| emoji.clone());
+                (stale.unwrap_or_default(), EMOJI_LIST_RETRY)
+            }
+        };
+
+        *self.cached.write().await = Some((Instant::now() + ttl, emoji.clone()));
+        emoji
+    }
+}
+
+/// Points aliases straight at the image they name. Aliases of standard emoji are
+/// dropped since the shortcode table already covers them.
+fn resolve_aliases(emoji: HashMap<SlackEmojiName, SlackEmojiRef>) -> HashMap<String, String> {
+    let lookup = |name: &SlackEmojiName| emoji.get(name);
+
+    emoji
+        .iter()
+        .filter_map(|(name, reference)| {
+            let mut reference = reference;
+            // Alias chains are short, but don't loop forever on a cycle
+            for _ in 0..5 {
+                match reference {
+                    SlackEmojiRef::Url(url) => return Some((name.to_string(), url.to_string())),
+                    SlackEmojiRef::Alias(target) => reference = lookup(target)?,
+                }
+            }
+            None
+        })
+        .collect()
+}
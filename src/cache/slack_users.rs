@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use slack_morphism::errors::SlackClientError;
+use slack_morphism::prelude::*;
+use tokio::sync::{OnceCell, RwLock};
+use tracing::error;
+
+use crate::redis::RedisClient;
+use crate::slack_client::SlackApiClient;
+
+/// How long a profile is kept in memory before checking Redis again.
+const MEMORY_TTL: Duration = Duration::from_secs(5 * 60);
+/// How long a profile is kept in Redis before asking Slack again.
+const REDIS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The parts of a Slack user's profile the bridge shows on Discord.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackProfile {
+    pub name: String,
+    pub avatar: String,
+}
+
+impl SlackProfile {
+    fn from_user(user: &SlackUser) -> Self {
+        let profile = user.profile.as_ref();
+        let non_empty = |name: &Option<String>| name.clone().filter(|name| !name.is_empty());
+
+        // Prefer what people see in Slack over their handle
+        let name = profile
+            .and_then(|p| non_empty(&p.display_name))
+            .or_else(|| profile.and_then(|p| non_empty(&p.real_name)))
+            .or_else(|| non_empty(&user.real_name))
+            .or_else(|| non_empty(&user.name))
+            .unwrap_or_else(|| format!("User-{}", user.id));
+
+        let avatar = profile
+            .and_then(|p| p.icon.as_ref())
+            .and_then(|i| i.image_original.clone())
+            .unwrap_or_default();
+
+        Self { name, avatar }
+    }
+}
+
+/// Who the bridge itself is on Slack, as `auth.test` reports it.
+#[derive(Debug, Clone)]
+pub struct OwnBot {
+    pub user_id: SlackUserId,
+    pub bot_id: Option<SlackBotId>,
+}
+
+/// Caches user profiles in memory and in Redis so messages don't each need a `users.info` call.
+pub struct SlackUserCache {
+    redis_client: RedisClient,
+    profiles: RwLock<HashMap<(String, String), (Instant, SlackProfile)>>,
+    own_bot: OnceCell<OwnBot>,
+}
+
+impl SlackUserCache {
+    pub fn new(redis_client: RedisClient) -> Self {
+        Self {
+            redis_client,
+            profiles: RwLock::new(HashMap::new()),
+            own_bot: OnceCell::new(),
+        }
+    }
+
+    /// The bridge's own user and bot IDs, looked up once. A failed lookup is tried again
+    /// on the next call.
+    pub async fn own_bot(
+        &self,
+        slack_client: &SlackApiClient,
+        slack_token: &SlackApiToken,
+    ) -> Option<&OwnBot> {
+        let own_bot = self
+            .own_bot
+            .get_or_try_init(|| async {
+                let session = slack_client.open_session(slack_token);
+                let response = session.auth_test().await?;
+
+                Ok::<_, SlackClientError>(OwnBot {
+                    user_id: response.user_id,
+                    bot_id: response.bot_id,
+                })
+            })
+            .await;
+
+        match own_bot {
+            Ok(own_bot) => Some(own_bot),
+            Err(e) => {
+                error!(error = %e, "Failed to identify bot user");
+                None
+            }
+        }
+    }
+
+    pub async fn profile(
+        &self,
+        slack_client: &SlackApiClient,
+        slack_token: &SlackApiToken,
+        team_id: &str,
+        user_id: &SlackUserId,
+    ) -> Option<SlackProfile> {
+        let key = (team_id.to_string(), user_id.to_string());
+
+        if let Some((cached_at, profile)) = self.profiles.read().await.get(&key)
+            && cached_at.elapsed() < MEMORY_TTL
+        {
+            return Some(profile.clone());
+        }
+
+        let profile = match self
+            .redis_client
+            .get_slack_profile(team_id, user_id.as_ref())
+            .await
+        {
+            Ok(Some(profile)) => profile,
+            result => {
+                if let Err(e) = result {
+                    error!(error = %e, "Error fetching cached Slack profile");
+                }
+
+                let session = slack_client.open_session(slack_token);
+                let response = match session
+                    .users_info(&SlackApiUsersInfoRequest::new(user_id.clone()))
+                    .await
+                {
+                    Ok(response) => response,
+                    Err(e) => {
+                        error!(error = %e, user = %user_id, "Error fetching Slack user");
+                        return None;
+                    }
+                };
+
+                let profile = SlackProfile::from_user(&response.user);
+                if let Err(e) = self
+                    .redis_client
+                    .set_slack_profile(team_id, user_id.as_ref(), &profile, REDIS_TTL)
+                    .await
+                {
+                    error!(error = %e, "Error caching Slack profile");
+                }
+
+                profile
+            }
+        };
+
+        let mut profiles = self.profiles.write().await;
+        profiles.retain(|_, (cached_at, _)| cached_at.elapsed() < MEMORY_TTL);
+        profiles.insert(key, (Instant::now(), profile.clone()));
+
+        Some(profile)
+    }
+
+    /// Forgets a user's profile, e.g. after Slack tells us it changed.
+    pub async fn invalidate(&self, team_id: &str, user_id: &SlackUserId) {
+        self.profiles
+            .write()
+            .await
+            .remove(&(team_id.to_string(), user_id.to_string()));
+
+        if let Err(e) = self
+            .redis_client
+            .delete_slack_profile(team_id, user_id.as_ref())
+            .await
+        {
+            error!(error = %e, "Error invalidating Slack profile");
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::bridge::{DeadLetter, QueuedEvent};
+use crate::commands::link::verify_and_join_slack_channel;
+use crate::commands::list::describe_links;
+use crate::config::Config;
+use crate::redis::RedisClient;
+use crate::settings::{LinkSettings, LinkState};
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::Error;
+use crate::{doctor, migrations};
+
+/// Bumped whenever the export format changes in a way older builds can't read.
+const EXPORT_VERSION: u32 = 1;
+
+/// Bridges Slack and Discord channels. Every command reads the same config as the bridge,
+/// from carmine.toml (or CARMINE_CONFIG) and the environment.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the bridge. This is the default.
+    Run,
+    /// List, add or remove linked channels.
+    Links {
+        #[command(subcommand)]
+        command: LinksCommand,
+    },
+    /// Bring the Redis key schema up to date.
+    Migrate {
+        /// Only report what would change.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check Redis, Slack, Discord and every link, failing if anything's wrong.
+    Doctor,
+    /// Write every link with its settings and queued events, plus the dead letters, as JSON.
+    Export {
+        /// Where to write it, instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Restore what `carmine export` wrote. Links whose channels are already linked, and
+    /// dead letters already stored, are left alone.
+    Import {
+        /// Where to read it from, instead of stdin.
+        input: Option<PathBuf>,
+    },
+    /// Ask the running bridge to replay the dead letters.
+    ReplayDlq,
+}
+
+#[derive(Subcommand)]
+pub enum LinksCommand {
+    /// List every link and its state.
+    List,
+    /// Link a Discord channel to a Slack channel, joining the Slack channel if needed.
+    Add {
+        discord_channel_id: u64,
+        slack_channel_id: String,
+    },
+    /// Remove a link.
+    Remove {
+        discord_channel_id: u64,
+        slack_channel_id: String,
+        /// Also forget which messages were bridged, so old ones can't be edited or replied to.
+        #[arg(long)]
+        purge: bool,
+    },
+}
+
+/// Runs anything but `carmine run`.
+pub async fn execute(command: Command, config: Arc<Config>) -> Result<(), Error> {
+    let redis_client = RedisClient::new(&config.redis_url).await?;
+
+    match command {
+        Command::Run => unreachable!("the bridge runs from main"),
+        Command::Links { command } => links(command, &config, &redis_client).await,
+        Command::Migrate { dry_run } => migrate(&redis_client, dry_run).await,
+        Command::Doctor => doctor::run(&config, &redis_client, &slack_client()?).await,
+        Command::Export { output } => export(&redis_client, output).await,
+        Command::Import { input } => import(&redis_client, &config, input).await,
+        Command::ReplayDlq => replay_dlq(&redis_client).await,
+    }
+}
+
+fn slack_client() -> Result<SlackApiClient, Error> {
+    Ok(crate::slack_client::new_client()?)
+}
+
+async fn links(
+    command: LinksCommand,
+    config: &Config,
+    redis_client: &RedisClient,
+) -> Result<(), Error> {
+    match command {
+        LinksCommand::List => {
+            let links = describe_links(redis_client, |discord_channel_id, slack_channel_id| {
+                format!("Discord {discord_channel_id} ↔ Slack {slack_channel_id}")
+            })
+            .await?;
+            println!("{links}");
+        }
+        LinksCommand::Add {
+            discord_channel_id,
+            slack_channel_id,
+        } => {
+            if redis_client
+                .get_linked_slack_channel(discord_channel_id)
+                .await?
+                .is_some()
+                || redis_client
+                    .get_linked_discord_channel(&slack_channel_id)
+                    .await?
+                    .is_some()
+            {
+                return Err("One of the channels is already linked".into());
+            }
+
+            // Same as the link commands: make sure we can post on the Slack side first
+            let channel_name = verify_and_join_slack_channel(
+                &slack_client()?,
+                &config.slack_token(),
+                &slack_channel_id,
+            )
+            .await?;
+            redis_client
+                .link_channels(discord_channel_id, &slack_channel_id)
+                .await?;
+            println!(
+                "Linked Discord channel {discord_channel_id} to Slack channel #{channel_name}"
+            );
+        }
+        LinksCommand::Remove {
+            discord_channel_id,
+            slack_channel_id,
+            purge,
+        } => {
+            let linked = redis_client
+                .get_linked_slack_channel(discord_channel_id)
+                .await?;
+            if linked.as_deref() != Some(slack_channel_id.as_str()) {
+                return Err("No such link".into());
+            }
+
+            redis_client
+                .unlink_channels(discord_channel_id, &slack_channel_id, purge)
+                .await?;
+            println!(
+                "Unlinked Discord channel {discord_channel_id} from Slack channel {slack_channel_id}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn migrate(redis_client: &RedisClient, dry_run: bool) -> Result<(), Error> {
+    let pending = migrations::pending(redis_client).await?;
+    if pending.is_empty() {
+        println!(
+            "Schema is up to date at version {}",
+            migrations::CURRENT_VERSION
+        );
+        return Ok(());
+    }
+
+    for migration in pending {
+        let changed = migrations::apply(redis_client, migration, dry_run).await?;
+        let verb = if dry_run { "would change" } else { "changed" };
+        println!(
+            "{}: {} ({} {verb})",
+            migration.version,
+            migration.description,
+            plural(changed, "record")
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+struct Export {
+    version: u32,
+    links: Vec<ExportedLink>,
+    /// Newest first, as they're stored.
+    dead_letters: Vec<DeadLetter>,
+}
+
+/// Message mappings stay behind, so edits and replies to messages bridged before the
+/// import won't carry over.
+#[derive(Serialize, Deserialize)]
+struct ExportedLink {
+    discord_channel_id: String,
+    slack_channel_id: String,
+    state: String,
+    settings: Map<String, Value>,
+    #[serde(default)]
+    queued: Vec<QueuedEvent>,
+}
+
+async fn export(redis_client: &RedisClient, output: Option<PathBuf>) -> Result<(), Error> {
+    let mut links = Vec::new();
+    for (discord_channel_id, slack_channel_id) in redis_client.list_links().await? {
+        let state = redis_client
+            .get_link_state(discord_channel_id, &slack_channel_id)
+            .await?;
+        // Dead links only linger for their history, which isn't exported
+        if state == LinkState::Dead {
+            continue;
+        }
+
+        links.push(ExportedLink {
+            discord_channel_id: discord_channel_id.to_string(),
+            state: state.key().to_string(),
+            settings: redis_client
+                .get_link_settings(discord_channel_id, &slack_channel_id)
+                .await?
+                .to_json(),
+            queued: redis_client
+                .peek_queued_events(discord_channel_id, &slack_channel_id)
+                .await?,
+            slack_channel_id,
+        });
+    }
+
+    let dead_letter_count = redis_client.count_dead_letters().await?;
+    let export = Export {
+        version: EXPORT_VERSION,
+        links,
+        dead_letters: redis_client.list_dead_letters(dead_letter_count).await?,
+    };
+
+    let json = serde_json::to_string_pretty(&export)?;
+    match output {
+        Some(path) => std::fs::write(&path, json)?,
+        None => writeln!(std::io::stdout(), "{json}")?,
+    }
+    eprintln!(
+        "Exported {} and {}",
+        plural(export.links.len(), "link"),
+        plural(export.dead_letters.len(), "dead letter")
+    );
+
+    Ok(())
+}
+
+async fn import(
+    redis_client: &RedisClient,
+    config: &Config,
+    input: Option<PathBuf>,
+) -> Result<(), Error> {
+    let json = match input {
+        Some(path) => std::fs::read_to_string(&path)?,
+        None => {
+            let mut json = String::new();
+            std::io::stdin().read_to_string(&mut json)?;
+            json
+        }
+    };
+    let export: Export = serde_json::from_str(&json)?;
+    if export.version != EXPORT_VERSION {
+        return Err(format!(
+            "Can't import version {} exports, only version {EXPORT_VERSION}",
+            export.version
+        )
+        .into());
+    }
+
+    let mut imported = 0;
+    for link in export.links {
+        let label = format!(
+            "Discord {} ↔ Slack {}",
+            link.discord_channel_id, link.slack_channel_id
+        );
+        if let Err(e) = import_link(redis_client, &link, config.max_queued_events).await? {
+            eprintln!("Skipped {label}: {e}");
+            continue;
+        }
+        imported += 1;
+    }
+
+    // Dead letters already here, most likely from importing the same export before
+    let dead_letter_count = redis_client.count_dead_letters().await?;
+    let mut existing = HashSet::new();
+    for dead_letter in redis_client.list_dead_letters(dead_letter_count).await? {
+        existing.insert(serde_json::to_string(&dead_letter)?);
+    }
+
+    // Pushed oldest first, so they end up stored newest first again
+    let mut imported_dead_letters = 0;
+    for dead_letter in export.dead_letters.iter().rev() {
+        if existing.contains(&serde_json::to_string(dead_letter)?) {
+            continue;
+        }
+        redis_client.push_dead_letter(dead_letter).await?;
+        imported_dead_letters += 1;
+    }
+
+    eprintln!(
+        "Imported {} and {}",
+        plural(imported, "link"),
+        plural(imported_dead_letters, "dead letter")
+    );
+    let skipped_dead_letters = export.dead_letters.len() - imported_dead_letters;
+    if skipped_dead_letters > 0 {
+        eprintln!(
+            "Skipped {} already in Redis",
+            plural(skipped_dead_letters, "dead letter")
+        );
+    }
+    Ok(())
+}
+
+/// Recreates a link, or says why it couldn't. Only Redis failures are errors.
+async fn import_link(
+    redis_client: &RedisClient,
+    link: &ExportedLink,
+    max_queued_events: usize,
+) -> Result<Result<(), String>, Error> {
+    let Ok(discord_channel_id) = link.discord_channel_id.parse::<u64>() else {
+        return Ok(Err("invalid discord_channel_id".to_string()));
+    };
+    let state = match LinkState::from_key(&link.state) {
+        Some(LinkState::Dead) | None => {
+            return Ok(Err(format!("can't restore a {} link", link.state)));
+        }
+        Some(state) => state,
+    };
+    let mut settings = LinkSettings::default();
+    if let Err(e) = settings.apply_json(&link.settings) {
+        return Ok(Err(e));
+    }
+
+    if redis_client
+        .get_linked_slack_channel(discord_channel_id)
+        .await?
+        .is_some()
+        || redis_client
+            .get_linked_discord_channel(&link.slack_channel_id)
+            .await?
+            .is_some()
+    {
+        return Ok(Err("already linked".to_string()));
+    }
+
+    redis_client
+        .link_channels(discord_channel_id, &link.slack_channel_id)
+        .await?;
+    redis_client
+        .set_link_settings(discord_channel_id, &link.slack_channel_id, settings)
+        .await?;
+    redis_client
+        .set_link_state(discord_channel_id, &link.slack_channel_id, state)
+        .await?;
+    for event in &link.queued {
+        redis_client
+            .queue_event(
+                discord_channel_id,
+                &link.slack_channel_id,
+                event,
+                max_queued_events,
+            )
+            .await?;
+    }
+
+    Ok(Ok(()))
+}
+
+async fn replay_dlq(redis_client: &RedisClient) -> Result<(), Error> {
+    let count = redis_client.count_dead_letters().await?;
+    if count == 0 {
+        println!("There are no dead letters");
+        return Ok(());
+    }
+
+    // Only the bridge can deliver, so it does the replaying
+    match redis_client.request_replay().await? {
+        0 => Err("No running bridge heard the request, is `carmine run` up?".into()),
+        _ => {
+            println!(
+                "Asked the bridge to replay {}",
+                plural(count, "dead letter")
+            );
+            Ok(())
+        }
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    }
+}
@@ -1,43 +1,166 @@
 use std::sync::Arc;
 
-use poise::serenity_prelude::CreateAllowedMentions;
+use poise::serenity_prelude::{self as serenity, AutocompleteChoice, CreateAllowedMentions};
 use slack_morphism::api::{SlackApiConversationsInfoRequest, SlackApiConversationsJoinRequest};
+use slack_morphism::errors::SlackClientError;
 use slack_morphism::events::{SlackCommandEvent, SlackCommandEventResponse};
-use slack_morphism::prelude::SlackHyperClient;
 use slack_morphism::{SlackApiToken, SlackChannelId, SlackMessageContent};
+use tracing::error;
 
+use crate::config::Config;
 use crate::redis::RedisClient;
-use crate::sources::discord::{Context, Error};
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::{Context, DiscordClient, Error};
 
 // Slack command
 pub async fn handle_link_channel(
     event: SlackCommandEvent,
     redis_client: Arc<RedisClient>,
+    slack_client: Arc<SlackApiClient>,
+    discord_client: DiscordClient,
+    config: Arc<Config>,
 ) -> SlackCommandEventResponse {
-    if let Some(channel_id) = event.text
-        && !channel_id.is_empty()
-        && let Ok(discord_channel_id) = channel_id.trim().parse::<u64>()
+    let query = event.text.as_deref().map(str::trim).unwrap_or_default();
+    if query.is_empty() {
+        return SlackCommandEventResponse::new(SlackMessageContent::new().with_text(
+            "Please provide a Discord channel as `#channel-name`, `server/channel` or a channel ID"
+                .into(),
+        ));
+    }
+
+    let discord_cache = discord_client
+        .borrow()
+        .as_ref()
+        .map(|handle| handle.ctx.cache.clone());
+    let (discord_channel_id, channel_label) =
+        match resolve_discord_channel(discord_cache.as_deref(), config.discord.guild_id, query) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                return SlackCommandEventResponse::new(SlackMessageContent::new().with_text(e));
+            }
+        };
+
+    // Make sure we can post here, private channels need an invite first
+    let slack_token = config.slack_token();
+    if let Err(e) =
+        verify_and_join_slack_channel(&slack_client, &slack_token, event.channel_id.as_ref()).await
     {
-        // Store the mapping in Redis
-        match redis_client
-            .link_channels(discord_channel_id, event.channel_id.as_ref())
-            .await
-        {
-            Ok(_) => SlackCommandEventResponse::new(SlackMessageContent::new().with_text(format!(
-                "Successfully linked Discord channel `{discord_channel_id}` to this Slack channel"
-            ))),
-            Err(e) => SlackCommandEventResponse::new(
-                SlackMessageContent::new().with_text(format!("Error linking Discord channel: {e}")),
-            ),
+        return SlackCommandEventResponse::new(
+            SlackMessageContent::new().with_text(format!("Error linking Discord channel: {e}")),
+        );
+    }
+
+    // Store the mapping in Redis
+    match redis_client
+        .link_channels(discord_channel_id, event.channel_id.as_ref())
+        .await
+    {
+        Ok(_) => SlackCommandEventResponse::new(SlackMessageContent::new().with_text(format!(
+            "Successfully linked Discord channel {channel_label} to <#{}>",
+            event.channel_id
+        ))),
+        Err(e) => SlackCommandEventResponse::new(
+            SlackMessageContent::new().with_text(format!("Error linking Discord channel: {e}")),
+        ),
+    }
+}
+
+/// Resolves a Discord channel from a channel ID, a `#channel-name` in the configured
+/// guild, or a `server/channel` path, returning its ID and a label for Slack.
+fn resolve_discord_channel(
+    cache: Option<&serenity::Cache>,
+    guild_id: u64,
+    query: &str,
+) -> Result<(u64, String), String> {
+    // Raw IDs still work, even before the Discord cache is ready
+    if let Ok(channel_id) = query.parse::<u64>() {
+        let label = cache
+            .and_then(|cache| describe_discord_channel(cache, channel_id))
+            .unwrap_or_else(|| format!("`{channel_id}`"));
+        return Ok((channel_id, label));
+    }
+
+    let cache = cache.ok_or("Discord is still connecting, please try again in a moment")?;
+
+    let (guild_ids, channel_name) = match query.split_once('/') {
+        Some((guild_name, channel_name)) => {
+            let guild_name = guild_name.trim();
+            let guild_ids: Vec<_> = cache
+                .guilds()
+                .into_iter()
+                .filter(|guild_id| {
+                    cache
+                        .guild(*guild_id)
+                        .is_some_and(|guild| guild.name.eq_ignore_ascii_case(guild_name))
+                })
+                .collect();
+            if guild_ids.is_empty() {
+                return Err(format!(
+                    "Couldn't find a Discord server named `{guild_name}`"
+                ));
+            }
+
+            (guild_ids, channel_name)
+        }
+        None => (vec![serenity::GuildId::new(guild_id)], query),
+    };
+
+    let channel_name = slack_escaped_name(channel_name.trim())
+        .trim()
+        .trim_start_matches('#');
+
+    let matches: Vec<u64> = guild_ids
+        .into_iter()
+        .filter_map(|guild_id| cache.guild(guild_id))
+        .flat_map(|guild| {
+            guild
+                .channels
+                .values()
+                .filter(|channel| {
+                    matches!(
+                        channel.kind,
+                        serenity::ChannelType::Text | serenity::ChannelType::News
+                    ) && channel.name.eq_ignore_ascii_case(channel_name)
+                })
+                .map(|channel| channel.id.get())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [channel_id] => {
+            let label = describe_discord_channel(cache, *channel_id)
+                .unwrap_or_else(|| format!("`{channel_id}`"));
+            Ok((*channel_id, label))
         }
-    } else {
-        SlackCommandEventResponse::new(
-            SlackMessageContent::new()
-                .with_text("Please provide a valid Discord channel ID".into()),
-        )
+        [] => Err(format!(
+            "Couldn't find a Discord channel named `#{channel_name}`"
+        )),
+        _ => Err(format!(
+            "More than one Discord channel is named `#{channel_name}`, use `server/channel` or the channel ID"
+        )),
     }
 }
 
+/// Slack turns `#name` into `<#C123|name>` when the command escapes channels.
+fn slack_escaped_name(text: &str) -> &str {
+    text.strip_prefix("<#")
+        .and_then(|rest| rest.strip_suffix('>'))
+        .and_then(|rest| rest.split_once('|'))
+        .map_or(text, |(_, name)| name)
+}
+
+fn describe_discord_channel(cache: &serenity::Cache, channel_id: u64) -> Option<String> {
+    let channel_id = serenity::ChannelId::new(channel_id);
+
+    cache.guilds().into_iter().find_map(|guild_id| {
+        let guild = cache.guild(guild_id)?;
+        let channel = guild.channels.get(&channel_id)?;
+
+        Some(format!("*#{}* in *{}*", channel.name, guild.name))
+    })
+}
+
 // Discord command
 #[poise::command(
     prefix_command,
@@ -46,13 +169,28 @@ pub async fn handle_link_channel(
 )]
 pub async fn link_channel(
     ctx: Context<'_>,
-    #[description = "Slack channel ID (e.g., C1234567890)"] slack_channel_id: String,
+    #[description = "Slack channel name or ID (e.g., #general or C1234567890)"]
+    #[autocomplete = "autocomplete_slack_channel"]
+    slack_channel: String,
 ) -> Result<(), Error> {
     let data = ctx.data();
     let redis_client = &data.redis_client;
 
-    let oauth_token = std::env::var("SLACK_OAUTH_TOKEN").expect("SLACK_OAUTH_TOKEN must be set");
-    let slack_token = SlackApiToken::new(oauth_token.into());
+    let slack_token = data.config.slack_token();
+
+    // Resolve names through the channel list, anything else is treated as an ID
+    let slack_channel_id = match data
+        .slack_channels
+        .find(&data.slack_client, &slack_token, &slack_channel)
+        .await
+    {
+        Ok(Some(channel)) => channel.id,
+        Ok(None) => slack_channel.trim().trim_start_matches('#').to_string(),
+        Err(e) => {
+            error!(error = %e, "Failed to list Slack channels");
+            slack_channel.trim().to_string()
+        }
+    };
 
     match verify_and_join_slack_channel(&data.slack_client, &slack_token, &slack_channel_id).await {
         Ok(channel_name) => {
@@ -64,7 +202,8 @@ pub async fn link_channel(
             ctx.send(
                 poise::CreateReply::default()
                     .content(format!(
-                        "Successfully linked Slack channel **`{channel_name}`** to this Discord channel",
+                        "Successfully linked Slack channel **#{channel_name}** (`{slack_channel_id}`) to <#{}>",
+                        ctx.channel_id()
                     ))
                     .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
                     .reply(true),
@@ -87,8 +226,35 @@ pub async fn link_channel(
     }
 }
 
-async fn verify_and_join_slack_channel(
-    client: &SlackHyperClient,
+async fn autocomplete_slack_channel(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let data = ctx.data();
+
+    let slack_token = data.config.slack_token();
+
+    let channels = match data
+        .slack_channels
+        .channels(&data.slack_client, &slack_token)
+        .await
+    {
+        Ok(channels) => channels,
+        Err(e) => {
+            error!(error = %e, "Failed to list Slack channels");
+            return Vec::new();
+        }
+    };
+
+    let partial = partial.trim().trim_start_matches('#').to_lowercase();
+    channels
+        .iter()
+        .filter(|channel| channel.name.to_lowercase().contains(&partial))
+        // Discord shows at most 25 choices
+        .take(25)
+        .map(|channel| AutocompleteChoice::new(format!("#{}", channel.name), channel.id.clone()))
+        .collect()
+}
+
+pub async fn verify_and_join_slack_channel(
+    client: &SlackApiClient,
     token: &SlackApiToken,
     channel_id: &str,
 ) -> Result<String, String> {
@@ -96,28 +262,43 @@ async fn verify_and_join_slack_channel(
     let channel_info_req =
         SlackApiConversationsInfoRequest::new(SlackChannelId::new(channel_id.to_string()));
 
-    // Verify the channel exists and get its name
+    // Verify the channel exists and get its name. Private channels we weren't
+    // invited to look like they don't exist at all.
     let channel_info = session
         .conversations_info(&channel_info_req)
         .await
-        .map_err(|e| format!("Failed to get channel info: {e}"))?;
+        .map_err(|e| match e {
+            SlackClientError::ApiError(api_error) if api_error.code == "channel_not_found" => {
+                format!(
+                    "Couldn't find Slack channel `{channel_id}`. If it's private, invite @carmine to it first"
+                )
+            }
+            e => format!("Failed to get channel info: {e}"),
+        })?;
     let channel_name = channel_info
         .channel
         .name
         .unwrap_or("Unknown".to_string())
         .to_string();
+    let flags = &channel_info.channel.flags;
+
+    if flags.is_member == Some(true) {
+        return Ok(channel_name);
+    }
+
+    // Private channels and group DMs can't be joined, someone has to invite us
+    if flags.is_private == Some(true) || flags.is_mpim == Some(true) {
+        return Err(format!(
+            "'{channel_name}' is private, invite @carmine to it first"
+        ));
+    }
 
     // Join the channel if not already a member
-    match channel_info.channel.flags.is_member {
-        Some(true) => Ok(channel_name),
-        _ => {
-            let join_req =
-                SlackApiConversationsJoinRequest::new(SlackChannelId::new(channel_id.to_string()));
-
-            match session.conversations_join(&join_req).await {
-                Ok(_) => Ok(channel_name),
-                Err(e) => Err(format!("Failed to join channel '{channel_name}': {e}")),
-            }
-        }
+    let join_req =
+        SlackApiConversationsJoinRequest::new(SlackChannelId::new(channel_id.to_string()));
+
+    match session.conversations_join(&join_req).await {
+        Ok(_) => Ok(channel_name),
+        Err(e) => Err(format!("Failed to join channel '{channel_name}': {e}")),
     }
 }
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use ::redis::RedisResult;
+use poise::serenity_prelude::CreateAllowedMentions;
+use slack_morphism::{
+    SlackMessageContent,
+    events::{SlackCommandEvent, SlackCommandEventResponse},
+};
+
+use crate::{
+    redis::RedisClient,
+    sources::discord::{Context, Error},
+};
+
+/// Lists every link along with its state, one per line.
+pub async fn describe_links(
+    redis_client: &RedisClient,
+    format_link: impl Fn(u64, &str) -> String,
+) -> RedisResult<String> {
+    let links = redis_client.list_links().await?;
+    if links.is_empty() {
+        return Ok("There are no linked channels".to_string());
+    }
+
+    let mut lines = Vec::with_capacity(links.len());
+    for (discord_channel_id, slack_channel_id) in links {
+        let state = redis_client
+            .get_link_state(discord_channel_id, &slack_channel_id)
+            .await?;
+
+        lines.push(format!(
+            "{} ({})",
+            format_link(discord_channel_id, &slack_channel_id),
+            state.describe()
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+// Slack command
+pub async fn handle_list_links(
+    _event: SlackCommandEvent,
+    redis_client: Arc<RedisClient>,
+) -> SlackCommandEventResponse {
+    let links = describe_links(&redis_client, |discord_channel_id, slack_channel_id| {
+        format!("<#{slack_channel_id}> ↔ Discord channel `{discord_channel_id}`")
+    })
+    .await;
+
+    match links {
+        Ok(text) => SlackCommandEventResponse::new(SlackMessageContent::new().with_text(text)),
+        Err(e) => SlackCommandEventResponse::new(
+            SlackMessageContent::new().with_text(format!("Error listing links: {e}")),
+        ),
+    }
+}
+
+// Discord command
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized("en-US", "List all linked channels and their state.")
+)]
+pub async fn list_links(ctx: Context<'_>) -> Result<(), Error> {
+    let content = describe_links(
+        &ctx.data().redis_client,
+        |discord_channel_id, slack_channel_id| {
+            format!("<#{discord_channel_id}> ↔ Slack channel **`{slack_channel_id}`**")
+        },
+    )
+    .await?;
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
 pub mod general;
 pub mod link;
+pub mod list;
+pub mod pause;
+pub mod settings;
 pub mod unlink;
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::CreateAllowedMentions;
+use slack_morphism::{
+    SlackMessageContent,
+    events::{SlackCommandEvent, SlackCommandEventResponse},
+};
+
+use crate::{
+    bridge::{self, BridgeChannels},
+    redis::RedisClient,
+    settings::LinkState,
+    sources::discord::{Context, Error},
+};
+
+// Slack commands
+pub async fn handle_pause_link(
+    event: SlackCommandEvent,
+    redis_client: Arc<RedisClient>,
+) -> SlackCommandEventResponse {
+    let channel_id = event.channel_id.to_string();
+    let state = match event.text.as_deref().map(str::trim) {
+        Some("queue") => LinkState::Queueing,
+        _ => LinkState::Paused,
+    };
+
+    // See if there's a linked Discord channel
+    if let Ok(Some(discord_channel_str)) =
+        redis_client.get_linked_discord_channel(&channel_id).await
+        && let Ok(discord_channel_id) = discord_channel_str.trim().parse::<u64>()
+    {
+        match redis_client
+            .set_link_state(discord_channel_id, &channel_id, state)
+            .await
+        {
+            Ok(_) => SlackCommandEventResponse::new(SlackMessageContent::new().with_text(format!(
+                "{} the link with Discord channel `{discord_channel_id}`",
+                pause_verb(state)
+            ))),
+            Err(e) => SlackCommandEventResponse::new(
+                SlackMessageContent::new().with_text(format!("Error pausing link: {e}")),
+            ),
+        }
+    } else {
+        SlackCommandEventResponse::new(
+            SlackMessageContent::new()
+                .with_text("This Slack channel is not linked to any Discord channel".into()),
+        )
+    }
+}
+
+pub async fn handle_resume_link(
+    event: SlackCommandEvent,
+    redis_client: Arc<RedisClient>,
+    bridge: Arc<BridgeChannels>,
+) -> SlackCommandEventResponse {
+    let channel_id = event.channel_id.to_string();
+
+    // See if there's a linked Discord channel
+    if let Ok(Some(discord_channel_str)) =
+        redis_client.get_linked_discord_channel(&channel_id).await
+        && let Ok(discord_channel_id) = discord_channel_str.trim().parse::<u64>()
+    {
+        match bridge::resume_link(&redis_client, &bridge, discord_channel_id, &channel_id).await {
+            Ok(count) => {
+                SlackCommandEventResponse::new(SlackMessageContent::new().with_text(format!(
+                    "Resumed the link with Discord channel `{discord_channel_id}`{}",
+                    replay_note(count)
+                )))
+            }
+            Err(e) => SlackCommandEventResponse::new(
+                SlackMessageContent::new().with_text(format!("Error resuming link: {e}")),
+            ),
+        }
+    } else {
+        SlackCommandEventResponse::new(
+            SlackMessageContent::new()
+                .with_text("This Slack channel is not linked to any Discord channel".into()),
+        )
+    }
+}
+
+// Discord commands
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized("en-US", "Pause this channel's link without unlinking it.")
+)]
+pub async fn pause_link(
+    ctx: Context<'_>,
+    #[description = "Queue events and deliver them on resume instead of dropping them"]
+    queue: Option<bool>,
+) -> Result<(), Error> {
+    let redis_client = &ctx.data().redis_client;
+    let channel_id: u64 = ctx.channel_id().into();
+    let state = if queue.unwrap_or(false) {
+        LinkState::Queueing
+    } else {
+        LinkState::Paused
+    };
+
+    let content = match redis_client.get_linked_slack_channel(channel_id).await? {
+        Some(slack_channel_id) => {
+            redis_client
+                .set_link_state(channel_id, &slack_channel_id, state)
+                .await?;
+
+            format!(
+                "{} the link with Slack channel **`{slack_channel_id}`**",
+                pause_verb(state)
+            )
+        }
+        None => "This Discord channel is not linked to any Slack channel".to_string(),
+    };
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized("en-US", "Resume this channel's paused link.")
+)]
+pub async fn resume_link(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let channel_id: u64 = ctx.channel_id().into();
+
+    let content = match data
+        .redis_client
+        .get_linked_slack_channel(channel_id)
+        .await?
+    {
+        Some(slack_channel_id) => {
+            let count = bridge::resume_link(
+                &data.redis_client,
+                &data.bridge,
+                channel_id,
+                &slack_channel_id,
+            )
+            .await?;
+
+            format!(
+                "Resumed the link with Slack channel **`{slack_channel_id}`**{}",
+                replay_note(count)
+            )
+        }
+        None => "This Discord channel is not linked to any Slack channel".to_string(),
+    };
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn pause_verb(state: LinkState) -> &'static str {
+    match state {
+        LinkState::Queueing => "Paused (queueing events)",
+        _ => "Paused (dropping events)",
+    }
+}
+
+fn replay_note(count: usize) -> String {
+    match count {
+        0 => String::new(),
+        1 => " and delivered 1 queued event".to_string(),
+        n => format!(" and delivered {n} queued events"),
+    }
+}
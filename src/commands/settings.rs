@@ -0,0 +1,393 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use poise::ChoiceParameter;
+use poise::serenity_prelude::CreateAllowedMentions;
+use slack_morphism::prelude::*;
+use tracing::{error, warn};
+
+use crate::config::Config;
+use crate::redis::RedisClient;
+use crate::settings::{FailureFeedback, LinkSettings, LongMessages, Setting};
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::{Context, Error};
+
+pub const SETTINGS_CALLBACK_ID: &str = "carmine_settings";
+const SETTINGS_BLOCK_ID: &str = "settings";
+const SETTINGS_ACTION_ID: &str = "enabled";
+const LONG_MESSAGES_BLOCK_ID: &str = "long_messages";
+const LONG_MESSAGES_ACTION_ID: &str = "policy";
+const FAILURE_FEEDBACK_BLOCK_ID: &str = "failure_feedback";
+const FAILURE_FEEDBACK_ACTION_ID: &str = "mode";
+
+// Slack command
+pub async fn handle_settings(
+    event: SlackCommandEvent,
+    redis_client: Arc<RedisClient>,
+    slack_client: Arc<SlackApiClient>,
+    config: Arc<Config>,
+) -> SlackCommandEventResponse {
+    let slack_channel_id = event.channel_id.to_string();
+
+    // See if there's a linked Discord channel
+    if let Ok(Some(discord_channel_str)) = redis_client
+        .get_linked_discord_channel(&slack_channel_id)
+        .await
+        && let Ok(discord_channel_id) = discord_channel_str.trim().parse::<u64>()
+    {
+        let settings = match redis_client
+            .get_link_settings(discord_channel_id, &slack_channel_id)
+            .await
+        {
+            Ok(settings) => settings,
+            Err(e) => {
+                return SlackCommandEventResponse::new(
+                    SlackMessageContent::new().with_text(format!("Error loading settings: {e}")),
+                );
+            }
+        };
+
+        let slack_token = config.slack_token();
+        let session = slack_client.open_session(&slack_token);
+
+        let view = settings_modal(discord_channel_id, &slack_channel_id, settings);
+        match session
+            .views_open(&SlackApiViewsOpenRequest::new(event.trigger_id, view))
+            .await
+        {
+            Ok(_) => SlackCommandEventResponse::new(
+                SlackMessageContent::new().with_text("Opening link settings...".into()),
+            ),
+            Err(e) => SlackCommandEventResponse::new(
+                SlackMessageContent::new().with_text(format!("Error opening settings: {e}")),
+            ),
+        }
+    } else {
+        SlackCommandEventResponse::new(
+            SlackMessageContent::new()
+                .with_text("This Slack channel is not linked to any Discord channel".into()),
+        )
+    }
+}
+
+// Slack modal submission
+pub async fn handle_settings_submission(
+    event: SlackInteractionViewSubmissionEvent,
+    redis_client: Arc<RedisClient>,
+) {
+    let SlackView::Modal(modal) = &event.view.view else {
+        return;
+    };
+
+    // The modal carries the link it was opened for
+    let Some((discord_channel_id, slack_channel_id)) = modal
+        .private_metadata
+        .as_deref()
+        .and_then(|metadata| metadata.split_once(':'))
+        .and_then(|(discord, slack)| Some((discord.parse::<u64>().ok()?, slack.to_string())))
+    else {
+        warn!(
+            metadata = ?modal.private_metadata,
+            "Invalid settings modal metadata"
+        );
+        return;
+    };
+
+    let state_value = |block_id: &str, action_id: &str| {
+        event
+            .view
+            .state_params
+            .state
+            .as_ref()
+            .and_then(|state| state.values.get(&block_id.into()))
+            .and_then(|block| block.get(&action_id.into()))
+    };
+
+    let selected: HashSet<String> = state_value(SETTINGS_BLOCK_ID, SETTINGS_ACTION_ID)
+        .and_then(|value| value.selected_options.as_ref())
+        .map(|options| options.iter().map(|o| o.value.clone()).collect())
+        .unwrap_or_default();
+
+    let mut settings = LinkSettings::default();
+    for setting in Setting::ALL {
+        settings.set(setting, selected.contains(setting.key()));
+    }
+    if let Some(policy) = state_value(LONG_MESSAGES_BLOCK_ID, LONG_MESSAGES_ACTION_ID)
+        .and_then(|value| value.selected_option.as_ref())
+        .and_then(|option| LongMessages::from_key(&option.value))
+    {
+        settings.long_messages = policy;
+    }
+    if let Some(mode) = state_value(FAILURE_FEEDBACK_BLOCK_ID, FAILURE_FEEDBACK_ACTION_ID)
+        .and_then(|value| value.selected_option.as_ref())
+        .and_then(|option| FailureFeedback::from_key(&option.value))
+    {
+        settings.failure_feedback = mode;
+    }
+
+    // The link may have been removed while the modal was open
+    match redis_client
+        .get_linked_slack_channel(discord_channel_id)
+        .await
+    {
+        Ok(Some(linked)) if linked == slack_channel_id => {}
+        Ok(_) => {
+            warn!(
+                link = %format!("{discord_channel_id}:{slack_channel_id}"),
+                "Ignoring settings for a link that no longer exists"
+            );
+            return;
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to look up link");
+            return;
+        }
+    }
+
+    if let Err(e) = redis_client
+        .set_link_settings(discord_channel_id, &slack_channel_id, settings)
+        .await
+    {
+        error!(error = %e, "Failed to store link settings");
+    }
+}
+
+fn settings_modal(
+    discord_channel_id: u64,
+    slack_channel_id: &str,
+    settings: LinkSettings,
+) -> SlackView {
+    let option = |setting: Setting| {
+        SlackBlockChoiceItem::new(
+            SlackBlockPlainText::from(setting.name()).into(),
+            setting.key().to_string(),
+        )
+    };
+
+    let options: Vec<_> = Setting::ALL.into_iter().map(option).collect();
+    let initial_options: Vec<_> = Setting::ALL
+        .into_iter()
+        .filter(|setting| settings.get(*setting))
+        .map(option)
+        .collect();
+
+    let mut checkboxes = SlackBlockCheckboxesElement::new(SETTINGS_ACTION_ID.into(), options);
+    if !initial_options.is_empty() {
+        checkboxes = checkboxes.with_initial_options(initial_options);
+    }
+
+    let input = SlackInputBlock::new("Mirror across this link".into(), checkboxes.into())
+        .with_block_id(SETTINGS_BLOCK_ID.into())
+        .with_optional(true);
+
+    let policy_option = |policy: LongMessages| {
+        SlackBlockChoiceItem::new(
+            SlackBlockPlainText::from(policy.name()).into(),
+            policy.key().to_string(),
+        )
+    };
+    let policies = SlackBlockRadioButtonsElement::new(
+        LONG_MESSAGES_ACTION_ID.into(),
+        LongMessages::ALL.into_iter().map(policy_option).collect(),
+    )
+    .with_initial_option(policy_option(settings.long_messages));
+
+    let long_messages =
+        SlackInputBlock::new("Messages too long for Discord".into(), policies.into())
+            .with_block_id(LONG_MESSAGES_BLOCK_ID.into());
+
+    let mode_option = |mode: FailureFeedback| {
+        SlackBlockChoiceItem::new(
+            SlackBlockPlainText::from(mode.name()).into(),
+            mode.key().to_string(),
+        )
+    };
+    let modes = SlackBlockRadioButtonsElement::new(
+        FAILURE_FEEDBACK_ACTION_ID.into(),
+        FailureFeedback::ALL.into_iter().map(mode_option).collect(),
+    )
+    .with_initial_option(mode_option(settings.failure_feedback));
+
+    let failure_feedback =
+        SlackInputBlock::new("Tell authors when a message fails".into(), modes.into())
+            .with_block_id(FAILURE_FEEDBACK_BLOCK_ID.into());
+
+    SlackView::Modal(
+        SlackModalView::new(
+            "Link settings".into(),
+            vec![input.into(), long_messages.into(), failure_feedback.into()],
+        )
+        .with_submit("Save".into())
+        .with_close("Cancel".into())
+        .with_callback_id(SETTINGS_CALLBACK_ID.into())
+        .with_private_metadata(format!("{discord_channel_id}:{slack_channel_id}")),
+    )
+}
+
+// Discord commands
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("show", "set", "long_messages", "failure_feedback"),
+    subcommand_required,
+    description_localized("en-US", "Manage what gets mirrored across this channel's link.")
+)]
+pub async fn settings(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized("en-US", "Show the settings for this channel's link.")
+)]
+async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let redis_client = &ctx.data().redis_client;
+    let channel_id: u64 = ctx.channel_id().into();
+
+    let content = match redis_client.get_linked_slack_channel(channel_id).await? {
+        Some(slack_channel_id) => {
+            let settings = redis_client
+                .get_link_settings(channel_id, &slack_channel_id)
+                .await?;
+
+            format!(
+                "Settings for the link with Slack channel **`{slack_channel_id}`**:\n{}",
+                settings.describe()
+            )
+        }
+        None => "This Discord channel is not linked to any Slack channel".to_string(),
+    };
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized("en-US", "Change a setting for this channel's link.")
+)]
+async fn set(
+    ctx: Context<'_>,
+    #[description = "Setting to change"] setting: Setting,
+    #[description = "Whether the setting is enabled"] enabled: bool,
+) -> Result<(), Error> {
+    let redis_client = &ctx.data().redis_client;
+    let channel_id: u64 = ctx.channel_id().into();
+
+    let content = match redis_client.get_linked_slack_channel(channel_id).await? {
+        Some(slack_channel_id) => {
+            let mut settings = redis_client
+                .get_link_settings(channel_id, &slack_channel_id)
+                .await?;
+            settings.set(setting, enabled);
+            redis_client
+                .set_link_settings(channel_id, &slack_channel_id, settings)
+                .await?;
+
+            let state = if enabled { "enabled" } else { "disabled" };
+            format!("**{}** is now {state} for this link", setting.name())
+        }
+        None => "This Discord channel is not linked to any Slack channel".to_string(),
+    };
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized(
+        "en-US",
+        "Choose what happens to messages too long for Discord on this channel's link."
+    )
+)]
+async fn long_messages(
+    ctx: Context<'_>,
+    #[description = "What to do with long messages"] policy: LongMessages,
+) -> Result<(), Error> {
+    let redis_client = &ctx.data().redis_client;
+    let channel_id: u64 = ctx.channel_id().into();
+
+    let content = match redis_client.get_linked_slack_channel(channel_id).await? {
+        Some(slack_channel_id) => {
+            let mut settings = redis_client
+                .get_link_settings(channel_id, &slack_channel_id)
+                .await?;
+            settings.long_messages = policy;
+            redis_client
+                .set_link_settings(channel_id, &slack_channel_id, settings)
+                .await?;
+
+            format!("Long messages on this link: **{}**", policy.name())
+        }
+        None => "This Discord channel is not linked to any Slack channel".to_string(),
+    };
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    description_localized(
+        "en-US",
+        "Choose how authors hear about messages that couldn't be bridged on this channel's link."
+    )
+)]
+async fn failure_feedback(
+    ctx: Context<'_>,
+    #[description = "How to tell authors"] mode: FailureFeedback,
+) -> Result<(), Error> {
+    let redis_client = &ctx.data().redis_client;
+    let channel_id: u64 = ctx.channel_id().into();
+
+    let content = match redis_client.get_linked_slack_channel(channel_id).await? {
+        Some(slack_channel_id) => {
+            let mut settings = redis_client
+                .get_link_settings(channel_id, &slack_channel_id)
+                .await?;
+            settings.failure_feedback = mode;
+            redis_client
+                .set_link_settings(channel_id, &slack_channel_id, settings)
+                .await?;
+
+            format!("Failure feedback on this link: **{}**", mode.name())
+        }
+        None => "This Discord channel is not linked to any Slack channel".to_string(),
+    };
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
+            .reply(true),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -11,12 +11,34 @@ use crate::{
     sources::discord::{Context, Error},
 };
 
+/// What happens to a link's message mappings when it's removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum History {
+    #[name = "Keep history"]
+    Keep,
+    #[name = "Purge mappings"]
+    Purge,
+}
+
+impl History {
+    fn note(self) -> &'static str {
+        match self {
+            History::Keep => "",
+            History::Purge => " and purged its message history",
+        }
+    }
+}
+
 // Slack command
 pub async fn handle_unlink_channel(
     event: SlackCommandEvent,
     redis_client: Arc<RedisClient>,
 ) -> SlackCommandEventResponse {
     let channel_id = event.channel_id.to_string();
+    let history = match event.text.as_deref().map(str::trim) {
+        Some("purge") => History::Purge,
+        _ => History::Keep,
+    };
 
     // See if there's a linked Discord channel
     if let Ok(Some(discord_channel_str)) =
@@ -25,11 +47,12 @@ pub async fn handle_unlink_channel(
     {
         // Remove the link from Redis
         match redis_client
-            .unlink_channels(discord_channel_id, &channel_id)
+            .unlink_channels(discord_channel_id, &channel_id, history == History::Purge)
             .await
         {
             Ok(_) => SlackCommandEventResponse::new(SlackMessageContent::new().with_text(format!(
-                "Successfully unlinked Discord channel `{discord_channel_id}` from this Slack channel"
+                "Successfully unlinked Discord channel `{discord_channel_id}` from this Slack channel{}",
+                history.note()
             ))),
             Err(e) => SlackCommandEventResponse::new(
                 SlackMessageContent::new()
@@ -50,9 +73,14 @@ pub async fn handle_unlink_channel(
     slash_command,
     description_localized("en-US", "Unlink a Slack channel from this Discord channel.")
 )]
-pub async fn unlink_channel(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn unlink_channel(
+    ctx: Context<'_>,
+    #[description = "Keep message history so edits and deletes resume on relink, or purge it"]
+    history: Option<History>,
+) -> Result<(), Error> {
     let data = ctx.data();
     let redis_client = &data.redis_client;
+    let history = history.unwrap_or(History::Keep);
 
     let channel_id: u64 = ctx.channel_id().into();
 
@@ -61,13 +89,14 @@ pub async fn unlink_channel(ctx: Context<'_>) -> Result<(), Error> {
         Some(slack_channel_id) => {
             // Remove the link from Redis
             redis_client
-                .unlink_channels(channel_id, &slack_channel_id)
+                .unlink_channels(channel_id, &slack_channel_id, history == History::Purge)
                 .await?;
 
             ctx.send(
                 poise::CreateReply::default()
                     .content(format!(
-                        "Successfully unlinked Slack channel **`{slack_channel_id}`** from this Discord channel"
+                        "Successfully unlinked Slack channel **`{slack_channel_id}`** from this Discord channel{}",
+                        history.note()
                     ))
                     .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
                     .reply(true),
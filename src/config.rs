@@ -0,0 +1,450 @@
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use redis::IntoConnectionInfo;
+use serde::Deserialize;
+use slack_morphism::prelude::SlackApiToken;
+
+const DEFAULT_PATH: &str = "carmine.toml";
+
+const DEFAULT_BOT_SCOPE: &str = "channels:history,channels:join,channels:read,chat:write,chat:write.customize,commands,emoji:read,groups:history,groups:read,mpim:history,mpim:read,pins:write,reactions:read,reactions:write,users:read";
+
+/// Settings for the whole bridge, read once at startup from `carmine.toml` (or the file
+/// named by `CARMINE_CONFIG`), with environment variables taking precedence.
+#[derive(Clone)]
+pub struct Config {
+    pub listen_addr: SocketAddr,
+    pub redis_url: String,
+    pub dead_link_retention: Duration,
+    /// Most events a paused link holds before dropping the oldest.
+    pub max_queued_events: usize,
+    pub log: LogConfig,
+    pub discord: DiscordConfig,
+    pub slack: SlackConfig,
+    pub admin: AdminConfig,
+    pub dashboard: DashboardConfig,
+}
+
+#[derive(Clone)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    pub unredacted: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Json,
+    Pretty,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(LogFormat::Json),
+            "pretty" => Ok(LogFormat::Pretty),
+            _ => Err(format!("expected \"json\" or \"pretty\", got \"{s}\"")),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DiscordConfig {
+    pub token: String,
+    pub guild_id: u64,
+}
+
+#[derive(Clone)]
+pub struct SlackConfig {
+    pub team_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub signing_secret: String,
+    pub bot_scope: String,
+    pub redirect_host: String,
+    pub oauth_token: String,
+}
+
+/// Where error reports go, and the bearer token for the `/admin` API. Either, both or
+/// neither channel may be set, and the API is off without a token.
+#[derive(Clone)]
+pub struct AdminConfig {
+    pub discord_channel_id: Option<u64>,
+    pub slack_channel_id: Option<String>,
+    pub api_token: Option<String>,
+}
+
+impl AdminConfig {
+    pub fn reports_enabled(&self) -> bool {
+        self.discord_channel_id.is_some() || self.slack_channel_id.is_some()
+    }
+}
+
+/// Who may sign in to the web dashboard, which is off until someone is listed. Slack
+/// sign-in reuses the app's own credentials; Discord needs the application's OAuth2 ones.
+#[derive(Clone)]
+pub struct DashboardConfig {
+    pub slack_admins: Vec<String>,
+    pub discord_admins: Vec<u64>,
+    pub discord_client_id: Option<u64>,
+    pub discord_client_secret: Option<String>,
+}
+
+impl DashboardConfig {
+    pub fn enabled(&self) -> bool {
+        !self.slack_admins.is_empty() || !self.discord_admins.is_empty()
+    }
+}
+
+impl Config {
+    /// Loads and validates the config, returning every problem found rather than the first.
+    pub fn load() -> Result<Config, Vec<String>> {
+        let mut errors = Vec::new();
+
+        let mut raw = RawConfig::read(&mut errors);
+        raw.apply_env(&mut errors);
+        let config = raw.validate(&mut errors);
+
+        match config {
+            Some(config) if errors.is_empty() => Ok(config),
+            _ => Err(errors),
+        }
+    }
+
+    pub fn slack_token(&self) -> SlackApiToken {
+        SlackApiToken::new(self.slack.oauth_token.clone().into())
+    }
+}
+
+/// The file as written, where anything may be missing until the environment fills it in.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    listen_addr: Option<SocketAddr>,
+    redis_url: Option<String>,
+    dead_link_retention_days: Option<u64>,
+    max_queued_events: Option<usize>,
+    log: RawLogConfig,
+    discord: RawDiscordConfig,
+    slack: RawSlackConfig,
+    admin: RawAdminConfig,
+    dashboard: RawDashboardConfig,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawLogConfig {
+    format: Option<LogFormat>,
+    unredacted: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawDiscordConfig {
+    token: Option<String>,
+    guild_id: Option<u64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawSlackConfig {
+    team_id: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    signing_secret: Option<String>,
+    bot_scope: Option<String>,
+    redirect_host: Option<String>,
+    oauth_token: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawAdminConfig {
+    discord_channel_id: Option<u64>,
+    slack_channel_id: Option<String>,
+    api_token: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawDashboardConfig {
+    slack_admins: Option<Vec<String>>,
+    discord_admins: Option<Vec<u64>>,
+    discord_client_id: Option<u64>,
+    discord_client_secret: Option<String>,
+}
+
+impl RawConfig {
+    /// A missing file is fine when the path wasn't asked for, since the environment
+    /// can supply everything.
+    fn read(errors: &mut Vec<String>) -> RawConfig {
+        let explicit = std::env::var("CARMINE_CONFIG").ok();
+        let path = explicit.as_deref().unwrap_or(DEFAULT_PATH);
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && explicit.is_none() => {
+                return RawConfig::default();
+            }
+            Err(e) => {
+                errors.push(format!("{path}: {e}"));
+                return RawConfig::default();
+            }
+        };
+
+        toml::from_str(&text).unwrap_or_else(|e| {
+            errors.push(format!("{path}: {e}"));
+            RawConfig::default()
+        })
+    }
+
+    fn apply_env(&mut self, errors: &mut Vec<String>) {
+        env("LISTEN_ADDR", &mut self.listen_addr, errors);
+        env("REDIS_URL", &mut self.redis_url, errors);
+        env(
+            "DEAD_LINK_RETENTION_DAYS",
+            &mut self.dead_link_retention_days,
+            errors,
+        );
+        env("MAX_QUEUED_EVENTS", &mut self.max_queued_events, errors);
+
+        env("LOG_FORMAT", &mut self.log.format, errors);
+        env("LOG_UNREDACTED", &mut self.log.unredacted, errors);
+
+        env("DISCORD_TOKEN", &mut self.discord.token, errors);
+        env("DISCORD_GUILD_ID", &mut self.discord.guild_id, errors);
+
+        env("SLACK_TEAM_ID", &mut self.slack.team_id, errors);
+        env("SLACK_CLIENT_ID", &mut self.slack.client_id, errors);
+        env("SLACK_CLIENT_SECRET", &mut self.slack.client_secret, errors);
+        env(
+            "SLACK_SIGNING_SECRET",
+            &mut self.slack.signing_secret,
+            errors,
+        );
+        env("SLACK_BOT_SCOPE", &mut self.slack.bot_scope, errors);
+        env("SLACK_REDIRECT_HOST", &mut self.slack.redirect_host, errors);
+        env("SLACK_OAUTH_TOKEN", &mut self.slack.oauth_token, errors);
+
+        env(
+            "ADMIN_DISCORD_CHANNEL_ID",
+            &mut self.admin.discord_channel_id,
+            errors,
+        );
+        env(
+            "ADMIN_SLACK_CHANNEL_ID",
+            &mut self.admin.slack_channel_id,
+            errors,
+        );
+        env("ADMIN_API_TOKEN", &mut self.admin.api_token, errors);
+
+        env_list(
+            "DASHBOARD_SLACK_ADMINS",
+            &mut self.dashboard.slack_admins,
+            errors,
+        );
+        env_list(
+            "DASHBOARD_DISCORD_ADMINS",
+            &mut self.dashboard.discord_admins,
+            errors,
+        );
+        env(
+            "DASHBOARD_DISCORD_CLIENT_ID",
+            &mut self.dashboard.discord_client_id,
+            errors,
+        );
+        env(
+            "DASHBOARD_DISCORD_CLIENT_SECRET",
+            &mut self.dashboard.discord_client_secret,
+            errors,
+        );
+    }
+
+    fn validate(self, errors: &mut Vec<String>) -> Option<Config> {
+        let redis_url = required("redis_url", "REDIS_URL", self.redis_url, errors);
+        if let Some(url) = &redis_url
+            && let Err(e) = url.as_str().into_connection_info()
+        {
+            errors.push(format!("redis_url: {e}"));
+        }
+
+        if self.dead_link_retention_days == Some(0) {
+            errors.push("dead_link_retention_days: must be at least 1".to_string());
+        }
+        // Redis takes the expiry as a signed number of seconds
+        let dead_link_retention = self
+            .dead_link_retention_days
+            .unwrap_or(30)
+            .checked_mul(24 * 60 * 60)
+            .filter(|&seconds| i64::try_from(seconds).is_ok())
+            .map(Duration::from_secs);
+        if dead_link_retention.is_none() {
+            errors.push("dead_link_retention_days: too many days".to_string());
+        }
+        if self.max_queued_events == Some(0) {
+            errors.push("max_queued_events: must be at least 1".to_string());
+        }
+
+        let discord_token = required("discord.token", "DISCORD_TOKEN", self.discord.token, errors);
+        let guild_id = required(
+            "discord.guild_id",
+            "DISCORD_GUILD_ID",
+            self.discord.guild_id,
+            errors,
+        );
+
+        let team_id = required("slack.team_id", "SLACK_TEAM_ID", self.slack.team_id, errors);
+        let client_id = required(
+            "slack.client_id",
+            "SLACK_CLIENT_ID",
+            self.slack.client_id,
+            errors,
+        );
+        let client_secret = required(
+            "slack.client_secret",
+            "SLACK_CLIENT_SECRET",
+            self.slack.client_secret,
+            errors,
+        );
+        let signing_secret = required(
+            "slack.signing_secret",
+            "SLACK_SIGNING_SECRET",
+            self.slack.signing_secret,
+            errors,
+        );
+        let redirect_host = required(
+            "slack.redirect_host",
+            "SLACK_REDIRECT_HOST",
+            self.slack.redirect_host,
+            errors,
+        );
+        let oauth_token = required(
+            "slack.oauth_token",
+            "SLACK_OAUTH_TOKEN",
+            self.slack.oauth_token,
+            errors,
+        );
+        // Anything short enough to guess would hand out every link
+        if let Some(token) = self.admin.api_token.as_deref().filter(|t| !t.is_empty())
+            && token.len() < 32
+        {
+            errors.push("admin.api_token: must be at least 32 characters".to_string());
+        }
+
+        let dashboard = DashboardConfig {
+            slack_admins: self.dashboard.slack_admins.unwrap_or_default(),
+            discord_admins: self.dashboard.discord_admins.unwrap_or_default(),
+            discord_client_id: self.dashboard.discord_client_id.filter(|&id| id != 0),
+            discord_client_secret: self
+                .dashboard
+                .discord_client_secret
+                .filter(|secret| !secret.is_empty()),
+        };
+        if !dashboard.discord_admins.is_empty()
+            && (dashboard.discord_client_id.is_none() || dashboard.discord_client_secret.is_none())
+        {
+            errors.push(
+                "dashboard.discord_admins: needs dashboard.discord_client_id and discord_client_secret"
+                    .to_string(),
+            );
+        }
+
+        // A user token would post as whoever installed the app
+        if let Some(token) = &oauth_token
+            && !token.starts_with("xoxb-")
+        {
+            errors.push("slack.oauth_token: expected a bot token starting with xoxb-".to_string());
+        }
+
+        Some(Config {
+            listen_addr: self
+                .listen_addr
+                .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 8080))),
+            redis_url: redis_url?,
+            dead_link_retention: dead_link_retention?,
+            max_queued_events: self.max_queued_events.unwrap_or(1000),
+            log: LogConfig {
+                format: self.log.format.unwrap_or_default(),
+                unredacted: self.log.unredacted.unwrap_or(false),
+            },
+            discord: DiscordConfig {
+                token: discord_token?,
+                guild_id: guild_id?,
+            },
+            slack: SlackConfig {
+                team_id: team_id?,
+                client_id: client_id?,
+                client_secret: client_secret?,
+                signing_secret: signing_secret?,
+                bot_scope: self
+                    .slack
+                    .bot_scope
+                    .unwrap_or_else(|| DEFAULT_BOT_SCOPE.to_string()),
+                redirect_host: redirect_host?,
+                oauth_token: oauth_token?,
+            },
+            admin: AdminConfig {
+                discord_channel_id: self.admin.discord_channel_id.filter(|&id| id != 0),
+                slack_channel_id: self.admin.slack_channel_id.filter(|id| !id.is_empty()),
+                api_token: self.admin.api_token.filter(|token| !token.is_empty()),
+            },
+            dashboard,
+        })
+    }
+}
+
+/// Overrides `value` with the environment variable `name`, if set.
+fn env<T>(name: &str, value: &mut Option<T>, errors: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Ok(raw) = std::env::var(name) else {
+        return;
+    };
+
+    match raw.parse() {
+        Ok(parsed) => *value = Some(parsed),
+        Err(e) => errors.push(format!("{name}: {e}")),
+    }
+}
+
+/// Like [`env`], for a comma-separated list. An empty variable clears the list.
+fn env_list<T>(name: &str, value: &mut Option<Vec<T>>, errors: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Ok(raw) = std::env::var(name) else {
+        return;
+    };
+
+    let parsed: Result<Vec<T>, _> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::parse)
+        .collect();
+    match parsed {
+        Ok(parsed) => *value = Some(parsed),
+        Err(e) => errors.push(format!("{name}: {e}")),
+    }
+}
+
+fn required<T>(key: &str, env: &str, value: Option<T>, errors: &mut Vec<String>) -> Option<T>
+where
+    T: PartialEq + Default,
+{
+    match value {
+        Some(value) if value != T::default() => Some(value),
+        _ => {
+            errors.push(format!("{key} must be set (or {env})"));
+            None
+        }
+    }
+}
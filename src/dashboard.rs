@@ -0,0 +1,605 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use axum::extract::{Path, Query, Request};
+use axum::http::header::{COOKIE, SET_COOKIE};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{AppendHeaders, Html, IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::{info, warn};
+
+use crate::admin::{self, ApiError, ApiResult, Link, load_link};
+use crate::bridge::Direction;
+use crate::cache::slack_channels::SlackChannelCache;
+use crate::config::Config;
+use crate::metrics::{self, TrafficHour};
+use crate::redis::RedisClient;
+use crate::reports::{self, Report};
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::DiscordClient;
+
+const PAGE: &str = include_str!("../assets/dashboard.html");
+
+const SESSION_COOKIE: &str = "carmine_session";
+const STATE_COOKIE: &str = "carmine_oauth_state";
+
+const SESSION_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// How long a sign-in can sit on the provider's consent screen.
+const STATE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Required on anything that changes state. A cross-site form can't set headers, so this
+/// keeps other pages from acting with an admin's cookie.
+const CSRF_HEADER: &str = "x-carmine-dashboard";
+
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+/// Who's signed in, as stored in Redis under the session cookie.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Session {
+    provider: Provider,
+    user_id: String,
+    name: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Provider {
+    Slack,
+    Discord,
+}
+
+impl Provider {
+    const ALL: [Provider; 2] = [Provider::Slack, Provider::Discord];
+
+    fn key(self) -> &'static str {
+        match self {
+            Provider::Slack => "slack",
+            Provider::Discord => "discord",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|provider| provider.key() == key)
+    }
+
+    /// Only offered when someone could actually get in with it.
+    fn enabled(self, config: &Config) -> bool {
+        match self {
+            Provider::Slack => !config.dashboard.slack_admins.is_empty(),
+            Provider::Discord => !config.dashboard.discord_admins.is_empty(),
+        }
+    }
+
+    fn redirect_uri(self, config: &Config) -> String {
+        format!(
+            "{}/dashboard/callback/{}",
+            config.slack.redirect_host.trim_end_matches('/'),
+            self.key()
+        )
+    }
+
+    fn authorize_url(self, config: &Config, state: &str) -> Url {
+        let redirect_uri = self.redirect_uri(config);
+        let result = match self {
+            Provider::Slack => Url::parse_with_params(
+                "https://slack.com/openid/connect/authorize",
+                [
+                    ("response_type", "code"),
+                    ("scope", "openid profile"),
+                    ("client_id", &config.slack.client_id),
+                    ("team", &config.slack.team_id),
+                    ("redirect_uri", &redirect_uri),
+                    ("state", state),
+                ],
+            ),
+            Provider::Discord => Url::parse_with_params(
+                "https://discord.com/oauth2/authorize",
+                [
+                    ("response_type", "code"),
+                    ("scope", "identify"),
+                    (
+                        "client_id",
+                        &config
+                            .dashboard
+                            .discord_client_id
+                            .unwrap_or_default()
+                            .to_string(),
+                    ),
+                    ("redirect_uri", &redirect_uri),
+                    ("state", state),
+                ],
+            ),
+        };
+        result.expect("authorize URLs are valid")
+    }
+}
+
+/// A small web UI over the admin API for people who'd rather not use curl, mounted under
+/// `/dashboard` when any dashboard admins are configured. Sign-in is through Slack or
+/// Discord, and only the configured admins get past it.
+pub fn router() -> Router {
+    let api = Router::new()
+        .route("/overview", get(overview))
+        .merge(admin::routes())
+        .route_layer(middleware::from_fn(require_session));
+
+    Router::new()
+        .route("/", get(page))
+        .route("/session", get(session_info))
+        .route("/login/{provider}", get(login))
+        .route("/callback/{provider}", get(callback))
+        .route("/logout", post(logout))
+        .nest("/api", api)
+}
+
+async fn page() -> Html<&'static str> {
+    Html(PAGE)
+}
+
+async fn require_session(
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let changes = request.method() != Method::GET;
+    if changes && !request.headers().contains_key(CSRF_HEADER) {
+        return ApiError::new(StatusCode::FORBIDDEN, "missing dashboard header").into_response();
+    }
+
+    let session = match current_session(&config, &redis_client, request.headers()).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return ApiError::new(StatusCode::UNAUTHORIZED, "not signed in").into_response();
+        }
+        Err(e) => return e.into_response(),
+    };
+
+    if changes {
+        info!(
+            provider = session.provider.key(),
+            user = %session.name,
+            method = %request.method(),
+            path = %request.uri().path(),
+            "Dashboard change"
+        );
+    }
+    next.run(request).await
+}
+
+/// The signed-in admin, if the session is still valid and they're still an admin.
+async fn current_session(
+    config: &Config,
+    redis_client: &RedisClient,
+    headers: &HeaderMap,
+) -> Result<Option<Session>, ApiError> {
+    let Some(token) = cookie(headers, SESSION_COOKIE) else {
+        return Ok(None);
+    };
+
+    let session = redis_client.get_dashboard_session(token).await?;
+    Ok(session.filter(|session| is_admin(config, session)))
+}
+
+fn is_admin(config: &Config, session: &Session) -> bool {
+    match session.provider {
+        Provider::Slack => config.dashboard.slack_admins.contains(&session.user_id),
+        Provider::Discord => session
+            .user_id
+            .parse()
+            .is_ok_and(|id| config.dashboard.discord_admins.contains(&id)),
+    }
+}
+
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Lax rather than strict, since the sign-in callback arrives from the provider's site.
+fn set_cookie(config: &Config, name: &str, value: &str, max_age: Duration) -> String {
+    let secure = if config.slack.redirect_host.starts_with("https://") {
+        "; Secure"
+    } else {
+        ""
+    };
+    format!(
+        "{name}={value}; Path=/dashboard; HttpOnly; SameSite=Lax; Max-Age={}{secure}",
+        max_age.as_secs()
+    )
+}
+
+fn random_token() -> String {
+    rand::random::<[u8; 32]>()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Whether someone is signed in, and how they could sign in if not.
+async fn session_info(
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    headers: HeaderMap,
+) -> ApiResult<Value> {
+    let session = current_session(&config, &redis_client, &headers).await?;
+    let providers: Vec<_> = Provider::ALL
+        .into_iter()
+        .filter(|provider| provider.enabled(&config))
+        .map(Provider::key)
+        .collect();
+
+    Ok(Json(json!({
+        "user": session.map(|session| json!({
+            "provider": session.provider,
+            "name": session.name,
+        })),
+        "providers": providers,
+    })))
+}
+
+async fn login(
+    Extension(config): Extension<Arc<Config>>,
+    Path(provider): Path<String>,
+) -> Result<Response, ApiError> {
+    let provider = Provider::from_key(&provider)
+        .filter(|provider| provider.enabled(&config))
+        .ok_or(ApiError::new(StatusCode::NOT_FOUND, "no such sign-in"))?;
+
+    let state = random_token();
+    Ok((
+        AppendHeaders([(
+            SET_COOKIE,
+            set_cookie(&config, STATE_COOKIE, &state, STATE_TTL),
+        )]),
+        Redirect::to(provider.authorize_url(&config, &state).as_str()),
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+struct Callback {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+async fn callback(
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Path(provider): Path<String>,
+    Query(callback): Query<Callback>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(provider) = Provider::from_key(&provider).filter(|p| p.enabled(&config)) else {
+        return sign_in_failed("That sign-in isn't available");
+    };
+    if let Some(error) = callback.error {
+        return sign_in_failed(&format!("Sign-in was cancelled ({error})"));
+    }
+
+    let expected_state = cookie(&headers, STATE_COOKIE).unwrap_or_default();
+    let (Some(code), Some(state)) = (callback.code, callback.state) else {
+        return sign_in_failed("Sign-in didn't finish, please try again");
+    };
+    if expected_state.is_empty() || !admin::tokens_match(&state, expected_state) {
+        return sign_in_failed("Sign-in expired, please try again");
+    }
+
+    let session = match identify(&config, provider, &code).await {
+        Ok(session) => session,
+        Err(e) => {
+            warn!(provider = provider.key(), error = %e, "Dashboard sign-in failed");
+            return sign_in_failed("Couldn't confirm who you are, please try again");
+        }
+    };
+    if !is_admin(&config, &session) {
+        warn!(
+            provider = provider.key(),
+            user_id = %session.user_id,
+            "Refused dashboard sign-in from a non-admin"
+        );
+        return sign_in_failed(&format!(
+            "{} isn't a Carmine admin. Ask one to add your {} user ID ({}).",
+            session.name,
+            provider.key(),
+            session.user_id
+        ));
+    }
+
+    let token = random_token();
+    if let Err(e) = redis_client
+        .set_dashboard_session(&token, &session, SESSION_TTL)
+        .await
+    {
+        return ApiError::from(e).into_response();
+    }
+    info!(provider = provider.key(), user = %session.name, "Dashboard sign-in");
+
+    (
+        AppendHeaders([
+            (
+                SET_COOKIE,
+                set_cookie(&config, SESSION_COOKIE, &token, SESSION_TTL),
+            ),
+            (
+                SET_COOKIE,
+                set_cookie(&config, STATE_COOKIE, "", Duration::ZERO),
+            ),
+        ]),
+        Redirect::to("/dashboard"),
+    )
+        .into_response()
+}
+
+/// Back to the dashboard, which shows the reason above the sign-in buttons.
+fn sign_in_failed(reason: &str) -> Response {
+    let url = Url::parse_with_params("http://carmine/dashboard", [("error", reason)])
+        .expect("dashboard URL is valid");
+    Redirect::to(&format!("/dashboard?{}", url.query().unwrap_or_default())).into_response()
+}
+
+#[derive(Deserialize)]
+struct SlackTokenResponse {
+    ok: bool,
+    error: Option<String>,
+    access_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SlackUserInfo {
+    ok: bool,
+    error: Option<String>,
+    #[serde(rename = "https://slack.com/user_id")]
+    user_id: Option<String>,
+    #[serde(rename = "https://slack.com/team_id")]
+    team_id: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiscordTokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct DiscordUser {
+    id: String,
+    username: String,
+    global_name: Option<String>,
+}
+
+/// Trades the sign-in code for the user it was issued to.
+async fn identify(config: &Config, provider: Provider, code: &str) -> Result<Session, String> {
+    let redirect_uri = provider.redirect_uri(config);
+
+    match provider {
+        Provider::Slack => {
+            let token: SlackTokenResponse = HTTP
+                .post("https://slack.com/api/openid.connect.token")
+                .form(&[
+                    ("client_id", config.slack.client_id.as_str()),
+                    ("client_secret", &config.slack.client_secret),
+                    ("code", code),
+                    ("redirect_uri", &redirect_uri),
+                ])
+                .send()
+                .await
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+            let access_token = match token {
+                SlackTokenResponse {
+                    ok: true,
+                    access_token: Some(access_token),
+                    ..
+                } => access_token,
+                token => return Err(token.error.unwrap_or_else(|| "no token".to_string())),
+            };
+
+            let user: SlackUserInfo = HTTP
+                .get("https://slack.com/api/openid.connect.userInfo")
+                .bearer_auth(access_token)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !user.ok {
+                return Err(user.error.unwrap_or_else(|| "no user".to_string()));
+            }
+            // The admin list names users in our workspace, not whoever shares their ID
+            if user.team_id.as_deref() != Some(config.slack.team_id.as_str()) {
+                return Err("signed in to another workspace".to_string());
+            }
+
+            let user_id = user.user_id.ok_or("no user ID")?;
+            Ok(Session {
+                provider,
+                name: user.name.unwrap_or_else(|| user_id.clone()),
+                user_id,
+            })
+        }
+        Provider::Discord => {
+            let client_id = config
+                .dashboard
+                .discord_client_id
+                .unwrap_or_default()
+                .to_string();
+            let token: DiscordTokenResponse = HTTP
+                .post("https://discord.com/api/oauth2/token")
+                .form(&[
+                    ("client_id", client_id.as_str()),
+                    (
+                        "client_secret",
+                        config
+                            .dashboard
+                            .discord_client_secret
+                            .as_deref()
+                            .unwrap_or_default(),
+                    ),
+                    ("grant_type", "authorization_code"),
+                    ("code", code),
+                    ("redirect_uri", &redirect_uri),
+                ])
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let user: DiscordUser = HTTP
+                .get("https://discord.com/api/users/@me")
+                .bearer_auth(token.access_token)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            Ok(Session {
+                provider,
+                name: user.global_name.unwrap_or(user.username),
+                user_id: user.id,
+            })
+        }
+    }
+}
+
+async fn logout(
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if !headers.contains_key(CSRF_HEADER) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "missing dashboard header",
+        ));
+    }
+
+    if let Some(token) = cookie(&headers, SESSION_COOKIE) {
+        redis_client.delete_dashboard_session(token).await?;
+    }
+    Ok((
+        AppendHeaders([(
+            SET_COOKIE,
+            set_cookie(&config, SESSION_COOKIE, "", Duration::ZERO),
+        )]),
+        StatusCode::NO_CONTENT,
+    )
+        .into_response())
+}
+
+#[derive(Serialize)]
+struct Overview {
+    links: Vec<LinkOverview>,
+    errors: Vec<Report>,
+    queues: Queues,
+}
+
+#[derive(Serialize)]
+struct LinkOverview {
+    #[serde(flatten)]
+    link: Link,
+    discord_channel_name: Option<String>,
+    slack_channel_name: Option<String>,
+    queued: usize,
+    traffic: Vec<TrafficHour>,
+}
+
+#[derive(Serialize)]
+struct Queues {
+    to_discord: i64,
+    to_slack: i64,
+    dead_letters: usize,
+}
+
+/// Everything the dashboard shows, in one request so it can poll cheaply.
+async fn overview(
+    Extension(config): Extension<Arc<Config>>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(discord_client): Extension<DiscordClient>,
+    Extension(slack_client): Extension<Arc<SlackApiClient>>,
+    Extension(slack_channels): Extension<Arc<SlackChannelCache>>,
+) -> ApiResult<Overview> {
+    let discord_names = discord_channel_names(&config, &discord_client);
+    let slack_names: HashMap<String, String> = match slack_channels
+        .channels(&slack_client, &config.slack_token())
+        .await
+    {
+        Ok(channels) => channels
+            .iter()
+            .map(|channel| (channel.id.clone(), channel.name.clone()))
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, "Failed to list Slack channels for the dashboard");
+            HashMap::new()
+        }
+    };
+
+    let mut links = Vec::new();
+    for (discord_channel_id, slack_channel_id) in redis_client.list_links().await? {
+        links.push(LinkOverview {
+            link: load_link(&redis_client, discord_channel_id, &slack_channel_id).await?,
+            discord_channel_name: discord_names.get(&discord_channel_id).cloned(),
+            slack_channel_name: slack_names.get(&slack_channel_id).cloned(),
+            queued: redis_client
+                .count_queued_events(discord_channel_id, &slack_channel_id)
+                .await?,
+            traffic: metrics::link_traffic(discord_channel_id, &slack_channel_id),
+        });
+    }
+
+    Ok(Json(Overview {
+        links,
+        errors: reports::recent_errors(),
+        queues: Queues {
+            to_discord: metrics::queue_depth(Direction::ToDiscord),
+            to_slack: metrics::queue_depth(Direction::ToSlack),
+            dead_letters: redis_client.count_dead_letters().await?,
+        },
+    }))
+}
+
+/// Names of the guild's channels, from the cache of whichever Discord client is running.
+fn discord_channel_names(config: &Config, discord_client: &DiscordClient) -> HashMap<u64, String> {
+    let handle = discord_client.borrow();
+    let Some(guild) = handle
+        .as_ref()
+        .and_then(|handle| handle.ctx.cache.guild(config.discord.guild_id))
+    else {
+        return HashMap::new();
+    };
+
+    guild
+        .channels
+        .iter()
+        .map(|(id, channel)| (id.get(), channel.name.clone()))
+        .chain(
+            guild
+                .threads
+                .iter()
+                .map(|thread| (thread.id.get(), thread.name.clone())),
+        )
+        .collect()
+}
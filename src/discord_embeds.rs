@@ -0,0 +1,121 @@
+use std::sync::LazyLock;
+
+use poise::serenity_prelude as serenity;
+use regex::Regex;
+
+use crate::bridge::{Embed, EmbedField};
+
+/// Markdown links, with or without the angle brackets that suppress previews.
+static MARKDOWN_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]\n]+)\]\(<?(https?://[^)>\s]+)>?\)").unwrap());
+
+static MARKDOWN_ITALIC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^*])\*([^*\n]+)\*($|[^*])").unwrap());
+
+static MARKDOWN_BOLD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*([^*\n]+)\*\*").unwrap());
+
+static MARKDOWN_UNDERLINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"__([^_\n]+)__").unwrap());
+
+static MARKDOWN_STRIKE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"~~([^~\n]+)~~").unwrap());
+
+/// Headings and subtext, which Slack has no equivalent for.
+static MARKDOWN_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^(?:#{1,3}|-#) (.+)$").unwrap());
+
+/// Collects the parts of a Discord message that aren't in its content: bot embeds,
+/// stickers and polls. Link previews are skipped since Slack unfurls links itself.
+pub fn bridge_embeds(
+    embeds: &[serenity::Embed],
+    stickers: &[serenity::StickerItem],
+    poll: Option<&serenity::Poll>,
+    message_url: &str,
+) -> Vec<Embed> {
+    let mut bridged: Vec<Embed> = embeds
+        .iter()
+        .filter(|embed| embed.kind.as_deref().is_none_or(|kind| kind == "rich"))
+        .map(bridge_embed)
+        .collect();
+
+    bridged.extend(stickers.iter().map(bridge_sticker));
+    bridged.extend(poll.map(|poll| bridge_poll(poll, message_url)));
+
+    bridged
+}
+
+fn bridge_embed(embed: &serenity::Embed) -> Embed {
+    Embed {
+        title: embed.title.as_deref().map(mrkdwn),
+        url: embed.url.clone(),
+        description: embed.description.as_deref().map(mrkdwn),
+        color: embed.colour.map(|colour| colour.0),
+        fields: embed
+            .fields
+            .iter()
+            .map(|field| EmbedField {
+                name: mrkdwn(&field.name),
+                value: mrkdwn(&field.value),
+                inline: field.inline,
+            })
+            .collect(),
+        image_url: embed.image.as_ref().map(|image| image.url.clone()),
+        thumbnail_url: embed
+            .thumbnail
+            .as_ref()
+            .map(|thumbnail| thumbnail.url.clone()),
+        footer: embed.footer.as_ref().map(|footer| mrkdwn(&footer.text)),
+        author: embed.author.as_ref().map(|author| author.name.clone()),
+    }
+}
+
+/// Stickers become their image, except Lottie ones which only Discord can play.
+fn bridge_sticker(sticker: &serenity::StickerItem) -> Embed {
+    let image_url = sticker
+        .image_url()
+        .filter(|_| sticker.format_type != serenity::StickerFormatType::Lottie);
+
+    Embed {
+        title: Some(sticker.name.clone()),
+        image_url,
+        footer: Some("Sticker".to_string()),
+        ..Embed::default()
+    }
+}
+
+fn bridge_poll(poll: &serenity::Poll, message_url: &str) -> Embed {
+    let options = poll
+        .answers
+        .iter()
+        .enumerate()
+        .map(|(index, answer)| {
+            let emoji = match &answer.poll_media.emoji {
+                Some(serenity::PollMediaEmoji::Name(name)) => format!("{name} "),
+                _ => String::new(),
+            };
+            let text = answer.poll_media.text.as_deref().unwrap_or_default();
+            format!("{}. {emoji}{}", index + 1, mrkdwn(text))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Embed {
+        title: Some(format!(
+            "📊 {}",
+            poll.question.text.as_deref().unwrap_or("Poll")
+        )),
+        url: Some(message_url.to_string()),
+        description: Some(options),
+        footer: Some(format!("Poll · <{message_url}|Vote on Discord>")),
+        ..Embed::default()
+    }
+}
+
+/// Converts Discord markdown to Slack mrkdwn.
+fn mrkdwn(text: &str) -> String {
+    let text = MARKDOWN_LINK.replace_all(text, "<$2|$1>");
+    let text = MARKDOWN_ITALIC.replace_all(&text, "${1}_${2}_${3}");
+    let text = MARKDOWN_BOLD.replace_all(&text, "*$1*");
+    let text = MARKDOWN_HEADING.replace_all(&text, "*$1*");
+    let text = MARKDOWN_UNDERLINE.replace_all(&text, "_${1}_");
+    MARKDOWN_STRIKE.replace_all(&text, "~$1~").into_owned()
+}
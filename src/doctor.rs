@@ -0,0 +1,197 @@
+use poise::serenity_prelude as serenity;
+use slack_morphism::prelude::SlackApiConversationsInfoRequest;
+use url::Url;
+
+use crate::config::Config;
+use crate::migrations;
+use crate::redis::RedisClient;
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::Error;
+
+/// Prints one line per check, and counts the ones that failed.
+#[derive(Default)]
+struct Checks {
+    failed: usize,
+}
+
+impl Checks {
+    fn pass(&self, name: &str, detail: impl AsRef<str>) {
+        println!("✓ {name}: {}", detail.as_ref());
+    }
+
+    fn fail(&mut self, name: &str, detail: impl AsRef<str>) {
+        self.failed += 1;
+        println!("✗ {name}: {}", detail.as_ref());
+    }
+}
+
+/// Checks that Redis, Slack and Discord are reachable with the configured credentials,
+/// and that every link's channels still exist and can be posted to. Fails if any
+/// check does.
+pub async fn run(
+    config: &Config,
+    redis_client: &RedisClient,
+    slack_client: &SlackApiClient,
+) -> Result<(), Error> {
+    let mut checks = Checks::default();
+    let redis_address = redis_address(&config.redis_url);
+
+    let links = match redis_client.ping().await {
+        Ok(()) => {
+            checks.pass("Redis", &redis_address);
+            check_schema(&mut checks, redis_client).await;
+            check_dead_letters(&mut checks, redis_client).await;
+            match redis_client.list_links().await {
+                Ok(links) => links,
+                Err(e) => {
+                    checks.fail("Links", format!("couldn't list links: {e}"));
+                    Vec::new()
+                }
+            }
+        }
+        Err(e) => {
+            checks.fail("Redis", format!("{redis_address} is unreachable: {e}"));
+            Vec::new()
+        }
+    };
+
+    let slack_token = config.slack_token();
+    let slack_session = slack_client.open_session(&slack_token);
+    let slack_ok = match slack_session.auth_test().await {
+        Ok(auth) if auth.team_id.to_string() == config.slack.team_id => {
+            checks.pass("Slack", format!("signed in to {}", auth.team));
+            true
+        }
+        Ok(auth) => {
+            checks.fail(
+                "Slack",
+                format!(
+                    "the bot token is for team {}, but slack.team_id is {}",
+                    auth.team_id, config.slack.team_id
+                ),
+            );
+            false
+        }
+        Err(e) => {
+            checks.fail("Slack", format!("couldn't check the bot token: {e}"));
+            false
+        }
+    };
+
+    let discord_http = serenity::Http::new(&config.discord.token);
+    let discord_ok = match discord_http.get_current_user().await {
+        Ok(user) => {
+            checks.pass("Discord", format!("signed in as {}", user.name));
+            match discord_http
+                .get_guild(serenity::GuildId::new(config.discord.guild_id))
+                .await
+            {
+                Ok(guild) => {
+                    checks.pass("Discord guild", guild.name);
+                    true
+                }
+                Err(e) => {
+                    checks.fail(
+                        "Discord guild",
+                        format!("can't see guild {}: {e}", config.discord.guild_id),
+                    );
+                    false
+                }
+            }
+        }
+        Err(e) => {
+            checks.fail("Discord", format!("couldn't check the bot token: {e}"));
+            false
+        }
+    };
+
+    for (discord_channel_id, slack_channel_id) in links {
+        let name = format!("Link {discord_channel_id} ↔ {slack_channel_id}");
+        match redis_client
+            .get_linked_slack_channel(discord_channel_id)
+            .await
+        {
+            Ok(Some(linked)) if linked == slack_channel_id => {}
+            // Dead links are kept for their history, there's nothing left to check
+            Ok(_) => continue,
+            Err(e) => {
+                checks.fail(&name, e.to_string());
+                continue;
+            }
+        }
+
+        let mut problems = Vec::new();
+        if slack_ok {
+            let request = SlackApiConversationsInfoRequest::new(slack_channel_id.clone().into());
+            match slack_session.conversations_info(&request).await {
+                Ok(info) if info.channel.flags.is_archived == Some(true) => {
+                    problems.push("the Slack channel is archived".to_string());
+                }
+                Ok(info) if info.channel.flags.is_member != Some(true) => {
+                    problems.push("the bot isn't in the Slack channel".to_string());
+                }
+                Ok(_) => {}
+                Err(e) => problems.push(format!("can't see the Slack channel: {e}")),
+            }
+        }
+        if discord_ok
+            && let Err(e) = discord_http
+                .get_channel(serenity::ChannelId::new(discord_channel_id))
+                .await
+        {
+            problems.push(format!("can't see the Discord channel: {e}"));
+        }
+
+        if problems.is_empty() {
+            checks.pass(&name, "ok");
+        } else {
+            checks.fail(&name, problems.join(", "));
+        }
+    }
+
+    match checks.failed {
+        0 => Ok(()),
+        1 => Err("1 check failed".into()),
+        n => Err(format!("{n} checks failed").into()),
+    }
+}
+
+/// Where the Redis URL points, leaving out the password it may carry.
+fn redis_address(redis_url: &str) -> String {
+    match Url::parse(redis_url) {
+        Ok(url) => match url.host_str() {
+            Some(host) => format!("{host}:{}", url.port().unwrap_or(6379)),
+            // A Unix socket
+            None => url.path().to_string(),
+        },
+        Err(_) => "Redis".to_string(),
+    }
+}
+
+async fn check_schema(checks: &mut Checks, redis_client: &RedisClient) {
+    match migrations::pending(redis_client).await {
+        Ok(pending) if pending.is_empty() => {
+            checks.pass("Schema", format!("version {}", migrations::CURRENT_VERSION));
+        }
+        Ok(pending) => checks.fail(
+            "Schema",
+            format!(
+                "{} migrations pending, run `carmine migrate`",
+                pending.len()
+            ),
+        ),
+        Err(e) => checks.fail("Schema", e.to_string()),
+    }
+}
+
+/// Dead letters aren't a failure on their own, but they're worth knowing about.
+async fn check_dead_letters(checks: &mut Checks, redis_client: &RedisClient) {
+    match redis_client.count_dead_letters().await {
+        Ok(0) => checks.pass("Dead letters", "none"),
+        Ok(count) => checks.pass(
+            "Dead letters",
+            format!("{count} waiting, see `carmine replay-dlq`"),
+        ),
+        Err(e) => checks.fail("Dead letters", e.to_string()),
+    }
+}
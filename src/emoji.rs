@@ -0,0 +1,362 @@
+use std::sync::LazyLock;
+
+use emojis::{Emoji, SkinTone};
+use regex::{Captures, Regex};
+
+use crate::emoji_names::SLACK_NAMES;
+
+/// Slack names for emoji, besides the GitHub shortcodes the `emojis` table uses, come
+/// from their Unicode and CLDR names, which [`SLACK_NAMES`] is generated from. This
+/// covers what that misses, as `(slack, github)`: names kept from older Unicode drafts,
+/// like `the_horns`, and the ones Slack prefers over the GitHub shortcode. The first
+/// Slack name listed for an emoji is the one we send.
+const SLACK_ALIASES: &[(&str, &str)] = &[
+    ("thinking_face", "thinking"),
+    ("face_with_rolling_eyes", "roll_eyes"),
+    ("hugging_face", "hugs"),
+    ("robot_face", "robot"),
+    ("spock-hand", "vulcan_salute"),
+    ("star-struck", "star_struck"),
+    ("grinning_face_with_star_eyes", "star_struck"),
+    ("face_vomiting", "vomiting_face"),
+    ("face_with_open_mouth_vomiting", "vomiting_face"),
+    ("rolling_on_the_floor_laughing", "rofl"),
+    ("face_with_cowboy_hat", "cowboy_hat_face"),
+    ("face_with_raised_eyebrow", "raised_eyebrow"),
+    ("face_with_one_eyebrow_raised", "raised_eyebrow"),
+    ("face_with_monocle", "monocle_face"),
+    ("face_with_hand_over_mouth", "hand_over_mouth"),
+    (
+        "smiling_face_with_smiling_eyes_and_hand_covering_mouth",
+        "hand_over_mouth",
+    ),
+    ("face_with_symbols_on_mouth", "cursing_face"),
+    ("serious_face_with_symbols_covering_mouth", "cursing_face"),
+    ("face_with_finger_covering_closed_lips", "shushing_face"),
+    ("shocked_face_with_exploding_head", "exploding_head"),
+    (
+        "grinning_face_with_one_large_and_one_small_eye",
+        "zany_face",
+    ),
+    ("the_horns", "metal"),
+    ("sign_of_the_horns", "metal"),
+    ("reversed_hand_with_middle_finger_extended", "middle_finger"),
+    (
+        "hand_with_index_and_middle_fingers_crossed",
+        "crossed_fingers",
+    ),
+    ("simple_smile", "slightly_smiling_face"),
+    ("sleuth_or_spy", "detective"),
+    ("speaking_head_in_silhouette", "speaking_head"),
+    (
+        "man_in_business_suit_levitating",
+        "business_suit_levitating",
+    ),
+    ("man-woman-boy", "family_man_woman_boy"),
+];
+
+/// Slack shortcodes in message text, with an optional skin tone, e.g. `:wave::skin-tone-3:`.
+static SLACK_SHORTCODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r":([a-z0-9_+'-]+):(?::skin-tone-([2-6]):)?").unwrap());
+
+/// Discord custom emoji in message text, e.g. `<:party_parrot:123>` or `<a:dance:456>`.
+static DISCORD_CUSTOM_EMOJI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(a?):(\w+):(\d+)>").unwrap());
+
+/// How a Slack shortcode that isn't a standard emoji should appear on Discord.
+pub enum CustomEmoji {
+    /// A Discord custom emoji with the same name, as `<:name:id>`.
+    Discord(String),
+    /// The image behind a Slack custom emoji.
+    Image(String),
+}
+
+/// Looks up a Slack reaction or shortcode name, e.g. `thumbsup` or `wave::skin-tone-3`.
+pub fn unicode_from_shortcode(name: &str) -> Option<String> {
+    let (name, skin_tone) = match name.split_once("::skin-tone-") {
+        Some((name, tone)) => (name, tone.parse().ok().and_then(skin_tone_from_slack)),
+        None => (name, None),
+    };
+
+    if let Some(flag) = name.strip_prefix("flag-").and_then(flag_from_country_code) {
+        return Some(flag);
+    }
+
+    let emoji = emojis::get_by_shortcode(name)
+        .or_else(|| {
+            SLACK_ALIASES
+                .iter()
+                .find(|(slack, _)| *slack == name)
+                .and_then(|(_, github)| emojis::get_by_shortcode(github))
+        })
+        .or_else(|| {
+            SLACK_NAMES
+                .binary_search_by_key(&name, |(slack, _)| slack)
+                .ok()
+                .and_then(|index| emojis::get(SLACK_NAMES[index].1))
+        })
+        .or_else(|| emojis::get_by_shortcode(&name.replace('-', "_")))?;
+
+    let emoji = skin_tone
+        .and_then(|tone| emoji.with_skin_tone(tone))
+        .unwrap_or(emoji);
+    Some(emoji.as_str().to_string())
+}
+
+/// The Slack name for a unicode emoji, e.g. for adding it as a reaction.
+pub fn shortcode_from_unicode(unicode: &str) -> Option<String> {
+    if let Some(code) = country_code_from_flag(unicode) {
+        return Some(format!("flag-{code}"));
+    }
+
+    let emoji = emojis::get(unicode)?;
+
+    // Skin tone variants have no shortcode of their own
+    let (base, skin_tone) = match emoji.skin_tone() {
+        Some(tone) if tone != SkinTone::Default => {
+            (emoji.skin_tones()?.next()?, skin_tone_to_slack(tone))
+        }
+        _ => (emoji, None),
+    };
+
+    let name = slack_name(base)?;
+    Some(match skin_tone {
+        Some(tone) => format!("{name}::skin-tone-{tone}"),
+        None => name,
+    })
+}
+
+fn slack_name(emoji: &Emoji) -> Option<String> {
+    let github = emoji.shortcode()?;
+
+    Some(
+        SLACK_ALIASES
+            .iter()
+            .find(|(_, alias)| *alias == github)
+            .map_or(github, |(slack, _)| slack)
+            .to_string(),
+    )
+}
+
+/// Replaces Slack shortcodes with unicode, or with whatever `custom` resolves them to.
+/// Shortcodes that resolve to nothing are left as they are.
+pub fn slack_text_to_discord(text: &str, custom: impl Fn(&str) -> Option<CustomEmoji>) -> String {
+    SLACK_SHORTCODE
+        .replace_all(text, |captures: &Captures| {
+            let name = &captures[1];
+            let lookup = match captures.get(2) {
+                Some(tone) => format!("{name}::skin-tone-{}", tone.as_str()),
+                None => name.to_string(),
+            };
+
+            if let Some(unicode) = unicode_from_shortcode(&lookup) {
+                return unicode;
+            }
+
+            match custom(name) {
+                Some(CustomEmoji::Discord(emoji)) => emoji,
+                Some(CustomEmoji::Image(url)) => format!("[:{name}:](<{url}>)"),
+                None => captures[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Replaces Discord custom emoji with the Slack custom emoji of the same name when the
+/// workspace has one, and with a link to the emoji's image otherwise.
+pub fn discord_text_to_slack(text: &str, slack_has: impl Fn(&str) -> bool) -> String {
+    DISCORD_CUSTOM_EMOJI
+        .replace_all(text, |captures: &Captures| {
+            let name = &captures[2];
+            if slack_has(name) {
+                format!(":{name}:")
+            } else {
+                format!(
+                    "<{}|:{name}:>",
+                    custom_emoji_url(&captures[3], &captures[1] == "a")
+                )
+            }
+        })
+        .into_owned()
+}
+
+/// The Slack reaction for a Discord one, which is either unicode or `<:name:id>`. Custom
+/// emoji only carry over when the workspace has one with the same name.
+pub fn slack_reaction_name(emoji: &str, slack_has: impl Fn(&str) -> bool) -> Option<String> {
+    match DISCORD_CUSTOM_EMOJI.captures(emoji) {
+        Some(captures) => slack_has(&captures[2]).then(|| captures[2].to_string()),
+        None => shortcode_from_unicode(emoji),
+    }
+}
+
+pub fn custom_emoji_url(id: &str, animated: bool) -> String {
+    let extension = if animated { "gif" } else { "png" };
+    format!("https://cdn.discordapp.com/emojis/{id}.{extension}")
+}
+
+/// Slack writes flags as `flag-` plus an ISO country code, e.g. `flag-fr`.
+fn flag_from_country_code(code: &str) -> Option<String> {
+    if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    code.to_ascii_uppercase()
+        .chars()
+        .map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)))
+        .collect()
+}
+
+/// The lowercase country code of a flag made of two regional indicators, e.g. `fr`.
+/// Slack only knows most flags by these, not by their GitHub shortcodes like `norway`.
+fn country_code_from_flag(flag: &str) -> Option<String> {
+    let code: String = flag
+        .chars()
+        .map(|c| match c as u32 {
+            0x1F1E6..=0x1F1FF => char::from_u32('a' as u32 + (c as u32 - 0x1F1E6)),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    (code.len() == 2).then_some(code)
+}
+
+fn skin_tone_from_slack(tone: u8) -> Option<SkinTone> {
+    match tone {
+        2 => Some(SkinTone::Light),
+        3 => Some(SkinTone::MediumLight),
+        4 => Some(SkinTone::Medium),
+        5 => Some(SkinTone::MediumDark),
+        6 => Some(SkinTone::Dark),
+        _ => None,
+    }
+}
+
+fn skin_tone_to_slack(tone: SkinTone) -> Option<u8> {
+    match tone {
+        SkinTone::Light => Some(2),
+        SkinTone::MediumLight => Some(3),
+        SkinTone::Medium => Some(4),
+        SkinTone::MediumDark => Some(5),
+        SkinTone::Dark => Some(6),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unicode(name: &str) -> Option<String> {
+        unicode_from_shortcode(name)
+    }
+
+    #[test]
+    fn looks_up_github_and_slack_names() {
+        assert_eq!(unicode("thumbsup").as_deref(), Some("👍"));
+        assert_eq!(unicode("+1").as_deref(), Some("👍"));
+        // Hand-picked, generated from Unicode names, generated from CLDR names
+        assert_eq!(unicode("thinking_face").as_deref(), Some("🤔"));
+        assert_eq!(unicode("the_horns").as_deref(), Some("🤘"));
+        assert_eq!(unicode("robot_face").as_deref(), Some("🤖"));
+        assert_eq!(unicode("sleuth_or_spy").as_deref(), Some("🕵️"));
+        assert_eq!(unicode("star-struck").as_deref(), Some("🤩"));
+        assert_eq!(unicode("not_an_emoji"), None);
+    }
+
+    #[test]
+    fn sends_the_slack_name() {
+        assert_eq!(
+            shortcode_from_unicode("🤔").as_deref(),
+            Some("thinking_face")
+        );
+        assert_eq!(shortcode_from_unicode("👍").as_deref(), Some("+1"));
+        assert_eq!(shortcode_from_unicode("not an emoji"), None);
+    }
+
+    #[test]
+    fn generated_names_are_sorted() {
+        assert!(SLACK_NAMES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(
+            SLACK_NAMES
+                .iter()
+                .all(|(_, emoji)| emojis::get(emoji).is_some())
+        );
+    }
+
+    #[test]
+    fn skin_tones_round_trip() {
+        assert_eq!(unicode("wave::skin-tone-3").as_deref(), Some("👋🏼"));
+        assert_eq!(
+            shortcode_from_unicode("👋🏼").as_deref(),
+            Some("wave::skin-tone-3")
+        );
+
+        for tone in 2..=6 {
+            let name = format!("thumbsup::skin-tone-{tone}");
+            let emoji = unicode(&name).unwrap();
+            assert_ne!(emoji, "👍");
+            assert_eq!(
+                shortcode_from_unicode(&emoji),
+                Some(format!("+1::skin-tone-{tone}"))
+            );
+        }
+
+        // Emoji without skin tones ignore them
+        assert_eq!(unicode("rocket::skin-tone-4").as_deref(), Some("🚀"));
+        assert_eq!(
+            slack_text_to_discord("hi :wave::skin-tone-6:", |_| None),
+            "hi 👋🏿"
+        );
+    }
+
+    #[test]
+    fn flags_round_trip() {
+        for (name, flag) in [("flag-fr", "🇫🇷"), ("flag-no", "🇳🇴"), ("flag-jp", "🇯🇵")]
+        {
+            assert_eq!(unicode(name).as_deref(), Some(flag));
+            assert_eq!(shortcode_from_unicode(flag).as_deref(), Some(name));
+        }
+        assert_eq!(unicode("flag-xyz"), None);
+    }
+
+    #[test]
+    fn discord_custom_emoji_round_trip() {
+        let discord = "nice <:party_parrot:123> <a:dance:456>";
+        let slack = discord_text_to_slack(discord, |name| name == "party_parrot");
+        assert_eq!(
+            slack,
+            "nice :party_parrot: <https://cdn.discordapp.com/emojis/456.gif|:dance:>"
+        );
+
+        let back = slack_text_to_discord(&slack, |name| {
+            (name == "party_parrot").then(|| CustomEmoji::Discord("<:party_parrot:123>".into()))
+        });
+        assert_eq!(
+            back,
+            "nice <:party_parrot:123> <https://cdn.discordapp.com/emojis/456.gif|:dance:>"
+        );
+
+        assert_eq!(
+            slack_reaction_name("<:party_parrot:123>", |name| name == "party_parrot").as_deref(),
+            Some("party_parrot")
+        );
+        assert_eq!(slack_reaction_name("<:party_parrot:123>", |_| false), None);
+        assert_eq!(
+            slack_reaction_name("🤔", |_| false).as_deref(),
+            Some("thinking_face")
+        );
+    }
+
+    #[test]
+    fn slack_custom_emoji_fall_back_to_their_image() {
+        let text = slack_text_to_discord(":blob: and :unknown:", |name| {
+            (name == "blob")
+                .then(|| CustomEmoji::Image("https://emoji.slack-edge.com/blob.png".into()))
+        });
+
+        assert_eq!(
+            text,
+            "[:blob:](<https://emoji.slack-edge.com/blob.png>) and :unknown:"
+        );
+    }
+}
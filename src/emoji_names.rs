@@ -0,0 +1,929 @@
+// Generated by scripts/emoji_names.py, don't edit by hand.
+
+/// Slack names from Unicode character and CLDR names, sorted for binary search.
+#[rustfmt::skip]
+pub const SLACK_NAMES: &[(&str, &str)] = &[
+    ("admission_tickets", "🎟️"),
+    ("airplane_arrival", "🛬"),
+    ("airplane_arriving", "🛬"),
+    ("airplane_departure", "🛫"),
+    ("alien_monster", "👾"),
+    ("american_football", "🏈"),
+    ("anger_symbol", "💢"),
+    ("angry_face", "😠"),
+    ("angry_face_with_horns", "👿"),
+    ("anguished_face", "😧"),
+    ("antenna_bars", "📶"),
+    ("antenna_with_bars", "📶"),
+    ("anticlockwise_downwards_and_upwards_open_circle_arrows", "🔄"),
+    ("anxious_face_with_sweat", "😰"),
+    ("arrow_pointing_rightwards_then_curving_downwards", "⤵️"),
+    ("arrow_pointing_rightwards_then_curving_upwards", "⤴️"),
+    ("artist_palette", "🎨"),
+    ("astonished_face", "😲"),
+    ("atm_sign", "🏧"),
+    ("aubergine", "🍆"),
+    ("automated_teller_machine", "🏧"),
+    ("automobile", "🚗"),
+    ("baby_angel", "👼"),
+    ("back_arrow", "🔙"),
+    ("back_with_leftwards_arrow_above", "🔙"),
+    ("backhand_index_pointing_down", "👇"),
+    ("backhand_index_pointing_left", "👈"),
+    ("backhand_index_pointing_right", "👉"),
+    ("backhand_index_pointing_up", "👆"),
+    ("backpack", "🎒"),
+    ("bactrian_camel", "🐫"),
+    ("badminton_racquet_and_shuttlecock", "🏸"),
+    ("ball_of_yarn", "🧶"),
+    ("ballot_box_with_ballot", "🗳️"),
+    ("banknote_with_dollar_sign", "💵"),
+    ("banknote_with_euro_sign", "💶"),
+    ("banknote_with_pound_sign", "💷"),
+    ("banknote_with_yen_sign", "💴"),
+    ("bar_of_soap", "🧼"),
+    ("barber_pole", "💈"),
+    ("basketball_and_hoop", "🏀"),
+    ("beach_with_umbrella", "🏖️"),
+    ("beaming_face_with_smiling_eyes", "😁"),
+    ("bear_face", "🐻"),
+    ("beating_heart", "💓"),
+    ("beer_mug", "🍺"),
+    ("bell_with_cancellation_stroke", "🔕"),
+    ("bell_with_slash", "🔕"),
+    ("bento_box", "🍱"),
+    ("bicycle", "🚲"),
+    ("billiards", "🎱"),
+    ("biohazard_sign", "☣️"),
+    ("birthday_cake", "🎂"),
+    ("black_chess_pawn", "♟️"),
+    ("black_circle_for_record", "⏺️"),
+    ("black_club_suit", "♣️"),
+    ("black_diamond_suit", "♦️"),
+    ("black_down-pointing_double_triangle", "⏬"),
+    ("black_heart_suit", "♥️"),
+    ("black_left-pointing_double_triangle", "⏪"),
+    ("black_left-pointing_double_triangle_with_vertical_bar", "⏮️"),
+    ("black_left-pointing_triangle", "◀️"),
+    ("black_medium-small_square", "◾"),
+    ("black_question_mark_ornament", "❓"),
+    ("black_right-pointing_double_triangle", "⏩"),
+    ("black_right-pointing_double_triangle_with_vertical_bar", "⏭️"),
+    ("black_right-pointing_triangle", "▶️"),
+    ("black_right-pointing_triangle_with_double_vertical_bar", "⏯️"),
+    ("black_rightwards_arrow", "➡️"),
+    ("black_scissors", "✂️"),
+    ("black_spade_suit", "♠️"),
+    ("black_square_for_stop", "⏹️"),
+    ("black_sun_with_rays", "☀️"),
+    ("black_telephone", "☎️"),
+    ("black_universal_recycling_symbol", "♻️"),
+    ("black_up-pointing_double_triangle", "⏫"),
+    ("blue_circle", "🔵"),
+    ("bottle_with_popping_cork", "🍾"),
+    ("breast-feeding", "🤱"),
+    ("brick", "🧱"),
+    ("briefs", "🩲"),
+    ("bright_button", "🔆"),
+    ("broken_chain", "⛓️‍💥"),
+    ("brown_mushroom", "🍄‍🟫"),
+    ("bullet_train", "🚅"),
+    ("bullseye", "🎯"),
+    ("bus_stop", "🚏"),
+    ("camera_with_flash", "📸"),
+    ("carp_streamer", "🎏"),
+    ("castle", "🏰"),
+    ("cat_face", "🐱"),
+    ("cat_face_with_tears_of_joy", "😹"),
+    ("cat_face_with_wry_smile", "😼"),
+    ("cat_with_tears_of_joy", "😹"),
+    ("cat_with_wry_smile", "😼"),
+    ("chart_decreasing", "📉"),
+    ("chart_increasing", "📈"),
+    ("chart_increasing_with_yen", "💹"),
+    ("chart_with_upwards_trend_and_yen_sign", "💹"),
+    ("check_box_with_check", "☑️"),
+    ("check_mark", "✔️"),
+    ("check_mark_button", "✅"),
+    ("cheering_megaphone", "📣"),
+    ("cheese_wedge", "🧀"),
+    ("chequered_flag", "🏁"),
+    ("cigarette", "🚬"),
+    ("circled_ideograph_accept", "🉑"),
+    ("circled_ideograph_advantage", "🉐"),
+    ("circled_ideograph_congratulation", "㊗️"),
+    ("circled_ideograph_secret", "㊙️"),
+    ("circled_latin_capital_letter_m", "Ⓜ️"),
+    ("circled_m", "Ⓜ️"),
+    ("cityscape_at_dusk", "🌆"),
+    ("cl_button", "🆑"),
+    ("clapper_board", "🎬"),
+    ("clapping_hands", "👏"),
+    ("clapping_hands_sign", "👏"),
+    ("clinking_beer_mugs", "🍻"),
+    ("clock_face_eight-thirty", "🕣"),
+    ("clock_face_eight_oclock", "🕗"),
+    ("clock_face_eleven-thirty", "🕦"),
+    ("clock_face_eleven_oclock", "🕚"),
+    ("clock_face_five-thirty", "🕠"),
+    ("clock_face_five_oclock", "🕔"),
+    ("clock_face_four-thirty", "🕟"),
+    ("clock_face_four_oclock", "🕓"),
+    ("clock_face_nine-thirty", "🕤"),
+    ("clock_face_nine_oclock", "🕘"),
+    ("clock_face_one-thirty", "🕜"),
+    ("clock_face_one_oclock", "🕐"),
+    ("clock_face_seven-thirty", "🕢"),
+    ("clock_face_seven_oclock", "🕖"),
+    ("clock_face_six-thirty", "🕡"),
+    ("clock_face_six_oclock", "🕕"),
+    ("clock_face_ten-thirty", "🕥"),
+    ("clock_face_ten_oclock", "🕙"),
+    ("clock_face_three-thirty", "🕞"),
+    ("clock_face_three_oclock", "🕒"),
+    ("clock_face_twelve-thirty", "🕧"),
+    ("clock_face_twelve_oclock", "🕛"),
+    ("clock_face_two-thirty", "🕝"),
+    ("clock_face_two_oclock", "🕑"),
+    ("clockwise_downwards_and_upwards_open_circle_arrows", "🔃"),
+    ("clockwise_rightwards_and_leftwards_open_circle_arrows", "🔁"),
+    ("clockwise_rightwards_and_leftwards_open_circle_arrows_with_circled_one_overlay", "🔂"),
+    ("clockwise_vertical_arrows", "🔃"),
+    ("closed_mailbox_with_lowered_flag", "📪"),
+    ("closed_mailbox_with_raised_flag", "📫"),
+    ("cloud_with_tornado", "🌪️"),
+    ("club_suit", "♣️"),
+    ("clutch_bag", "👝"),
+    ("cocktail_glass", "🍸"),
+    ("collision_symbol", "💥"),
+    ("compression", "🗜️"),
+    ("computer_disk", "💽"),
+    ("confounded_face", "😖"),
+    ("confused_face", "😕"),
+    ("construction_sign", "🚧"),
+    ("cooked_rice", "🍚"),
+    ("cooking", "🍳"),
+    ("cool_button", "🆒"),
+    ("copyright_sign", "©️"),
+    ("counterclockwise_arrows_button", "🔄"),
+    ("cow_face", "🐮"),
+    ("cricket_bat_and_ball", "🏏"),
+    ("cross_mark", "❌"),
+    ("cross_mark_button", "❎"),
+    ("crying_cat", "😿"),
+    ("crying_face", "😢"),
+    ("curry_and_rice", "🍛"),
+    ("curry_rice", "🍛"),
+    ("dagger_knife", "🗡️"),
+    ("dash_symbol", "💨"),
+    ("dashing_away", "💨"),
+    ("delivery_truck", "🚚"),
+    ("derelict_house_building", "🏚️"),
+    ("diamond_suit", "♦️"),
+    ("diamond_with_a_dot", "💠"),
+    ("dim_button", "🔅"),
+    ("direct_hit", "🎯"),
+    ("disappointed_but_relieved_face", "😥"),
+    ("disappointed_face", "😞"),
+    ("divide", "➗"),
+    ("dizzy_symbol", "💫"),
+    ("dna_double_helix", "🧬"),
+    ("do_not_litter_symbol", "🚯"),
+    ("dog_face", "🐶"),
+    ("dollar_banknote", "💵"),
+    ("dotted_six-pointed_star", "🔯"),
+    ("double_curly_loop", "➿"),
+    ("double_exclamation_mark", "‼️"),
+    ("double_vertical_bar", "⏸️"),
+    ("dove_of_peace", "🕊️"),
+    ("down-left_arrow", "↙️"),
+    ("down-pointing_red_triangle", "🔻"),
+    ("down-pointing_small_red_triangle", "🔽"),
+    ("down-right_arrow", "↘️"),
+    ("down_arrow", "⬇️"),
+    ("downcast_face_with_sweat", "😓"),
+    ("downwards_black_arrow", "⬇️"),
+    ("downwards_button", "🔽"),
+    ("drum_with_drumsticks", "🥁"),
+    ("e-mail_symbol", "📧"),
+    ("ear_of_corn", "🌽"),
+    ("ear_of_maize", "🌽"),
+    ("earth_globe_americas", "🌎"),
+    ("earth_globe_asia-australia", "🌏"),
+    ("earth_globe_europe-africa", "🌍"),
+    ("eight-pointed_star", "✴️"),
+    ("eight-spoked_asterisk", "✳️"),
+    ("eight-thirty", "🕣"),
+    ("eject_symbol", "⏏️"),
+    ("electric_light_bulb", "💡"),
+    ("electric_torch", "🔦"),
+    ("eleven-thirty", "🕦"),
+    ("end_arrow", "🔚"),
+    ("end_with_leftwards_arrow_above", "🔚"),
+    ("enraged_face", "😡"),
+    ("envelope_with_downwards_arrow_above", "📩"),
+    ("euro_banknote", "💶"),
+    ("ewe", "🐑"),
+    ("exclamation_question_mark", "⁉️"),
+    ("expressionless_face", "😑"),
+    ("extraterrestrial_alien", "👽"),
+    ("eye_in_speech_bubble", "👁️‍🗨️"),
+    ("face_blowing_a_kiss", "😘"),
+    ("face_massage", "💆"),
+    ("face_palm", "🤦"),
+    ("face_savoring_food", "😋"),
+    ("face_savouring_delicious_food", "😋"),
+    ("face_screaming_in_fear", "😱"),
+    ("face_throwing_a_kiss", "😘"),
+    ("face_vomiting", "🤮"),
+    ("face_with_bags_under_eyes", "🫩"),
+    ("face_with_cold_sweat", "😓"),
+    ("face_with_cowboy_hat", "🤠"),
+    ("face_with_crossed-out_eyes", "😵"),
+    ("face_with_finger_covering_closed_lips", "🤫"),
+    ("face_with_hand_over_mouth", "🤭"),
+    ("face_with_head-bandage", "🤕"),
+    ("face_with_look_of_triumph", "😤"),
+    ("face_with_medical_mask", "😷"),
+    ("face_with_monocle", "🧐"),
+    ("face_with_no_good_gesture", "🙅"),
+    ("face_with_ok_gesture", "🙆"),
+    ("face_with_one_eyebrow_raised", "🤨"),
+    ("face_with_open_mouth", "😮"),
+    ("face_with_open_mouth_and_cold_sweat", "😰"),
+    ("face_with_open_mouth_vomiting", "🤮"),
+    ("face_with_party_horn_and_party_hat", "🥳"),
+    ("face_with_pleading_eyes", "🥺"),
+    ("face_with_raised_eyebrow", "🤨"),
+    ("face_with_rolling_eyes", "🙄"),
+    ("face_with_steam_from_nose", "😤"),
+    ("face_with_stuck-out_tongue", "😛"),
+    ("face_with_stuck-out_tongue_and_tightly-closed_eyes", "😝"),
+    ("face_with_stuck-out_tongue_and_winking_eye", "😜"),
+    ("face_with_symbols_on_mouth", "🤬"),
+    ("face_with_tears_of_joy", "😂"),
+    ("face_with_tongue", "😛"),
+    ("face_with_uneven_eyes_and_wavy_mouth", "🥴"),
+    ("face_without_mouth", "😶"),
+    ("fast-forward_button", "⏩"),
+    ("fast_down_button", "⏬"),
+    ("fast_reverse_button", "⏪"),
+    ("fast_up_button", "⏫"),
+    ("father_christmas", "🎅"),
+    ("fax_machine", "📠"),
+    ("fearful_face", "😨"),
+    ("fencer", "🤺"),
+    ("field_hockey_stick_and_ball", "🏑"),
+    ("film_frames", "🎞️"),
+    ("fingerprint", "🫆"),
+    ("firework_sparkler", "🎇"),
+    ("first_place_medal", "🥇"),
+    ("first_quarter_moon_face", "🌛"),
+    ("first_quarter_moon_symbol", "🌓"),
+    ("fish_cake_with_swirl", "🍥"),
+    ("fish_cake_with_swirl_design", "🍥"),
+    ("fishing_pole", "🎣"),
+    ("fisted_hand_sign", "👊"),
+    ("five-thirty", "🕠"),
+    ("flag_in_hole", "⛳"),
+    ("fleur-de-lis", "⚜️"),
+    ("flexed_biceps", "💪"),
+    ("flushed_face", "😳"),
+    ("folded_hands", "🙏"),
+    ("fork_and_knife_with_plate", "🍽️"),
+    ("four-thirty", "🕟"),
+    ("fox", "🦊"),
+    ("frame_with_picture", "🖼️"),
+    ("free_button", "🆓"),
+    ("freezing_face", "🥶"),
+    ("french_fries", "🍟"),
+    ("frog_face", "🐸"),
+    ("front-facing_baby_chick", "🐥"),
+    ("frowning_face_with_open_mouth", "😦"),
+    ("fuel_pump", "⛽"),
+    ("full_moon_face", "🌝"),
+    ("full_moon_symbol", "🌕"),
+    ("gem_stone", "💎"),
+    ("giraffe_face", "🦒"),
+    ("glass_of_milk", "🥛"),
+    ("glasses", "👓"),
+    ("globe_showing_americas", "🌎"),
+    ("globe_showing_asia-australia", "🌏"),
+    ("globe_showing_europe-africa", "🌍"),
+    ("glowing_star", "🌟"),
+    ("goblin", "👺"),
+    ("golfer", "🏌️"),
+    ("graduation_cap", "🎓"),
+    ("grimacing_face", "😬"),
+    ("grinning_cat", "😺"),
+    ("grinning_cat_face_with_smiling_eyes", "😸"),
+    ("grinning_cat_with_smiling_eyes", "😸"),
+    ("grinning_face", "😀"),
+    ("grinning_face_with_big_eyes", "😃"),
+    ("grinning_face_with_one_large_and_one_small_eye", "🤪"),
+    ("grinning_face_with_smiling_eyes", "😄"),
+    ("grinning_face_with_star_eyes", "🤩"),
+    ("grinning_face_with_sweat", "😅"),
+    ("grinning_squinting_face", "😆"),
+    ("growing_heart", "💗"),
+    ("hamster_face", "🐹"),
+    ("hand_with_fingers_splayed", "🖐️"),
+    ("hand_with_index_and_middle_fingers_crossed", "🤞"),
+    ("handball", "🤾"),
+    ("happy_person_raising_one_hand", "🙋"),
+    ("harp", "🪉"),
+    ("head_shaking_horizontally", "🙂‍↔️"),
+    ("head_shaking_vertically", "🙂‍↕️"),
+    ("headphone", "🎧"),
+    ("hear-no-evil_monkey", "🙉"),
+    ("heart_exclamation", "❣️"),
+    ("heart_suit", "♥️"),
+    ("heart_with_arrow", "💘"),
+    ("heart_with_ribbon", "💝"),
+    ("heavy_black_heart", "❤️"),
+    ("heavy_exclamation_mark_symbol", "❗"),
+    ("heavy_heart_exclamation_mark_ornament", "❣️"),
+    ("heavy_large_circle", "⭕"),
+    ("helmet_with_white_cross", "⛑️"),
+    ("high-heeled_shoe", "👠"),
+    ("high-speed_train", "🚄"),
+    ("high-speed_train_with_bullet_nose", "🚅"),
+    ("high_brightness_symbol", "🔆"),
+    ("high_voltage", "⚡"),
+    ("high_voltage_sign", "⚡"),
+    ("hollow_red_circle", "⭕"),
+    ("horizontal_traffic_light", "🚥"),
+    ("horse_face", "🐴"),
+    ("hot_beverage", "☕"),
+    ("hot_dog", "🌭"),
+    ("hot_springs", "♨️"),
+    ("hourglass_done", "⌛"),
+    ("hourglass_not_done", "⏳"),
+    ("hourglass_with_flowing_sand", "⏳"),
+    ("house_building", "🏠"),
+    ("house_buildings", "🏘️"),
+    ("hugging_face", "🤗"),
+    ("hundred_points", "💯"),
+    ("hundred_points_symbol", "💯"),
+    ("hushed_face", "😯"),
+    ("i_love_you_hand_sign", "🤟"),
+    ("ice", "🧊"),
+    ("ice_hockey_stick_and_puck", "🏒"),
+    ("id_button", "🆔"),
+    ("index_pointing_up", "☝️"),
+    ("information", "ℹ️"),
+    ("input_latin_letters", "🔤"),
+    ("input_latin_lowercase", "🔡"),
+    ("input_latin_uppercase", "🔠"),
+    ("input_numbers", "🔢"),
+    ("input_symbol_for_latin_capital_letters", "🔠"),
+    ("input_symbol_for_latin_letters", "🔤"),
+    ("input_symbol_for_latin_small_letters", "🔡"),
+    ("input_symbol_for_numbers", "🔢"),
+    ("input_symbol_for_symbols", "🔣"),
+    ("input_symbols", "🔣"),
+    ("jack-o-lantern", "🎃"),
+    ("japanese_dolls", "🎎"),
+    ("japanese_post_office", "🏣"),
+    ("japanese_symbol_for_beginner", "🔰"),
+    ("jigsaw_puzzle_piece", "🧩"),
+    ("joker", "🃏"),
+    ("juggling", "🤹"),
+    ("kiss_mark", "💋"),
+    ("kissing_cat_face_with_closed_eyes", "😽"),
+    ("kissing_face", "😗"),
+    ("kissing_face_with_closed_eyes", "😚"),
+    ("kissing_face_with_smiling_eyes", "😙"),
+    ("kitchen_knife", "🔪"),
+    ("kiwifruit", "🥝"),
+    ("lacrosse_stick_and_ball", "🥍"),
+    ("laptop", "💻"),
+    ("large_blue_square", "🟦"),
+    ("large_brown_circle", "🟤"),
+    ("large_brown_square", "🟫"),
+    ("large_green_circle", "🟢"),
+    ("large_green_square", "🟩"),
+    ("large_orange_circle", "🟠"),
+    ("large_orange_square", "🟧"),
+    ("large_purple_circle", "🟣"),
+    ("large_purple_square", "🟪"),
+    ("large_red_circle", "🔴"),
+    ("large_red_square", "🟥"),
+    ("large_yellow_circle", "🟡"),
+    ("large_yellow_square", "🟨"),
+    ("last_quarter_moon_face", "🌜"),
+    ("last_quarter_moon_symbol", "🌗"),
+    ("last_track_button", "⏮️"),
+    ("leaf_fluttering_in_wind", "🍃"),
+    ("leafless_tree", "🪾"),
+    ("left-facing_fist", "🤛"),
+    ("left-pointing_magnifying_glass", "🔍"),
+    ("left-right_arrow", "↔️"),
+    ("left_arrow", "⬅️"),
+    ("left_arrow_curving_right", "↪️"),
+    ("leftwards_black_arrow", "⬅️"),
+    ("light_bulb", "💡"),
+    ("lime", "🍋‍🟩"),
+    ("link_symbol", "🔗"),
+    ("linked_paperclips", "🖇️"),
+    ("lion_face", "🦁"),
+    ("litter_in_bin_sign", "🚮"),
+    ("locked", "🔒"),
+    ("locked_with_key", "🔐"),
+    ("locked_with_pen", "🔏"),
+    ("locomotive", "🚂"),
+    ("loudly_crying_face", "😭"),
+    ("love-you_gesture", "🤟"),
+    ("low_brightness_symbol", "🔅"),
+    ("lower_left_ballpoint_pen", "🖊️"),
+    ("lower_left_crayon", "🖍️"),
+    ("lower_left_fountain_pen", "🖋️"),
+    ("lower_left_paintbrush", "🖌️"),
+    ("magnifying_glass_tilted_left", "🔍"),
+    ("magnifying_glass_tilted_right", "🔎"),
+    ("mahjong_red_dragon", "🀄"),
+    ("mahjong_tile_red_dragon", "🀄"),
+    ("male_with_stroke_and_male_and_female_sign", "⚧️"),
+    ("man_and_woman_holding_hands", "👫"),
+    ("man_biking", "🚴‍♂️"),
+    ("man_bouncing_ball", "⛹️‍♂️"),
+    ("man_bowing", "🙇‍♂️"),
+    ("man_climbing", "🧗‍♂️"),
+    ("man_construction_worker", "👷‍♂️"),
+    ("man_detective", "🕵️‍♂️"),
+    ("man_elf", "🧝‍♂️"),
+    ("man_fairy", "🧚‍♂️"),
+    ("man_frowning", "🙍‍♂️"),
+    ("man_genie", "🧞‍♂️"),
+    ("man_gesturing_no", "🙅‍♂️"),
+    ("man_gesturing_ok", "🙆‍♂️"),
+    ("man_getting_haircut", "💇‍♂️"),
+    ("man_getting_massage", "💆‍♂️"),
+    ("man_golfing", "🏌️‍♂️"),
+    ("man_guard", "💂‍♂️"),
+    ("man_in_business_suit_levitating", "🕴️"),
+    ("man_in_lotus_position", "🧘‍♂️"),
+    ("man_in_manual_wheelchair_facing_right", "👨‍🦽‍➡️"),
+    ("man_in_motorized_wheelchair_facing_right", "👨‍🦼‍➡️"),
+    ("man_in_steamy_room", "🧖‍♂️"),
+    ("man_kneeling", "🧎‍♂️"),
+    ("man_kneeling_facing_right", "🧎‍♂️‍➡️"),
+    ("man_lifting_weights", "🏋️‍♂️"),
+    ("man_mage", "🧙‍♂️"),
+    ("man_mountain_biking", "🚵‍♂️"),
+    ("man_police_officer", "👮‍♂️"),
+    ("man_pouting", "🙎‍♂️"),
+    ("man_raising_hand", "🙋‍♂️"),
+    ("man_rowing_boat", "🚣‍♂️"),
+    ("man_running", "🏃‍♂️"),
+    ("man_running_facing_right", "🏃‍♂️‍➡️"),
+    ("man_standing", "🧍‍♂️"),
+    ("man_superhero", "🦸‍♂️"),
+    ("man_supervillain", "🦹‍♂️"),
+    ("man_surfing", "🏄‍♂️"),
+    ("man_swimming", "🏊‍♂️"),
+    ("man_tipping_hand", "💁‍♂️"),
+    ("man_vampire", "🧛‍♂️"),
+    ("man_walking", "🚶‍♂️"),
+    ("man_walking_facing_right", "🚶‍♂️‍➡️"),
+    ("man_wearing_turban", "👳‍♂️"),
+    ("man_with_white_cane", "👨‍🦯"),
+    ("man_with_white_cane_facing_right", "👨‍🦯‍➡️"),
+    ("man_zombie", "🧟‍♂️"),
+    ("map_of_japan", "🗾"),
+    ("mate_drink", "🧉"),
+    ("medium_black_circle", "⚫"),
+    ("medium_white_circle", "⚪"),
+    ("megaphone", "📣"),
+    ("men_holding_hands", "👬"),
+    ("men_with_bunny_ears", "👯‍♂️"),
+    ("menorah_with_nine_branches", "🕎"),
+    ("mens_symbol", "🚹"),
+    ("military_medal", "🎖️"),
+    ("minus", "➖"),
+    ("moai", "🗿"),
+    ("mobile_phone", "📱"),
+    ("mobile_phone_with_arrow", "📲"),
+    ("mobile_phone_with_rightwards_arrow_at_left", "📲"),
+    ("money-mouth_face", "🤑"),
+    ("money_bag", "💰"),
+    ("moon_viewing_ceremony", "🎑"),
+    ("mother_christmas", "🤶"),
+    ("mouse_face", "🐭"),
+    ("mouth", "👄"),
+    ("multiple_musical_notes", "🎶"),
+    ("multiply", "✖️"),
+    ("musical_notes", "🎶"),
+    ("muted_speaker", "🔇"),
+    ("nail_polish", "💅"),
+    ("negative_squared_ab", "🆎"),
+    ("negative_squared_latin_capital_letter_a", "🅰️"),
+    ("negative_squared_latin_capital_letter_b", "🅱️"),
+    ("negative_squared_latin_capital_letter_o", "🅾️"),
+    ("negative_squared_latin_capital_letter_p", "🅿️"),
+    ("new_button", "🆕"),
+    ("new_moon_face", "🌚"),
+    ("new_moon_symbol", "🌑"),
+    ("ng_button", "🆖"),
+    ("nine-thirty", "🕤"),
+    ("no_littering", "🚯"),
+    ("no_one_under_eighteen", "🔞"),
+    ("no_one_under_eighteen_symbol", "🔞"),
+    ("no_smoking_symbol", "🚭"),
+    ("non-potable_water_symbol", "🚱"),
+    ("north_east_arrow", "↗️"),
+    ("north_west_arrow", "↖️"),
+    ("octagonal_sign", "🛑"),
+    ("office_building", "🏢"),
+    ("ogre", "👹"),
+    ("ok_button", "🆗"),
+    ("ok_hand_sign", "👌"),
+    ("old_man", "👴"),
+    ("old_woman", "👵"),
+    ("older_person", "🧓"),
+    ("om_symbol", "🕉️"),
+    ("on_with_exclamation_mark_with_left_right_arrow_above", "🔛"),
+    ("oncoming_fist", "👊"),
+    ("one-piece_swimsuit", "🩱"),
+    ("one-thirty", "🕜"),
+    ("open_hands_sign", "👐"),
+    ("open_lock", "🔓"),
+    ("open_mailbox_with_lowered_flag", "📭"),
+    ("open_mailbox_with_raised_flag", "📬"),
+    ("optical_disc", "💿"),
+    ("optical_disk", "💿"),
+    ("overheated_face", "🥵"),
+    ("p_button", "🅿️"),
+    ("panda", "🐼"),
+    ("party_popper", "🎉"),
+    ("pedestrian", "🚶"),
+    ("pensive_face", "😔"),
+    ("people_with_bunny_ears", "👯"),
+    ("people_wrestling", "🤼"),
+    ("permanent_paper_sign", "♾️"),
+    ("persevering_face", "😣"),
+    ("person", "🧑"),
+    ("person_biking", "🚴"),
+    ("person_bouncing_ball", "⛹️"),
+    ("person_bowing", "🙇"),
+    ("person_bowing_deeply", "🙇"),
+    ("person_cartwheeling", "🤸"),
+    ("person_climbing", "🧗"),
+    ("person_doing_cartwheel", "🤸"),
+    ("person_facepalming", "🤦"),
+    ("person_frowning", "🙍"),
+    ("person_gesturing_no", "🙅"),
+    ("person_gesturing_ok", "🙆"),
+    ("person_getting_haircut", "💇"),
+    ("person_getting_massage", "💆"),
+    ("person_golfing", "🏌️"),
+    ("person_in_bed", "🛌"),
+    ("person_in_lotus_position", "🧘"),
+    ("person_in_manual_wheelchair_facing_right", "🧑‍🦽‍➡️"),
+    ("person_in_motorized_wheelchair_facing_right", "🧑‍🦼‍➡️"),
+    ("person_in_steamy_room", "🧖"),
+    ("person_in_suit_levitating", "🕴️"),
+    ("person_juggling", "🤹"),
+    ("person_kneeling", "🧎"),
+    ("person_kneeling_facing_right", "🧎‍➡️"),
+    ("person_lifting_weights", "🏋️"),
+    ("person_mountain_biking", "🚵"),
+    ("person_playing_handball", "🤾"),
+    ("person_playing_water_polo", "🤽"),
+    ("person_pouting", "🙎"),
+    ("person_raising_both_hands_in_celebration", "🙌"),
+    ("person_raising_hand", "🙋"),
+    ("person_rowing_boat", "🚣"),
+    ("person_running", "🏃"),
+    ("person_running_facing_right", "🏃‍➡️"),
+    ("person_shrugging", "🤷"),
+    ("person_standing", "🧍"),
+    ("person_surfing", "🏄"),
+    ("person_swimming", "🏊"),
+    ("person_taking_bath", "🛀"),
+    ("person_tipping_hand", "💁"),
+    ("person_walking", "🚶"),
+    ("person_walking_facing_right", "🚶‍➡️"),
+    ("person_wearing_turban", "👳"),
+    ("person_with_ball", "⛹️"),
+    ("person_with_blond_hair", "👱"),
+    ("person_with_folded_hands", "🙏"),
+    ("person_with_headscarf", "🧕"),
+    ("person_with_pouting_face", "🙎"),
+    ("person_with_skullcap", "👲"),
+    ("person_with_white_cane", "🧑‍🦯"),
+    ("person_with_white_cane_facing_right", "🧑‍🦯‍➡️"),
+    ("personal_computer", "💻"),
+    ("phoenix", "🐦‍🔥"),
+    ("pig_face", "🐷"),
+    ("pile_of_poo", "💩"),
+    ("pine_decoration", "🎍"),
+    ("pistol", "🔫"),
+    ("play_button", "▶️"),
+    ("playing_card_black_joker", "🃏"),
+    ("plus", "➕"),
+    ("police_car_light", "🚨"),
+    ("police_cars_revolving_light", "🚨"),
+    ("pool_8_ball", "🎱"),
+    ("pot_of_food", "🍲"),
+    ("potable_water_symbol", "🚰"),
+    ("pound_banknote", "💷"),
+    ("pouting_cat_face", "😾"),
+    ("prohibited", "🚫"),
+    ("public_address_loudspeaker", "📢"),
+    ("put_litter_in_its_place_symbol", "🚮"),
+    ("puzzle_piece", "🧩"),
+    ("rabbit_face", "🐰"),
+    ("racing_motorcycle", "🏍️"),
+    ("radioactive_sign", "☢️"),
+    ("raised_fist", "✊"),
+    ("raised_hand_with_part_between_middle_and_ring_fingers", "🖖"),
+    ("raising_hands", "🙌"),
+    ("recreational_vehicle", "🚙"),
+    ("recycling_symbol", "♻️"),
+    ("red_apple", "🍎"),
+    ("red_exclamation_mark", "❗"),
+    ("red_gift_envelope", "🧧"),
+    ("red_heart", "❤️"),
+    ("red_paper_lantern", "🏮"),
+    ("red_question_mark", "❓"),
+    ("red_triangle_pointed_down", "🔻"),
+    ("red_triangle_pointed_up", "🔺"),
+    ("registered_sign", "®️"),
+    ("relieved_face", "😌"),
+    ("repeat_button", "🔁"),
+    ("repeat_single_button", "🔂"),
+    ("reverse_button", "◀️"),
+    ("reversed_hand_with_middle_finger_extended", "🖕"),
+    ("right-facing_fist", "🤜"),
+    ("right-pointing_magnifying_glass", "🔎"),
+    ("right_arrow", "➡️"),
+    ("right_arrow_curving_down", "⤵️"),
+    ("right_arrow_curving_left", "↩️"),
+    ("right_arrow_curving_up", "⤴️"),
+    ("rightwards_arrow_with_hook", "↪️"),
+    ("roasted_sweet_potato", "🍠"),
+    ("robot_face", "🤖"),
+    ("rolled-up_newspaper", "🗞️"),
+    ("rolling_on_the_floor_laughing", "🤣"),
+    ("root_vegetable", "🫜"),
+    ("running_shirt", "🎽"),
+    ("running_shoe", "👟"),
+    ("sad_but_relieved_face", "😥"),
+    ("sake_bottle_and_cup", "🍶"),
+    ("salt_shaker", "🧂"),
+    ("santa_claus", "🎅"),
+    ("satellite_antenna", "📡"),
+    ("scales", "⚖️"),
+    ("scooter", "🛴"),
+    ("scorpio", "♏"),
+    ("second_place_medal", "🥈"),
+    ("see-no-evil_monkey", "🙈"),
+    ("serious_face_with_symbols_covering_mouth", "🤬"),
+    ("seven-thirty", "🕢"),
+    ("sheaf_of_rice", "🌾"),
+    ("shocked_face_with_exploding_head", "🤯"),
+    ("shooting_star", "🌠"),
+    ("shopping_bags", "🛍️"),
+    ("shopping_trolley", "🛒"),
+    ("shortcake", "🍰"),
+    ("shovel", "🪏"),
+    ("shuffle_tracks_button", "🔀"),
+    ("sign_of_the_horns", "🤘"),
+    ("silhouette_of_japan", "🗾"),
+    ("six-thirty", "🕡"),
+    ("six_pointed_star_with_middle_dot", "🔯"),
+    ("ski_and_ski_boot", "🎿"),
+    ("skis", "🎿"),
+    ("sleeping_accommodation", "🛌"),
+    ("sleeping_face", "😴"),
+    ("sleeping_symbol", "💤"),
+    ("sleepy_face", "😪"),
+    ("sleuth_or_spy", "🕵️"),
+    ("slice_of_pizza", "🍕"),
+    ("smiling_cat_face_with_heart-shaped_eyes", "😻"),
+    ("smiling_cat_face_with_open_mouth", "😺"),
+    ("smiling_cat_with_heart-eyes", "😻"),
+    ("smiling_face", "☺️"),
+    ("smiling_face_with_halo", "😇"),
+    ("smiling_face_with_heart-eyes", "😍"),
+    ("smiling_face_with_heart-shaped_eyes", "😍"),
+    ("smiling_face_with_hearts", "🥰"),
+    ("smiling_face_with_horns", "😈"),
+    ("smiling_face_with_open_hands", "🤗"),
+    ("smiling_face_with_open_mouth", "😃"),
+    ("smiling_face_with_open_mouth_and_cold_sweat", "😅"),
+    ("smiling_face_with_open_mouth_and_smiling_eyes", "😄"),
+    ("smiling_face_with_open_mouth_and_tightly-closed_eyes", "😆"),
+    ("smiling_face_with_smiling_eyes", "😊"),
+    ("smiling_face_with_smiling_eyes_and_hand_covering_mouth", "🤭"),
+    ("smiling_face_with_smiling_eyes_and_three_hearts", "🥰"),
+    ("smiling_face_with_sunglasses", "😎"),
+    ("smirking_face", "😏"),
+    ("smoking_symbol", "🚬"),
+    ("snow-capped_mountain", "🏔️"),
+    ("snow_capped_mountain", "🏔️"),
+    ("snowman_without_snow", "⛄"),
+    ("soccer_ball", "⚽"),
+    ("soft_ice_cream", "🍦"),
+    ("soon_arrow", "🔜"),
+    ("soon_with_rightwards_arrow_above", "🔜"),
+    ("sos_button", "🆘"),
+    ("south_east_arrow", "↘️"),
+    ("south_west_arrow", "↙️"),
+    ("spade_suit", "♠️"),
+    ("speak-no-evil_monkey", "🙊"),
+    ("speaker_high_volume", "🔊"),
+    ("speaker_low_volume", "🔈"),
+    ("speaker_medium_volume", "🔉"),
+    ("speaker_with_cancellation_stroke", "🔇"),
+    ("speaker_with_one_sound_wave", "🔉"),
+    ("speaker_with_three_sound_waves", "🔊"),
+    ("speaking_head_in_silhouette", "🗣️"),
+    ("spiral_calendar_pad", "🗓️"),
+    ("spiral_note_pad", "🗒️"),
+    ("spiral_shell", "🐚"),
+    ("splashing_sweat_symbol", "💦"),
+    ("splatter", "🫟"),
+    ("spool_of_thread", "🧵"),
+    ("sport_utility_vehicle", "🚙"),
+    ("sports_medal", "🏅"),
+    ("spouting_whale", "🐳"),
+    ("squared_cjk_unified_ideograph-5272", "🈹"),
+    ("squared_cjk_unified_ideograph-5408", "🈴"),
+    ("squared_cjk_unified_ideograph-55b6", "🈺"),
+    ("squared_cjk_unified_ideograph-6307", "🈯"),
+    ("squared_cjk_unified_ideograph-6708", "🈷️"),
+    ("squared_cjk_unified_ideograph-6709", "🈶"),
+    ("squared_cjk_unified_ideograph-6e80", "🈵"),
+    ("squared_cjk_unified_ideograph-7121", "🈚"),
+    ("squared_cjk_unified_ideograph-7533", "🈸"),
+    ("squared_cjk_unified_ideograph-7981", "🈲"),
+    ("squared_cjk_unified_ideograph-7a7a", "🈳"),
+    ("squared_cl", "🆑"),
+    ("squared_cool", "🆒"),
+    ("squared_free", "🆓"),
+    ("squared_id", "🆔"),
+    ("squared_katakana_koko", "🈁"),
+    ("squared_katakana_sa", "🈂️"),
+    ("squared_new", "🆕"),
+    ("squared_ng", "🆖"),
+    ("squared_ok", "🆗"),
+    ("squared_sos", "🆘"),
+    ("squared_up_with_exclamation_mark", "🆙"),
+    ("squared_vs", "🆚"),
+    ("squinting_face_with_tongue", "😝"),
+    ("staff_of_aesculapius", "⚕️"),
+    ("star-struck", "🤩"),
+    ("steaming_bowl", "🍜"),
+    ("sun", "☀️"),
+    ("sun_behind_cloud", "⛅"),
+    ("sunset", "🌇"),
+    ("sunset_over_buildings", "🌇"),
+    ("sweat_droplets", "💦"),
+    ("t-shirt", "👕"),
+    ("table_tennis_paddle_and_ball", "🏓"),
+    ("teacup_without_handle", "🍵"),
+    ("tear-off_calendar", "📆"),
+    ("television", "📺"),
+    ("ten-thirty", "🕥"),
+    ("tennis_racquet_and_ball", "🎾"),
+    ("thinking_face", "🤔"),
+    ("third_place_medal", "🥉"),
+    ("three-thirty", "🕞"),
+    ("three_button_mouse", "🖱️"),
+    ("thumbs_down", "👎"),
+    ("thumbs_down_sign", "👎"),
+    ("thumbs_up", "👍"),
+    ("thumbs_up_sign", "👍"),
+    ("thunder_cloud_and_rain", "⛈️"),
+    ("tiger_face", "🐯"),
+    ("top_arrow", "🔝"),
+    ("top_hat", "🎩"),
+    ("top_with_upwards_arrow_above", "🔝"),
+    ("trade_mark", "™️"),
+    ("trade_mark_sign", "™️"),
+    ("tram_car", "🚋"),
+    ("triangular_flag", "🚩"),
+    ("trident_emblem", "🔱"),
+    ("twelve-thirty", "🕧"),
+    ("two-hump_camel", "🐫"),
+    ("two-thirty", "🕝"),
+    ("umbrella_on_ground", "⛱️"),
+    ("umbrella_with_rain_drops", "☔"),
+    ("unamused_face", "😒"),
+    ("unicorn_face", "🦄"),
+    ("unlocked", "🔓"),
+    ("up-down_arrow", "↕️"),
+    ("up-left_arrow", "↖️"),
+    ("up-pointing_red_triangle", "🔺"),
+    ("up-pointing_small_red_triangle", "🔼"),
+    ("up-right_arrow", "↗️"),
+    ("up_arrow", "⬆️"),
+    ("up_down_arrow", "↕️"),
+    ("upside-down_face", "🙃"),
+    ("upwards_black_arrow", "⬆️"),
+    ("upwards_button", "🔼"),
+    ("victory_hand", "✌️"),
+    ("videocassette", "📼"),
+    ("vs_button", "🆚"),
+    ("waning_crescent_moon_symbol", "🌘"),
+    ("waning_gibbous_moon_symbol", "🌖"),
+    ("warning_sign", "⚠️"),
+    ("water_closet", "🚾"),
+    ("water_pistol", "🔫"),
+    ("water_wave", "🌊"),
+    ("waving_black_flag", "🏴"),
+    ("waving_hand", "👋"),
+    ("waving_hand_sign", "👋"),
+    ("waving_white_flag", "🏳️"),
+    ("waxing_crescent_moon_symbol", "🌒"),
+    ("waxing_gibbous_moon_symbol", "🌔"),
+    ("weary_cat", "🙀"),
+    ("weary_cat_face", "🙀"),
+    ("weary_face", "😩"),
+    ("weight_lifter", "🏋️"),
+    ("wheelchair_symbol", "♿"),
+    ("white_cane", "🦯"),
+    ("white_down_pointing_backhand_index", "👇"),
+    ("white_exclamation_mark", "❕"),
+    ("white_exclamation_mark_ornament", "❕"),
+    ("white_frowning_face", "☹️"),
+    ("white_heavy_check_mark", "✅"),
+    ("white_left_pointing_backhand_index", "👈"),
+    ("white_medium-small_square", "◽"),
+    ("white_medium_star", "⭐"),
+    ("white_question_mark", "❔"),
+    ("white_question_mark_ornament", "❔"),
+    ("white_right_pointing_backhand_index", "👉"),
+    ("white_smiling_face", "☺️"),
+    ("white_sun_behind_cloud", "🌥️"),
+    ("white_sun_behind_cloud_with_rain", "🌦️"),
+    ("white_sun_with_small_cloud", "🌤️"),
+    ("white_up_pointing_backhand_index", "👆"),
+    ("white_up_pointing_index", "☝️"),
+    ("wind_blowing_face", "🌬️"),
+    ("winking_face", "😉"),
+    ("winking_face_with_tongue", "😜"),
+    ("wolf_face", "🐺"),
+    ("woman_and_man_holding_hands", "👫"),
+    ("woman_biking", "🚴‍♀️"),
+    ("woman_bouncing_ball", "⛹️‍♀️"),
+    ("woman_bowing", "🙇‍♀️"),
+    ("woman_climbing", "🧗‍♀️"),
+    ("woman_construction_worker", "👷‍♀️"),
+    ("woman_detective", "🕵️‍♀️"),
+    ("woman_elf", "🧝‍♀️"),
+    ("woman_fairy", "🧚‍♀️"),
+    ("woman_frowning", "🙍‍♀️"),
+    ("woman_genie", "🧞‍♀️"),
+    ("woman_gesturing_no", "🙅‍♀️"),
+    ("woman_gesturing_ok", "🙆‍♀️"),
+    ("woman_getting_haircut", "💇‍♀️"),
+    ("woman_getting_massage", "💆‍♀️"),
+    ("woman_golfing", "🏌️‍♀️"),
+    ("woman_guard", "💂‍♀️"),
+    ("woman_in_lotus_position", "🧘‍♀️"),
+    ("woman_in_manual_wheelchair_facing_right", "👩‍🦽‍➡️"),
+    ("woman_in_motorized_wheelchair_facing_right", "👩‍🦼‍➡️"),
+    ("woman_in_steamy_room", "🧖‍♀️"),
+    ("woman_kneeling", "🧎‍♀️"),
+    ("woman_kneeling_facing_right", "🧎‍♀️‍➡️"),
+    ("woman_lifting_weights", "🏋️‍♀️"),
+    ("woman_mage", "🧙‍♀️"),
+    ("woman_mountain_biking", "🚵‍♀️"),
+    ("woman_police_officer", "👮‍♀️"),
+    ("woman_pouting", "🙎‍♀️"),
+    ("woman_raising_hand", "🙋‍♀️"),
+    ("woman_rowing_boat", "🚣‍♀️"),
+    ("woman_running", "🏃‍♀️"),
+    ("woman_running_facing_right", "🏃‍♀️‍➡️"),
+    ("woman_standing", "🧍‍♀️"),
+    ("woman_superhero", "🦸‍♀️"),
+    ("woman_supervillain", "🦹‍♀️"),
+    ("woman_surfing", "🏄‍♀️"),
+    ("woman_swimming", "🏊‍♀️"),
+    ("woman_tipping_hand", "💁‍♀️"),
+    ("woman_vampire", "🧛‍♀️"),
+    ("woman_walking", "🚶‍♀️"),
+    ("woman_walking_facing_right", "🚶‍♀️‍➡️"),
+    ("woman_wearing_turban", "👳‍♀️"),
+    ("woman_with_bunny_ears", "👯"),
+    ("woman_with_white_cane", "👩‍🦯"),
+    ("woman_with_white_cane_facing_right", "👩‍🦯‍➡️"),
+    ("woman_zombie", "🧟‍♀️"),
+    ("womans_boots", "👢"),
+    ("womans_sandal", "👡"),
+    ("women_holding_hands", "👭"),
+    ("women_with_bunny_ears", "👯‍♀️"),
+    ("womens_symbol", "🚺"),
+    ("worried_face", "😟"),
+    ("wrapped_gift", "🎁"),
+    ("wrapped_present", "🎁"),
+    ("wrestlers", "🤼"),
+    ("x-ray", "🩻"),
+    ("yen_banknote", "💴"),
+    ("yo-yo", "🪀"),
+    ("zebra_face", "🦓"),
+    ("zipper-mouth_face", "🤐"),
+];
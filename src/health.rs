@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::Json;
+use axum::http::StatusCode;
+use poise::serenity_prelude as serenity;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::redis::RedisClient;
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::DiscordClient;
+
+#[derive(Serialize)]
+pub struct Health {
+    status: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    status: &'static str,
+    redis: Component,
+    discord: Component,
+    slack: Component,
+}
+
+#[derive(Serialize)]
+struct Component {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl Component {
+    fn ok(detail: Option<String>) -> Self {
+        Self { ok: true, detail }
+    }
+
+    fn failed(detail: String) -> Self {
+        Self {
+            ok: false,
+            detail: Some(detail),
+        }
+    }
+}
+
+/// Liveness: answering at all means the process is up.
+pub async fn healthz() -> Json<Health> {
+    Json(Health { status: "ok" })
+}
+
+/// Readiness: whether Redis, the Discord gateway and the Slack token all work.
+pub async fn readyz(
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(slack_client): Extension<Arc<SlackApiClient>>,
+    Extension(discord_client): Extension<DiscordClient>,
+    Extension(config): Extension<Arc<Config>>,
+) -> (StatusCode, Json<Readiness>) {
+    let (redis, discord, slack) = tokio::join!(
+        check_redis(&redis_client),
+        check_discord(&discord_client),
+        check_slack(&slack_client, &config),
+    );
+
+    let ready = redis.ok && discord.ok && slack.ok;
+    let (code, status) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+    };
+
+    (
+        code,
+        Json(Readiness {
+            status,
+            redis,
+            discord,
+            slack,
+        }),
+    )
+}
+
+async fn check_redis(redis_client: &RedisClient) -> Component {
+    match redis_client.ping().await {
+        Ok(()) => Component::ok(None),
+        Err(e) => Component::failed(e.to_string()),
+    }
+}
+
+/// Every shard has to be connected, since events for its guilds stop otherwise.
+async fn check_discord(discord_client: &DiscordClient) -> Component {
+    let shard_manager = discord_client
+        .borrow()
+        .as_ref()
+        .map(|handle| handle.shard_manager.clone());
+    let Some(shard_manager) = shard_manager else {
+        return Component::failed("client not started".to_string());
+    };
+
+    let runners = shard_manager.runners.lock().await;
+    if runners.is_empty() {
+        return Component::failed("no shards running".to_string());
+    }
+
+    let stages = runners
+        .iter()
+        .map(|(id, runner)| format!("shard {id}: {}", runner.stage))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if runners
+        .values()
+        .all(|runner| runner.stage == serenity::ConnectionStage::Connected)
+    {
+        Component::ok(Some(stages))
+    } else {
+        Component::failed(stages)
+    }
+}
+
+async fn check_slack(slack_client: &SlackApiClient, config: &Config) -> Component {
+    let slack_token = config.slack_token();
+    let session = slack_client.open_session(&slack_token);
+
+    match session.auth_test().await {
+        Ok(response) => Component::ok(Some(format!("team {}", response.team_id))),
+        Err(e) => Component::failed(e.to_string()),
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::config::{Config, LogFormat};
+use crate::metrics;
+use crate::reports::{self, ReportReceiver};
+
+/// Message content and names stay out of logs unless the config turns redaction off.
+static UNREDACTED: AtomicBool = AtomicBool::new(false);
+
+/// Sets up logging. `RUST_LOG` picks levels, `info` by default, and output is JSON
+/// unless the config asks for something readable. Metrics and error reports see events
+/// on their own terms, whatever `RUST_LOG` says.
+pub fn init(config: &Config) -> Option<ReportReceiver> {
+    UNREDACTED.store(config.log.unredacted, Ordering::Relaxed);
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let output = match config.log.format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer()
+            .pretty()
+            .with_filter(filter)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_filter(filter)
+            .boxed(),
+    };
+
+    let (reports, report_rx) = reports::layer(config);
+
+    tracing_subscriber::registry()
+        .with(output)
+        .with(metrics::layer())
+        .with(reports)
+        .init();
+
+    report_rx
+}
+
+/// Sets up logging for the other commands, which print their own output, so only
+/// warnings go to stderr unless `RUST_LOG` asks for more.
+pub fn init_cli(config: &Config) {
+    UNREDACTED.store(config.log.unredacted, Ordering::Relaxed);
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(filter)
+        .init();
+}
+
+/// User content that only shows in logs when redaction is turned off.
+pub struct Redacted<'a>(&'a str);
+
+pub fn redact(text: &str) -> Redacted<'_> {
+    Redacted(text)
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if UNREDACTED.load(Ordering::Relaxed) {
+            f.write_str(self.0)
+        } else {
+            write!(f, "[redacted, {} chars]", self.0.chars().count())
+        }
+    }
+}
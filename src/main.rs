@@ -1,34 +1,195 @@
 use std::sync::Arc;
 
-use slack_morphism::{
-    SlackClient,
-    prelude::{SlackClientHyperConnector, SlackHyperClient},
-};
+use clap::Parser;
+use tokio::sync::watch;
+use tracing::{info, warn};
 
+use crate::cli::{Cli, Command};
+use crate::config::Config;
+use crate::slack_client::SlackApiClient;
+use crate::supervisor::{Shutdown, shutdown_signal, supervise};
+
+mod admin;
 mod bridge;
+mod cache;
+mod cli;
 mod commands;
+mod config;
+mod dashboard;
+mod discord_embeds;
+mod doctor;
+mod emoji;
+mod emoji_names;
+mod health;
+mod logging;
+mod message_links;
+mod metrics;
+mod migrations;
 mod redis;
+mod reports;
+mod settings;
+mod slack_blocks;
+mod slack_client;
 mod sources;
+mod split;
+mod supervisor;
 
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    let config = match Config::load() {
+        Ok(config) => Arc::new(config),
+        Err(errors) => {
+            eprintln!("Invalid configuration:");
+            for error in errors {
+                eprintln!("  {error}");
+            }
+            std::process::exit(1);
+        }
+    };
+
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => run(config).await,
+        command => {
+            logging::init_cli(&config);
+            if let Err(e) = cli::execute(command, config).await {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Runs the bridge until it's told to stop.
+async fn run(config: Arc<Config>) {
+    let report_rx = logging::init(&config);
 
     let (channels, discord_rx, slack_rx) = bridge::create_bridge();
-    let redis_client = redis::RedisClient::new()
+    let redis_client = redis::RedisClient::new(&config.redis_url)
         .await
         .expect("Failed to connect to Redis");
+    match migrations::pending(&redis_client).await {
+        Ok(pending) if !pending.is_empty() => {
+            warn!(
+                pending = pending.len(),
+                "Redis schema is out of date, run `carmine migrate`"
+            );
+        }
+        Ok(_) => {}
+        Err(e) => warn!(error = %e, "Failed to check the Redis schema version"),
+    }
+
+    let slack_client: Arc<SlackApiClient> = Arc::new(slack_client::new_client().unwrap());
 
-    let slack_client: Arc<SlackHyperClient> =
-        Arc::new(SlackClient::new(SlackClientHyperConnector::new().unwrap()));
+    let (discord_tx, discord_client) = watch::channel(None);
+    let slack_emoji = Arc::new(cache::slack_emoji::SlackEmojiCache::default());
+    let shutdown = Shutdown::default();
 
-    tokio::join!(
-        sources::discord::start(
+    let discord = tokio::spawn(supervise("discord", shutdown.ingress.clone(), {
+        let (config, channels, redis_client, slack_client, slack_emoji) = (
+            config.clone(),
             channels.clone(),
-            discord_rx,
             redis_client.clone(),
-            slack_client.clone()
-        ),
-        sources::slack::start(channels, slack_rx, redis_client, slack_client)
-    );
+            slack_client.clone(),
+            slack_emoji.clone(),
+        );
+        let stop = shutdown.ingress.clone();
+        move || {
+            sources::discord::run(
+                config.clone(),
+                channels.clone(),
+                redis_client.clone(),
+                slack_client.clone(),
+                slack_emoji.clone(),
+                discord_tx.clone(),
+                stop.clone(),
+            )
+        }
+    }));
+
+    let replays = tokio::spawn(supervise("replays", shutdown.ingress.clone(), {
+        let (channels, redis_client) = (channels.clone(), redis_client.clone());
+        let stop = shutdown.ingress.clone();
+        move || bridge::listen_for_replays(redis_client.clone(), channels.clone(), stop.clone())
+    }));
+
+    let slack = tokio::spawn(supervise("slack", shutdown.ingress.clone(), {
+        let (config, redis_client, slack_client, discord_client) = (
+            config.clone(),
+            redis_client.clone(),
+            slack_client.clone(),
+            discord_client.clone(),
+        );
+        let stop = shutdown.ingress.clone();
+        move || {
+            sources::slack::serve(
+                config.clone(),
+                channels.clone(),
+                redis_client.clone(),
+                slack_client.clone(),
+                discord_client.clone(),
+                stop.clone(),
+            )
+        }
+    }));
+
+    let discord_delivery = tokio::spawn(supervise("discord delivery", shutdown.drain.clone(), {
+        let (config, redis_client) = (config.clone(), redis_client.clone());
+        let drain = shutdown.drain.clone();
+        move || {
+            sources::discord::deliver(
+                config.clone(),
+                discord_rx.clone(),
+                discord_client.clone(),
+                redis_client.clone(),
+                drain.clone(),
+            )
+        }
+    }));
+
+    if let Some(report_rx) = report_rx {
+        let (config, slack_client) = (config.clone(), slack_client.clone());
+        let stop = shutdown.drain.clone();
+        tokio::spawn(supervise("reports", shutdown.drain.clone(), move || {
+            reports::run(
+                config.clone(),
+                report_rx.clone(),
+                slack_client.clone(),
+                stop.clone(),
+            )
+        }));
+    }
+
+    let slack_delivery = tokio::spawn(supervise("slack delivery", shutdown.drain.clone(), {
+        let drain = shutdown.drain.clone();
+        move || {
+            sources::slack::deliver(
+                config.clone(),
+                slack_rx.clone(),
+                redis_client.clone(),
+                slack_client.clone(),
+                slack_emoji.clone(),
+                drain.clone(),
+            )
+        }
+    }));
+
+    shutdown_signal().await;
+    info!("Shutting down");
+
+    // Stop taking new events before draining, so the queues can only shrink
+    shutdown.ingress.cancel();
+    let _ = tokio::join!(discord, slack, replays);
+
+    shutdown.drain.cancel();
+    let drained = tokio::time::timeout(supervisor::DRAIN_TIMEOUT, async {
+        tokio::join!(discord_delivery, slack_delivery)
+    })
+    .await;
+    if drained.is_err() {
+        warn!("Gave up on undelivered events");
+    }
+    info!("Shut down");
 }
@@ -0,0 +1,58 @@
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static DISCORD_MESSAGE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"https://(?:(?:ptb|canary)\.)?discord(?:app)?\.com/channels/(?:\d+|@me)/\d+/(\d+)")
+        .unwrap()
+});
+
+static SLACK_PERMALINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"https://[\w.-]+\.slack\.com/archives/[A-Z0-9]+/p(\d{10})(\d{6})(?:\?[^\s|>]*)?")
+        .unwrap()
+});
+
+/// A link to a message found in message text.
+pub struct MessageLink {
+    /// Where the whole link sits in the text.
+    pub range: Range<usize>,
+    /// The linked message's Discord ID or Slack timestamp.
+    pub message_id: String,
+}
+
+pub fn discord_message_links(text: &str) -> Vec<MessageLink> {
+    DISCORD_MESSAGE_LINK
+        .captures_iter(text)
+        .map(|captures| MessageLink {
+            range: captures.get(0).unwrap().range(),
+            message_id: captures[1].to_string(),
+        })
+        .collect()
+}
+
+/// Slack permalinks carry the message timestamp without its dot, e.g. `p1712345678123456`.
+pub fn slack_permalinks(text: &str) -> Vec<MessageLink> {
+    SLACK_PERMALINK
+        .captures_iter(text)
+        .map(|captures| MessageLink {
+            range: captures.get(0).unwrap().range(),
+            message_id: format!("{}.{}", &captures[1], &captures[2]),
+        })
+        .collect()
+}
+
+/// Replaces each range in `text`; the ranges must be in order and not overlap.
+pub fn replace_ranges(text: &str, replacements: Vec<(Range<usize>, String)>) -> String {
+    let mut rewritten = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (range, replacement) in replacements {
+        rewritten.push_str(&text[last..range.start]);
+        rewritten.push_str(&replacement);
+        last = range.end;
+    }
+    rewritten.push_str(&text[last..]);
+
+    rewritten
+}
@@ -0,0 +1,358 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use prometheus::{
+    Encoder, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, TextEncoder,
+    exponential_buckets, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge_vec,
+};
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::bridge::{Direction, EventType};
+
+static EVENTS_RECEIVED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "carmine_events_received_total",
+        "Events picked up from either platform",
+        &["direction", "event_type"]
+    )
+    .unwrap()
+});
+
+static EVENTS_DELIVERED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "carmine_events_delivered_total",
+        "Events delivered to the other platform",
+        &["direction", "event_type"]
+    )
+    .unwrap()
+});
+
+static EVENTS_DROPPED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "carmine_events_dropped_total",
+        "Events that weren't delivered, by why",
+        &["direction", "event_type", "reason"]
+    )
+    .unwrap()
+});
+
+static QUEUED_EVENTS_DROPPED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "carmine_queued_events_dropped_total",
+        "Events dropped from a paused link's queue because it was full"
+    )
+    .unwrap()
+});
+
+static FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "carmine_failures_total",
+        "Failed attempts at sending a message, by why",
+        &["direction", "kind"]
+    )
+    .unwrap()
+});
+
+static QUEUE_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "carmine_queue_depth",
+        "Events waiting to be delivered",
+        &["direction"]
+    )
+    .unwrap()
+});
+
+static DELIVERY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "carmine_delivery_duration_seconds",
+        "Time taken to deliver an event",
+        &["direction"]
+    )
+    .unwrap()
+});
+
+static API_CALLS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "carmine_api_calls_total",
+        "Requests made to the Slack and Discord APIs",
+        &["platform", "method"]
+    )
+    .unwrap()
+});
+
+static RATE_LIMITS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "carmine_rate_limits_total",
+        "Times a Slack or Discord rate limit was hit",
+        &["platform"]
+    )
+    .unwrap()
+});
+
+static REDIS_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "carmine_redis_command_duration_seconds",
+        "Time taken by Redis commands",
+        &["command"],
+        exponential_buckets(0.0005, 2.0, 12).unwrap()
+    )
+    .unwrap()
+});
+
+/// Hours of per-link traffic kept for the dashboard.
+const TRAFFIC_HOURS: u64 = 24;
+
+/// Deliveries per link by the hour. Links would make too many Prometheus series, so this
+/// stays in memory for the dashboard and starts over on restart.
+static LINK_TRAFFIC: LazyLock<Mutex<HashMap<String, VecDeque<TrafficHour>>>> =
+    LazyLock::new(Default::default);
+
+/// Serves everything registered above in the Prometheus text format.
+pub async fn serve() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut body) {
+        tracing::error!(error = %e, "Failed to encode metrics");
+    }
+
+    ([(CONTENT_TYPE, encoder.format_type().to_string())], body)
+}
+
+pub fn event_received(direction: Direction, event_type: &EventType) {
+    EVENTS_RECEIVED
+        .with_label_values(&[direction.label(), event_type.kind()])
+        .inc();
+}
+
+/// A send that failed, including ones that will be tried again. `kind` is one of the
+/// fixed set on `DeliveryFailure`.
+pub fn send_failed(direction: Direction, kind: &str) {
+    FAILURES.with_label_values(&[direction.label(), kind]).inc();
+}
+
+pub fn api_call(platform: &str, method: &str) {
+    API_CALLS.with_label_values(&[platform, method]).inc();
+}
+
+pub fn queued_events_dropped(count: usize) {
+    QUEUED_EVENTS_DROPPED.inc_by(count as u64);
+}
+
+pub fn set_queue_depth(direction: Direction, depth: usize) {
+    QUEUE_DEPTH
+        .with_label_values(&[direction.label()])
+        .set(depth as i64);
+}
+
+/// Events waiting in the bridge channel, as last reported.
+pub fn queue_depth(direction: Direction) -> i64 {
+    QUEUE_DEPTH.with_label_values(&[direction.label()]).get()
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct TrafficHour {
+    /// Start of the hour, in seconds since the epoch.
+    pub hour: u64,
+    pub delivered: u64,
+    pub failed: u64,
+}
+
+/// The link's last day of traffic, oldest first, with quiet hours filled in.
+pub fn link_traffic(discord_channel_id: u64, slack_channel_id: &str) -> Vec<TrafficHour> {
+    let now = current_hour();
+    let traffic = LINK_TRAFFIC.lock().unwrap();
+    let recorded = traffic.get(&format!("{discord_channel_id}:{slack_channel_id}"));
+
+    (now + 1 - TRAFFIC_HOURS..=now)
+        .map(|hour| hour * 3600)
+        .map(|hour| {
+            recorded
+                .and_then(|hours| hours.iter().find(|recorded| recorded.hour == hour))
+                .copied()
+                .unwrap_or(TrafficHour {
+                    hour,
+                    delivered: 0,
+                    failed: 0,
+                })
+        })
+        .collect()
+}
+
+fn record_traffic(link: &str, failed: bool) {
+    let hour = current_hour() * 3600;
+    let mut traffic = LINK_TRAFFIC.lock().unwrap();
+    let hours = traffic.entry(link.to_string()).or_default();
+
+    if hours.back().is_none_or(|last| last.hour != hour) {
+        hours.push_back(TrafficHour {
+            hour,
+            delivered: 0,
+            failed: 0,
+        });
+    }
+    while hours
+        .front()
+        .is_some_and(|first| first.hour + TRAFFIC_HOURS * 3600 <= hour)
+    {
+        hours.pop_front();
+    }
+
+    let current = hours.back_mut().unwrap();
+    if failed {
+        current.failed += 1;
+    } else {
+        current.delivered += 1;
+    }
+}
+
+fn current_hour() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / 3600
+}
+
+pub fn rate_limited(platform: &str) {
+    RATE_LIMITS.with_label_values(&[platform]).inc();
+}
+
+pub fn redis_command(command: &str, elapsed: Duration) {
+    REDIS_DURATION
+        .with_label_values(&[command])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Derives delivery outcomes and latency from the `deliver` span.
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    MetricsLayer.with_filter(Targets::new().with_target("carmine", Level::INFO))
+}
+
+struct MetricsLayer;
+
+/// Tracked on each `deliver` span until it closes.
+struct Delivery {
+    start: Instant,
+    direction: String,
+    event_type: String,
+    link: Option<String>,
+    outcome: Option<String>,
+}
+
+#[derive(Default)]
+struct Fields {
+    direction: Option<String>,
+    event_type: Option<String>,
+    link: Option<String>,
+    outcome: Option<String>,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "direction" => self.direction = Some(value.to_string()),
+            "event_type" => self.event_type = Some(value.to_string()),
+            "link" => self.link = Some(value.to_string()),
+            "outcome" => self.outcome = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "link" {
+            self.link = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "deliver" {
+            return;
+        }
+
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Delivery {
+                start: Instant::now(),
+                direction: fields.direction.unwrap_or_default(),
+                event_type: fields.event_type.unwrap_or_default(),
+                link: fields.link,
+                outcome: fields.outcome,
+            });
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(delivery) = extensions.get_mut::<Delivery>() else {
+            return;
+        };
+
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if fields.link.is_some() {
+            delivery.link = fields.link;
+        }
+        if fields.outcome.is_some() {
+            delivery.outcome = fields.outcome;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(delivery) = extensions.get::<Delivery>() else {
+            return;
+        };
+
+        DELIVERY_DURATION
+            .with_label_values(&[&delivery.direction])
+            .observe(delivery.start.elapsed().as_secs_f64());
+
+        // Failed deliveries say so, everything else that was delivered has no outcome
+        let failed = delivery.outcome.as_deref() == Some("failed");
+        if let Some(link) = &delivery.link
+            && (failed || delivery.outcome.is_none())
+        {
+            record_traffic(link, failed);
+        }
+
+        let labels = [delivery.direction.as_str(), delivery.event_type.as_str()];
+        let reason = if let Some(outcome) = &delivery.outcome {
+            outcome
+        } else if delivery.link.is_none() {
+            "unlinked"
+        } else {
+            EVENTS_DELIVERED.with_label_values(&labels).inc();
+            return;
+        };
+        EVENTS_DROPPED
+            .with_label_values(&[labels[0], labels[1], reason])
+            .inc();
+    }
+}
@@ -0,0 +1,148 @@
+use redis::RedisResult;
+
+use crate::redis::{RedisClient, parse_discord_message};
+
+/// A change to how data is laid out in Redis, applied once by `carmine migrate`.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+}
+
+/// In order. Each one only adds what's missing, so running one twice is harmless.
+pub const MIGRATIONS: [Migration; 2] = [
+    Migration {
+        version: 1,
+        description: "index links made before links were listed",
+    },
+    Migration {
+        version: 2,
+        description: "index message mappings made before unlink could purge them",
+    },
+];
+
+pub const CURRENT_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Migrations not applied yet. A store without a version that holds nothing to migrate
+/// is a new install, so it's stamped with the current version instead.
+pub async fn pending(redis_client: &RedisClient) -> RedisResult<Vec<&'static Migration>> {
+    let version = match redis_client.get_schema_version().await? {
+        Some(version) => version,
+        None if is_empty(redis_client).await? => {
+            redis_client.set_schema_version(CURRENT_VERSION).await?;
+            CURRENT_VERSION
+        }
+        None => 0,
+    };
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > version)
+        .collect())
+}
+
+/// Whether the store has none of the links or message mappings the migrations change.
+async fn is_empty(redis_client: &RedisClient) -> RedisResult<bool> {
+    Ok(redis_client
+        .scan_keys("discord_channel:*:slack")
+        .await?
+        .is_empty()
+        && redis_client.scan_keys("discord_msg:*").await?.is_empty())
+}
+
+/// Applies a migration, or with `dry_run` only counts what it would change. Returns how
+/// many records it changed.
+pub async fn apply(
+    redis_client: &RedisClient,
+    migration: &Migration,
+    dry_run: bool,
+) -> RedisResult<usize> {
+    let changed = match migration.version {
+        1 => index_links(redis_client, dry_run).await?,
+        2 => index_message_mappings(redis_client, dry_run).await?,
+        _ => unreachable!("unknown migration {}", migration.version),
+    };
+
+    if !dry_run {
+        redis_client.set_schema_version(migration.version).await?;
+    }
+    Ok(changed)
+}
+
+async fn index_links(redis_client: &RedisClient, dry_run: bool) -> RedisResult<usize> {
+    let links = redis_client.list_links().await?;
+    let mut changed = 0;
+
+    for key in redis_client.scan_keys("discord_channel:*:slack").await? {
+        let Some(discord_channel_id) = key
+            .strip_prefix("discord_channel:")
+            .and_then(|key| key.strip_suffix(":slack"))
+            .and_then(|id| id.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let Some(slack_channel_id) = redis_client
+            .get_linked_slack_channel(discord_channel_id)
+            .await?
+        else {
+            continue;
+        };
+
+        if dry_run {
+            changed += usize::from(!links.contains(&(discord_channel_id, slack_channel_id)));
+        } else if redis_client
+            .index_link(discord_channel_id, &slack_channel_id)
+            .await?
+        {
+            changed += 1;
+        }
+    }
+
+    Ok(changed)
+}
+
+async fn index_message_mappings(redis_client: &RedisClient, dry_run: bool) -> RedisResult<usize> {
+    let mut changed = 0;
+
+    for key in redis_client.scan_keys("discord_msg:*").await? {
+        let Some(discord_message_id) = key
+            .strip_prefix("discord_msg:")
+            .and_then(|id| id.parse::<u64>().ok())
+        else {
+            continue;
+        };
+
+        // The Discord side only records the Slack message, whose own mapping has the channel
+        let Some(slack_info) = redis_client.get_slack_message(discord_message_id).await? else {
+            continue;
+        };
+        let Some((slack_channel_id, slack_message_ts)) = slack_info.split_once(':') else {
+            continue;
+        };
+        let Some((discord_channel_id, _)) = redis_client
+            .get_discord_message(slack_message_ts)
+            .await?
+            .as_deref()
+            .and_then(parse_discord_message)
+        else {
+            continue;
+        };
+
+        if dry_run {
+            changed += usize::from(
+                !redis_client
+                    .is_message_mapping_indexed(
+                        discord_channel_id,
+                        slack_channel_id,
+                        discord_message_id,
+                    )
+                    .await?,
+            );
+        } else if redis_client
+            .index_message_mapping(discord_channel_id, slack_channel_id, discord_message_id)
+            .await?
+        {
+            changed += 1;
+        }
+    }
+
+    Ok(changed)
+}
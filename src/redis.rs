@@ -1,5 +1,26 @@
-use redis::{AsyncTypedCommands, ErrorKind, RedisError};
+use std::time::{Duration, Instant};
+
+use redis::aio::{ConnectionLike, MultiplexedConnection, PubSubStream};
+use redis::{Arg, AsyncTypedCommands, Cmd, ErrorKind, Pipeline, RedisError, RedisFuture, Value};
 use redis::{Client, RedisResult};
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use crate::bridge::{DeadLetter, QueuedEvent};
+use crate::cache::slack_users::SlackProfile;
+use crate::dashboard::Session;
+use crate::metrics;
+use crate::settings::{LinkSettings, LinkState};
+
+const LINKS_KEY: &str = "links";
+const DEAD_LETTERS_KEY: &str = "dead_letters";
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Where `carmine replay-dlq` asks running bridges to replay the dead letters.
+const REPLAY_CHANNEL: &str = "replay_dead_letters";
+
+/// Older dead letters are dropped past this many.
+const MAX_DEAD_LETTERS: isize = 1000;
 
 #[derive(Debug, Clone)]
 pub struct RedisClient {
@@ -7,15 +28,21 @@ pub struct RedisClient {
 }
 
 impl RedisClient {
-    pub async fn new() -> RedisResult<Self> {
-        let redis_url = std::env::var("REDIS_URL").expect("REDIS_URL must be set");
+    pub async fn new(redis_url: &str) -> RedisResult<Self> {
         let client = Client::open(redis_url)?;
 
         Ok(Self { client })
     }
 
-    async fn get_connection(&self) -> RedisResult<redis::aio::MultiplexedConnection> {
-        self.client.get_multiplexed_async_connection().await
+    async fn get_connection(&self) -> RedisResult<MeteredConnection> {
+        let conn = self.client.get_multiplexed_async_connection().await?;
+        Ok(MeteredConnection(conn))
+    }
+
+    /// Checks that Redis answers.
+    pub async fn ping(&self) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        redis::cmd("PING").query_async::<()>(&mut conn).await
     }
 
     // Channel linking
@@ -31,15 +58,41 @@ impl RedisClient {
 
         conn.set(&discord_key, slack_channel_id).await?;
         conn.set(&slack_key, discord_channel_id).await?;
+        // Relinking a dead link starts it fresh, keeping the settings and message mappings
+        // that were set to expire with it
+        conn.del(link_key(discord_channel_id, slack_channel_id, "state"))
+            .await?;
+        let mut keys = self
+            .link_mapping_keys(discord_channel_id, slack_channel_id)
+            .await?;
+        keys.push(link_key(discord_channel_id, slack_channel_id, "settings"));
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.persist(key).ignore();
+        }
+        pipe.query_async::<()>(&mut conn).await?;
+        conn.sadd(
+            LINKS_KEY,
+            format!("{discord_channel_id}:{slack_channel_id}"),
+        )
+        .await?;
 
         Ok(())
     }
 
+    /// Removes a link. With `purge_mappings`, the link's message mappings are deleted too;
+    /// otherwise they're kept so edits and deletes resume if the same channels are relinked.
     pub async fn unlink_channels(
         &self,
         discord_channel_id: u64,
         slack_channel_id: &str,
+        purge_mappings: bool,
     ) -> RedisResult<()> {
+        if purge_mappings {
+            self.purge_link_mappings(discord_channel_id, slack_channel_id)
+                .await?;
+        }
+
         let mut conn = self.get_connection().await?;
 
         let discord_key = format!("discord_channel:{discord_channel_id}:slack");
@@ -47,10 +100,64 @@ impl RedisClient {
 
         conn.del(&discord_key).await?;
         conn.del(&slack_key).await?;
+        conn.del(&[
+            link_key(discord_channel_id, slack_channel_id, "settings"),
+            link_key(discord_channel_id, slack_channel_id, "state"),
+            link_key(discord_channel_id, slack_channel_id, "queue"),
+        ])
+        .await?;
+        conn.srem(
+            LINKS_KEY,
+            format!("{discord_channel_id}:{slack_channel_id}"),
+        )
+        .await?;
 
         Ok(())
     }
 
+    pub async fn list_links(&self) -> RedisResult<Vec<(u64, String)>> {
+        let mut conn = self.get_connection().await?;
+
+        let mut links: Vec<_> = conn
+            .smembers(LINKS_KEY)
+            .await?
+            .iter()
+            .filter_map(|link| {
+                let (discord_channel_id, slack_channel_id) = link.split_once(':')?;
+                Some((
+                    discord_channel_id.parse().ok()?,
+                    slack_channel_id.to_string(),
+                ))
+            })
+            .collect();
+        links.sort();
+
+        // Dead links stay listed until their state expires, then drop out of the index.
+        // The Discord channel may have been linked elsewhere since, which doesn't count.
+        let mut live_links = Vec::with_capacity(links.len());
+        for (discord_channel_id, slack_channel_id) in links {
+            let linked = conn
+                .get(format!("discord_channel:{discord_channel_id}:slack"))
+                .await?
+                .is_some_and(|linked| linked == slack_channel_id);
+            let state = conn
+                .exists(link_key(discord_channel_id, &slack_channel_id, "state"))
+                .await?;
+
+            if linked || state {
+                live_links.push((discord_channel_id, slack_channel_id));
+            } else {
+                conn.srem(
+                    LINKS_KEY,
+                    format!("{discord_channel_id}:{slack_channel_id}"),
+                )
+                .await?;
+            }
+        }
+
+        Ok(live_links)
+    }
+
     pub async fn get_linked_slack_channel(
         &self,
         discord_channel_id: u64,
@@ -69,21 +176,449 @@ impl RedisClient {
         conn.get(&key).await
     }
 
+    // Link settings
+    pub async fn get_link_settings(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<LinkSettings> {
+        let mut conn = self.get_connection().await?;
+        let fields = conn
+            .hgetall(link_key(discord_channel_id, slack_channel_id, "settings"))
+            .await?;
+
+        Ok(LinkSettings::from_fields(&fields))
+    }
+
+    pub async fn set_link_settings(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+        settings: LinkSettings,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.hset_multiple(
+            link_key(discord_channel_id, slack_channel_id, "settings"),
+            &settings.to_fields(),
+        )
+        .await
+    }
+
+    // Link state
+    pub async fn get_link_state(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<LinkState> {
+        let mut conn = self.get_connection().await?;
+        let state = conn
+            .get(link_key(discord_channel_id, slack_channel_id, "state"))
+            .await?;
+
+        Ok(state
+            .as_deref()
+            .and_then(LinkState::from_key)
+            .unwrap_or_default())
+    }
+
+    pub async fn set_link_state(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+        state: LinkState,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set(
+            link_key(discord_channel_id, slack_channel_id, "state"),
+            state.key(),
+        )
+        .await
+    }
+
+    // Events held back while a link is paused
+    /// Queues an event, dropping the oldest past `max_len`. Returns how many were dropped.
+    pub async fn queue_event(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+        event: &QueuedEvent,
+        max_len: usize,
+    ) -> RedisResult<usize> {
+        let mut conn = self.get_connection().await?;
+        let payload = serde_json::to_string(event).map_err(|e| {
+            RedisError::from((
+                ErrorKind::TypeError,
+                "Failed to serialize queued event",
+                e.to_string(),
+            ))
+        })?;
+
+        let key = link_key(discord_channel_id, slack_channel_id, "queue");
+
+        let (len,): (usize,) = redis::pipe()
+            .atomic()
+            .rpush(&key, payload)
+            .ltrim(&key, -(max_len as isize), -1)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(len.saturating_sub(max_len))
+    }
+
+    /// Marks the link active and takes its queued events in one step, so nothing gets
+    /// queued after the events are taken and left behind for the next pause.
+    pub async fn activate_link(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<Vec<QueuedEvent>> {
+        let mut conn = self.get_connection().await?;
+        let key = link_key(discord_channel_id, slack_channel_id, "queue");
+
+        let (payloads,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .set(
+                link_key(discord_channel_id, slack_channel_id, "state"),
+                LinkState::Active.key(),
+            )
+            .ignore()
+            .lrange(&key, 0, -1)
+            .del(&key)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(parse_payloads(&payloads, "queued event"))
+    }
+
+    /// The link's queued events, left in place.
+    pub async fn peek_queued_events(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<Vec<QueuedEvent>> {
+        let mut conn = self.get_connection().await?;
+        let payloads = conn
+            .lrange(
+                link_key(discord_channel_id, slack_channel_id, "queue"),
+                0,
+                -1,
+            )
+            .await?;
+
+        Ok(parse_payloads(&payloads, "queued event"))
+    }
+
+    pub async fn count_queued_events(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<usize> {
+        let mut conn = self.get_connection().await?;
+        conn.llen(link_key(discord_channel_id, slack_channel_id, "queue"))
+            .await
+    }
+
+    // Events that failed for good, newest first
+    pub async fn push_dead_letter(&self, dead_letter: &DeadLetter) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        let payload = serde_json::to_string(dead_letter).map_err(|e| {
+            RedisError::from((
+                ErrorKind::TypeError,
+                "Failed to serialize dead letter",
+                e.to_string(),
+            ))
+        })?;
+
+        redis::pipe()
+            .atomic()
+            .lpush(DEAD_LETTERS_KEY, payload)
+            .ignore()
+            .ltrim(DEAD_LETTERS_KEY, 0, MAX_DEAD_LETTERS - 1)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+    }
+
+    /// The newest `limit` dead letters. No more than `MAX_DEAD_LETTERS` are kept, so a
+    /// bigger limit returns them all.
+    pub async fn list_dead_letters(&self, limit: usize) -> RedisResult<Vec<DeadLetter>> {
+        // A stop of -1 would mean the whole list
+        let limit = limit.min(MAX_DEAD_LETTERS as usize);
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.get_connection().await?;
+        let payloads = conn.lrange(DEAD_LETTERS_KEY, 0, limit as isize - 1).await?;
+
+        Ok(parse_payloads(&payloads, "dead letter"))
+    }
+
+    pub async fn count_dead_letters(&self) -> RedisResult<usize> {
+        let mut conn = self.get_connection().await?;
+        conn.llen(DEAD_LETTERS_KEY).await
+    }
+
+    /// Removes and returns every dead letter.
+    pub async fn take_dead_letters(&self) -> RedisResult<Vec<DeadLetter>> {
+        let mut conn = self.get_connection().await?;
+
+        let (payloads,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .lrange(DEAD_LETTERS_KEY, 0, -1)
+            .del(DEAD_LETTERS_KEY)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(parse_payloads(&payloads, "dead letter"))
+    }
+
+    // Schema migrations, see `migrations`
+    pub async fn get_schema_version(&self) -> RedisResult<Option<u32>> {
+        let mut conn = self.get_connection().await?;
+        let version = conn.get(SCHEMA_VERSION_KEY).await?;
+
+        Ok(version.map(|version| version.parse().unwrap_or(0)))
+    }
+
+    pub async fn set_schema_version(&self, version: u32) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set(SCHEMA_VERSION_KEY, version).await?;
+        Ok(())
+    }
+
+    /// Every key matching `pattern`. Scanned rather than listed with `KEYS`, so a big
+    /// store isn't blocked while it's walked.
+    pub async fn scan_keys(&self, pattern: &str) -> RedisResult<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        let mut keys = conn.scan_match::<_, String>(pattern).await?;
+
+        let mut found = Vec::new();
+        while let Some(key) = keys.next_item().await {
+            found.push(key);
+        }
+        Ok(found)
+    }
+
+    /// Adds a link to the set `list_links` reads, returning whether it was missing.
+    pub async fn index_link(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<bool> {
+        let mut conn = self.get_connection().await?;
+        let added = conn
+            .sadd(
+                LINKS_KEY,
+                format!("{discord_channel_id}:{slack_channel_id}"),
+            )
+            .await?;
+
+        Ok(added > 0)
+    }
+
+    /// Adds a message to its link's mappings, which purging reads, returning whether it
+    /// was missing.
+    pub async fn index_message_mapping(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+        discord_message_id: u64,
+    ) -> RedisResult<bool> {
+        let mut conn = self.get_connection().await?;
+        let added = conn
+            .sadd(
+                link_key(discord_channel_id, slack_channel_id, "mappings"),
+                discord_message_id,
+            )
+            .await?;
+
+        Ok(added > 0)
+    }
+
+    pub async fn is_message_mapping_indexed(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+        discord_message_id: u64,
+    ) -> RedisResult<bool> {
+        let mut conn = self.get_connection().await?;
+        conn.sismember(
+            link_key(discord_channel_id, slack_channel_id, "mappings"),
+            discord_message_id,
+        )
+        .await
+    }
+
+    // Dashboard sessions, by the token in the session cookie
+    pub async fn get_dashboard_session(&self, token: &str) -> RedisResult<Option<Session>> {
+        let mut conn = self.get_connection().await?;
+
+        let Some(payload) = conn.get(format!("dashboard_session:{token}")).await? else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str(&payload) {
+            Ok(session) => Ok(Some(session)),
+            Err(e) => {
+                warn!(error = %e, "Ignoring invalid dashboard session");
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn set_dashboard_session(
+        &self,
+        token: &str,
+        session: &Session,
+        ttl: Duration,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        let payload = serde_json::to_string(session).map_err(|e| {
+            RedisError::from((
+                ErrorKind::TypeError,
+                "Failed to serialize dashboard session",
+                e.to_string(),
+            ))
+        })?;
+
+        conn.set_ex(format!("dashboard_session:{token}"), payload, ttl.as_secs())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_dashboard_session(&self, token: &str) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+
+        conn.del(format!("dashboard_session:{token}")).await?;
+        Ok(())
+    }
+
+    /// Asks every running bridge to replay the dead letters, returning how many heard.
+    pub async fn request_replay(&self) -> RedisResult<usize> {
+        let mut conn = self.get_connection().await?;
+        conn.publish(REPLAY_CHANNEL, "").await
+    }
+
+    /// Replay requests as they're published, until the connection drops.
+    pub async fn replay_requests(&self) -> RedisResult<PubSubStream> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(REPLAY_CHANNEL).await?;
+
+        Ok(pubsub.into_on_message())
+    }
+
+    // Discord webhooks, stored as "id:token"
+    pub async fn get_discord_webhook(
+        &self,
+        discord_channel_id: u64,
+    ) -> RedisResult<Option<String>> {
+        let mut conn = self.get_connection().await?;
+        conn.get(format!("discord_channel:{discord_channel_id}:webhook"))
+            .await
+    }
+
+    pub async fn set_discord_webhook(
+        &self,
+        discord_channel_id: u64,
+        webhook: &str,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set(
+            format!("discord_channel:{discord_channel_id}:webhook"),
+            webhook,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_discord_webhook(&self, discord_channel_id: u64) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.del(format!("discord_channel:{discord_channel_id}:webhook"))
+            .await?;
+        Ok(())
+    }
+
+    // Slack user profiles
+    pub async fn get_slack_profile(
+        &self,
+        team_id: &str,
+        user_id: &str,
+    ) -> RedisResult<Option<SlackProfile>> {
+        let mut conn = self.get_connection().await?;
+
+        let Some(payload) = conn.get(slack_profile_key(team_id, user_id)).await? else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str(&payload) {
+            Ok(profile) => Ok(Some(profile)),
+            Err(e) => {
+                warn!(error = %e, "Ignoring invalid cached Slack profile");
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn set_slack_profile(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        profile: &SlackProfile,
+        ttl: Duration,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        let payload = serde_json::to_string(profile).map_err(|e| {
+            RedisError::from((
+                ErrorKind::TypeError,
+                "Failed to serialize Slack profile",
+                e.to_string(),
+            ))
+        })?;
+
+        conn.set_ex(slack_profile_key(team_id, user_id), payload, ttl.as_secs())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_slack_profile(&self, team_id: &str, user_id: &str) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+
+        conn.del(slack_profile_key(team_id, user_id)).await?;
+        Ok(())
+    }
+
     // Message mapping methods
+    /// Maps a Slack message to the Discord messages it was sent as. Long messages can
+    /// take several Discord messages, which all map back to the same Slack message.
     pub async fn store_message_mapping(
         &self,
         discord_channel_id: u64,
-        discord_message_id: u64,
+        discord_message_ids: &[u64],
         slack_channel_id: &str,
         slack_message_ts: &str,
     ) -> RedisResult<()> {
         let mut conn = self.get_connection().await?;
 
-        let discord_info = format!("{discord_channel_id}:{discord_message_id}");
+        let message_ids = discord_message_ids
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let discord_info = format!("{discord_channel_id}:{message_ids}");
         let slack_info = format!("{slack_channel_id}:{slack_message_ts}");
 
-        conn.set(format!("discord_msg:{discord_message_id}"), &slack_info)
+        for discord_message_id in discord_message_ids {
+            conn.set(format!("discord_msg:{discord_message_id}"), &slack_info)
+                .await?;
+            conn.sadd(
+                link_key(discord_channel_id, slack_channel_id, "mappings"),
+                discord_message_id,
+            )
             .await?;
+        }
         conn.set(format!("slack_msg:{slack_message_ts}"), &discord_info)
             .await?;
 
@@ -95,33 +630,39 @@ impl RedisClient {
         conn.get(format!("discord_msg:{discord_message_id}")).await
     }
 
+    /// Returns the raw `channel:id,id,...` mapping, see [`parse_discord_message`].
     pub async fn get_discord_message(&self, slack_message_ts: &str) -> RedisResult<Option<String>> {
         let mut conn = self.get_connection().await?;
         conn.get(format!("slack_msg:{slack_message_ts}")).await
     }
 
+    /// Remembers what the bridged copy of a Slack message currently shows.
+    pub async fn set_content_hash(&self, slack_message_ts: &str, hash: &str) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set(format!("slack_msg:{slack_message_ts}:hash"), hash)
+            .await
+    }
+
+    pub async fn get_content_hash(&self, slack_message_ts: &str) -> RedisResult<Option<String>> {
+        let mut conn = self.get_connection().await?;
+        conn.get(format!("slack_msg:{slack_message_ts}:hash")).await
+    }
+
     pub async fn delete_message_mapping_from_slack(
         &self,
         slack_message_ts: &str,
     ) -> RedisResult<()> {
-        let mut conn = self.get_connection().await?;
-
-        // Delete discord message mapping first
+        // Delete discord message mappings first
         if let Some(discord_info) = self.get_discord_message(slack_message_ts).await? {
-            match discord_info.split(':').collect::<Vec<_>>().as_slice() {
-                [_, message_id] => {
-                    conn.del(format!("discord_msg:{message_id}")).await?;
-                }
-                _ => {
-                    return Err(RedisError::from((
-                        ErrorKind::TypeError,
-                        "Invalid Discord message mapping format",
-                    )));
-                }
-            }
+            self.delete_discord_parts(&discord_info).await?;
         }
 
-        conn.del(format!("slack_msg:{slack_message_ts}")).await?;
+        let mut conn = self.get_connection().await?;
+        conn.del(&[
+            format!("slack_msg:{slack_message_ts}"),
+            format!("slack_msg:{slack_message_ts}:hash"),
+        ])
+        .await?;
         Ok(())
     }
 
@@ -131,11 +672,19 @@ impl RedisClient {
     ) -> RedisResult<()> {
         let mut conn = self.get_connection().await?;
 
-        // Delete slack message mapping first
+        // Delete slack message mapping first, along with any other parts of the message
         if let Some(slack_info) = self.get_slack_message(discord_message_id).await? {
             match slack_info.split(':').collect::<Vec<_>>().as_slice() {
                 [_, slack_message_ts] => {
-                    conn.del(format!("slack_msg:{slack_message_ts}")).await?;
+                    if let Some(discord_info) = self.get_discord_message(slack_message_ts).await? {
+                        self.delete_discord_parts(&discord_info).await?;
+                    }
+
+                    conn.del(&[
+                        format!("slack_msg:{slack_message_ts}"),
+                        format!("slack_msg:{slack_message_ts}:hash"),
+                    ])
+                    .await?;
                 }
                 _ => {
                     return Err(RedisError::from((
@@ -150,4 +699,194 @@ impl RedisClient {
             .await?;
         Ok(())
     }
+
+    /// Deletes the Discord side of a mapping and removes it from its link's index.
+    async fn delete_discord_parts(&self, discord_info: &str) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+
+        let Some((discord_channel_id, message_ids)) = parse_discord_message(discord_info) else {
+            return Err(RedisError::from((
+                ErrorKind::TypeError,
+                "Invalid Discord message mapping format",
+            )));
+        };
+
+        for message_id in message_ids {
+            if let Some(slack_info) = conn.get(format!("discord_msg:{message_id}")).await?
+                && let Some((slack_channel_id, _)) = slack_info.split_once(':')
+            {
+                conn.srem(
+                    link_key(discord_channel_id, slack_channel_id, "mappings"),
+                    message_id,
+                )
+                .await?;
+            }
+
+            conn.del(format!("discord_msg:{message_id}")).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Every message mapping key recorded for a link, followed by the link's mapping index.
+    async fn link_mapping_keys(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        let index_key = link_key(discord_channel_id, slack_channel_id, "mappings");
+
+        let mut keys = Vec::new();
+        for discord_message_id in conn.smembers(&index_key).await? {
+            let discord_key = format!("discord_msg:{discord_message_id}");
+            if let Some(slack_info) = conn.get(&discord_key).await?
+                && let Some((_, slack_message_ts)) = slack_info.split_once(':')
+            {
+                keys.push(format!("slack_msg:{slack_message_ts}"));
+                keys.push(format!("slack_msg:{slack_message_ts}:hash"));
+            }
+            keys.push(discord_key);
+        }
+        keys.push(index_key);
+
+        Ok(keys)
+    }
+
+    /// Deletes every message mapping recorded for a link.
+    pub async fn purge_link_mappings(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+
+        let keys = self
+            .link_mapping_keys(discord_channel_id, slack_channel_id)
+            .await?;
+        conn.del(&keys).await?;
+
+        Ok(())
+    }
+
+    /// Marks a link dead after one of its channels went away, unlinking both channels.
+    /// The link's record and message mappings are kept for `retention`, then expire, and
+    /// `list_links` drops it from the index once they have.
+    pub async fn mark_link_dead(
+        &self,
+        discord_channel_id: u64,
+        slack_channel_id: &str,
+        retention: Duration,
+    ) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+
+        conn.del(&[
+            format!("discord_channel:{discord_channel_id}:slack"),
+            format!("slack_channel:{slack_channel_id}:discord"),
+            link_key(discord_channel_id, slack_channel_id, "queue"),
+        ])
+        .await?;
+        conn.set(
+            link_key(discord_channel_id, slack_channel_id, "state"),
+            LinkState::Dead.key(),
+        )
+        .await?;
+
+        let mut keys = self
+            .link_mapping_keys(discord_channel_id, slack_channel_id)
+            .await?;
+        keys.push(link_key(discord_channel_id, slack_channel_id, "settings"));
+        keys.push(link_key(discord_channel_id, slack_channel_id, "state"));
+
+        let seconds = retention.as_secs();
+        if seconds == 0 {
+            conn.del(&keys).await?;
+            conn.srem(
+                LINKS_KEY,
+                format!("{discord_channel_id}:{slack_channel_id}"),
+            )
+            .await?;
+        } else {
+            let mut pipe = redis::pipe();
+            for key in &keys {
+                pipe.expire(key, seconds as i64).ignore();
+            }
+            pipe.query_async::<()>(&mut conn).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes stored JSON, skipping anything that no longer parses.
+fn parse_payloads<T: DeserializeOwned>(payloads: &[String], what: &str) -> Vec<T> {
+    payloads
+        .iter()
+        .filter_map(|payload| match serde_json::from_str(payload) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!(error = %e, "Dropping invalid {what}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Splits a `channel:id,id,...` Discord mapping into its channel and message IDs.
+pub fn parse_discord_message(discord_info: &str) -> Option<(u64, Vec<u64>)> {
+    let (channel_id, message_ids) = discord_info.split_once(':')?;
+    let message_ids = message_ids
+        .split(',')
+        .map(str::parse)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+
+    Some((channel_id.parse().ok()?, message_ids))
+}
+
+/// A connection that times each command it sends for the metrics.
+struct MeteredConnection(MultiplexedConnection);
+
+impl ConnectionLike for MeteredConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = self.0.req_packed_command(cmd).await;
+
+            let command = match cmd.args_iter().next() {
+                Some(Arg::Simple(name)) => std::str::from_utf8(name).unwrap_or("unknown"),
+                _ => "unknown",
+            };
+            metrics::redis_command(command, start.elapsed());
+
+            result
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        pipeline: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = self.0.req_packed_commands(pipeline, offset, count).await;
+            metrics::redis_command("PIPELINE", start.elapsed());
+
+            result
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.0.get_db()
+    }
+}
+
+fn link_key(discord_channel_id: u64, slack_channel_id: &str, suffix: &str) -> String {
+    format!("link:{discord_channel_id}:{slack_channel_id}:{suffix}")
+}
+
+fn slack_profile_key(team_id: &str, user_id: &str) -> String {
+    format!("slack_user:{team_id}:{user_id}:profile")
 }
@@ -0,0 +1,520 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use poise::serenity_prelude as serenity;
+use serde::Serialize;
+use slack_morphism::prelude::{SlackApiChatPostMessageRequest, SlackMessageContent};
+use tokio::sync::{Mutex, mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber, warn};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::Config;
+use crate::metrics;
+use crate::slack_client::SlackApiClient;
+use crate::sources::discord::{self, Error};
+use crate::split::{DISCORD_MESSAGE_LIMIT, SLACK_MESSAGE_LIMIT, split_message};
+
+/// The same error on the same link is only reported once in this long.
+const DEDUP_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Reports beyond this many a minute are held back and counted in the next one.
+const MAX_REPORTS_PER_MINUTE: usize = 10;
+
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How many errors and links the daily summary lists before lumping in the rest.
+const SUMMARY_TOP: usize = 10;
+
+/// How many of the latest errors the dashboard can show.
+const RECENT_ERRORS: usize = 50;
+
+static RECENT: LazyLock<std::sync::Mutex<VecDeque<Report>>> = LazyLock::new(Default::default);
+
+/// Errors waiting to be reported, shared so a restarted reporter keeps the same queue.
+pub type ReportReceiver = Arc<Mutex<mpsc::UnboundedReceiver<Report>>>;
+
+/// An error logged by the bridge, with whatever the delivery it happened in knew.
+#[derive(Clone, Serialize)]
+pub struct Report {
+    /// Seconds since the epoch.
+    at: u64,
+    message: String,
+    error: Option<String>,
+    link: Option<String>,
+    event_type: Option<String>,
+    trace: Option<String>,
+}
+
+/// Picks error events out of tracing, keeping the latest for the dashboard and queueing
+/// them for the admin channels when any are configured.
+pub fn layer<S>(config: &Config) -> (impl Layer<S>, Option<ReportReceiver>)
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let targets = Targets::new().with_target("carmine", Level::INFO);
+    if !config.admin.reports_enabled() {
+        return (ReportLayer { tx: None }.with_filter(targets), None);
+    }
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    (
+        ReportLayer { tx: Some(tx) }.with_filter(targets),
+        Some(Arc::new(Mutex::new(rx))),
+    )
+}
+
+/// The latest errors, newest first.
+pub fn recent_errors() -> Vec<Report> {
+    RECENT.lock().unwrap().iter().rev().cloned().collect()
+}
+
+struct ReportLayer {
+    tx: Option<mpsc::UnboundedSender<Report>>,
+}
+
+/// Tracked on each `deliver` span so errors inside it can say what they were delivering.
+#[derive(Clone, Default)]
+struct Delivery {
+    link: Option<String>,
+    event_type: Option<String>,
+    trace: Option<String>,
+}
+
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    error: Option<String>,
+    delivery: Delivery,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = Some(value.to_string()),
+            "error" => self.error = Some(value.to_string()),
+            "link" => self.delivery.link = Some(value.to_string()),
+            "event_type" => self.delivery.event_type = Some(value.to_string()),
+            "trace" => self.delivery.trace = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record_str(field, &format!("{value:?}"));
+    }
+}
+
+impl<S> Layer<S> for ReportLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "deliver" {
+            return;
+        }
+
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(fields.delivery);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(delivery) = extensions.get_mut::<Delivery>() else {
+            return;
+        };
+
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if fields.delivery.link.is_some() {
+            delivery.link = fields.delivery.link;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let delivery = ctx
+            .event_scope(event)
+            .and_then(|scope| {
+                scope
+                    .into_iter()
+                    .find_map(|span| span.extensions().get::<Delivery>().cloned())
+            })
+            .unwrap_or_default();
+
+        let report = Report {
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            message: fields.message.unwrap_or_default(),
+            error: fields.error,
+            link: delivery.link,
+            event_type: delivery.event_type,
+            trace: delivery.trace,
+        };
+
+        let mut recent = RECENT.lock().unwrap();
+        if recent.len() == RECENT_ERRORS {
+            recent.pop_front();
+        }
+        recent.push_back(report.clone());
+        drop(recent);
+
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(report);
+        }
+    }
+}
+
+/// Posts reports to the admin channels until `stop` is cancelled, plus a summary of
+/// failure counts once a day.
+pub async fn run(
+    config: Arc<Config>,
+    reports: ReportReceiver,
+    slack_client: Arc<SlackApiClient>,
+    stop: CancellationToken,
+) -> Result<(), Error> {
+    let reporter = Reporter {
+        discord_http: discord::http_client(&config.discord.token),
+        slack_client,
+        config,
+    };
+    let mut reports = reports.lock().await;
+    let mut limiter = Limiter::default();
+    let mut summary = Summary::default();
+
+    let mut summary_timer = tokio::time::interval_at(
+        tokio::time::Instant::now() + SUMMARY_INTERVAL,
+        SUMMARY_INTERVAL,
+    );
+
+    loop {
+        tokio::select! {
+            report = reports.recv() => {
+                let Some(report) = report else {
+                    return Ok(());
+                };
+                summary.count(&report);
+                if let Some(held_back) = limiter.admit(&report) {
+                    reporter.post(&report.render(held_back)).await;
+                }
+            }
+            _ = summary_timer.tick() => {
+                reporter.post(&summary.render()).await;
+                summary = Summary::default();
+            }
+            () = stop.cancelled() => return Ok(()),
+        }
+    }
+}
+
+impl Report {
+    fn render(&self, held_back: HeldBack) -> String {
+        let mut text = format!("⚠️ {}", self.message);
+
+        let mut context = Vec::new();
+        if let Some(link) = &self.link {
+            context.push(format!("link `{link}`"));
+        }
+        if let Some(event_type) = &self.event_type {
+            context.push(format!("event `{event_type}`"));
+        }
+        if let Some(trace) = &self.trace {
+            context.push(format!("trace `{trace}`"));
+        }
+        if !context.is_empty() {
+            text.push('\n');
+            text.push_str(&context.join(" · "));
+        }
+
+        if let Some(error) = &self.error {
+            text.push_str(&format!("\nerror: `{error}`"));
+        }
+        if held_back.repeats > 0 {
+            text.push_str(&format!(
+                "\n(happened {} more times since it was last reported)",
+                held_back.repeats
+            ));
+        }
+        if held_back.rate_limited > 0 {
+            text.push_str(&format!(
+                "\n({} other reports held back by the rate limit, see the daily summary)",
+                held_back.rate_limited
+            ));
+        }
+        text
+    }
+}
+
+/// Reports that weren't posted since the last one that was.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct HeldBack {
+    /// The same error on the same link, inside the dedup window.
+    repeats: u64,
+    /// Any report, dropped because too many went out that minute.
+    rate_limited: u64,
+}
+
+/// Deduplicates reports by error and link, and caps how many go out a minute.
+#[derive(Default)]
+struct Limiter {
+    seen: HashMap<(String, Option<String>), Seen>,
+    sent: Vec<Instant>,
+    rate_limited: u64,
+}
+
+struct Seen {
+    reported: Instant,
+    repeats: u64,
+}
+
+impl Limiter {
+    /// Whether to post this report, and if so what was held back since the last. Repeats
+    /// are this report's own; rate-limited reports could be any, so they go with
+    /// whichever report is posted next.
+    fn admit(&mut self, report: &Report) -> Option<HeldBack> {
+        let now = Instant::now();
+        self.seen
+            .retain(|_, seen| now.duration_since(seen.reported) < SUMMARY_INTERVAL);
+        self.sent
+            .retain(|sent| now.duration_since(*sent) < Duration::from_secs(60));
+
+        let key = (report.message.clone(), report.link.clone());
+        let repeats = match self.seen.get_mut(&key) {
+            Some(seen) if now.duration_since(seen.reported) < DEDUP_WINDOW => {
+                seen.repeats += 1;
+                return None;
+            }
+            Some(seen) => seen.repeats,
+            None => 0,
+        };
+        if self.sent.len() >= MAX_REPORTS_PER_MINUTE {
+            self.rate_limited += 1;
+            return None;
+        }
+
+        self.sent.push(now);
+        self.seen.insert(
+            key,
+            Seen {
+                reported: now,
+                repeats: 0,
+            },
+        );
+        Some(HeldBack {
+            repeats,
+            rate_limited: std::mem::take(&mut self.rate_limited),
+        })
+    }
+}
+
+/// Failure counts since the last summary.
+#[derive(Default)]
+struct Summary {
+    total: u64,
+    by_error: BTreeMap<String, u64>,
+    by_link: BTreeMap<String, u64>,
+}
+
+impl Summary {
+    fn count(&mut self, report: &Report) {
+        self.total += 1;
+        *self.by_error.entry(report.message.clone()).or_default() += 1;
+        if let Some(link) = &report.link {
+            *self.by_link.entry(link.clone()).or_default() += 1;
+        }
+    }
+
+    fn render(&self) -> String {
+        if self.total == 0 {
+            return "Daily summary: no failures in the last 24 hours".to_string();
+        }
+
+        let mut text = format!(
+            "Daily summary: {} failures in the last 24 hours",
+            self.total
+        );
+        text.push_str("\n*By error*");
+        push_top(&mut text, &self.by_error, |message| message.clone());
+        if !self.by_link.is_empty() {
+            text.push_str("\n*By link*");
+            push_top(&mut text, &self.by_link, |link| format!("`{link}`"));
+        }
+        text
+    }
+}
+
+/// Lists the most frequent entries, then how many others there were.
+fn push_top(text: &mut String, counts: &BTreeMap<String, u64>, label: impl Fn(&String) -> String) {
+    let mut counts: Vec<_> = counts.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1));
+
+    for (key, count) in counts.iter().take(SUMMARY_TOP) {
+        text.push_str(&format!("\n• {}: {count}", label(key)));
+    }
+    if counts.len() > SUMMARY_TOP {
+        let rest = &counts[SUMMARY_TOP..];
+        let total: u64 = rest.iter().map(|(_, count)| **count).sum();
+        text.push_str(&format!(
+            "\n• and {} more, with {total} failures between them",
+            rest.len()
+        ));
+    }
+}
+
+struct Reporter {
+    config: Arc<Config>,
+    discord_http: serenity::Http,
+    slack_client: Arc<SlackApiClient>,
+}
+
+impl Reporter {
+    /// Posts in as many messages as each platform needs. Failures here are only warnings,
+    /// so they can't turn into reports of their own.
+    async fn post(&self, text: &str) {
+        if let Some(channel_id) = self.config.admin.discord_channel_id {
+            for part in split_message(text, DISCORD_MESSAGE_LIMIT) {
+                metrics::api_call("discord", "create_message");
+                if let Err(e) = serenity::ChannelId::new(channel_id)
+                    .say(&self.discord_http, part)
+                    .await
+                {
+                    warn!(error = %e, "Failed to post error report to Discord");
+                    break;
+                }
+            }
+        }
+
+        if let Some(channel_id) = &self.config.admin.slack_channel_id {
+            let slack_token = self.config.slack_token();
+            let session = self.slack_client.open_session(&slack_token);
+            for part in split_message(text, SLACK_MESSAGE_LIMIT) {
+                let request = SlackApiChatPostMessageRequest::new(
+                    channel_id.clone().into(),
+                    SlackMessageContent::new().with_text(part),
+                );
+                if let Err(e) = session.chat_post_message(&request).await {
+                    warn!(error = %e, "Failed to post error report to Slack");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(message: &str, link: Option<&str>) -> Report {
+        Report {
+            at: 0,
+            message: message.to_string(),
+            error: None,
+            link: link.map(str::to_string),
+            event_type: None,
+            trace: None,
+        }
+    }
+
+    #[test]
+    fn summary_lists_the_top_errors_and_counts_the_rest() {
+        let mut summary = Summary::default();
+        for n in 0..25 {
+            for _ in 0..=n {
+                summary.count(&report(&format!("Error {n}"), Some("1:C1")));
+            }
+        }
+
+        let text = summary.render();
+        assert!(text.starts_with("Daily summary: 325 failures"));
+        assert!(text.contains("\n• Error 24: 25"));
+        assert!(text.contains("\n• Error 15: 16"));
+        assert!(!text.contains("\n• Error 14: 15"));
+        assert!(text.contains("\n• and 15 more, with 120 failures between them"));
+        assert!(text.contains("\n• `1:C1`: 325"));
+    }
+
+    #[test]
+    fn long_summaries_split_to_fit_discord() {
+        let mut summary = Summary::default();
+        for n in 0..SUMMARY_TOP {
+            summary.count(&report(&format!("{n} {}", "x".repeat(500)), None));
+        }
+
+        let parts = split_message(&summary.render(), DISCORD_MESSAGE_LIMIT);
+        assert!(parts.len() > 1);
+        assert!(
+            parts
+                .iter()
+                .all(|part| part.chars().count() <= DISCORD_MESSAGE_LIMIT)
+        );
+    }
+
+    #[test]
+    fn repeats_are_held_back_until_the_dedup_window_passes() {
+        let mut limiter = Limiter::default();
+        let failure = report("Failed to deliver", Some("1:C1"));
+
+        assert_eq!(limiter.admit(&failure), Some(HeldBack::default()));
+        assert_eq!(limiter.admit(&failure), None);
+        assert_eq!(limiter.admit(&failure), None);
+
+        // Pretend the window has passed
+        let seen = limiter.seen.values_mut().next().unwrap();
+        seen.reported -= DEDUP_WINDOW;
+        assert_eq!(
+            limiter.admit(&failure),
+            Some(HeldBack {
+                repeats: 2,
+                rate_limited: 0
+            })
+        );
+    }
+
+    #[test]
+    fn rate_limited_reports_are_counted_apart_from_repeats() {
+        let mut limiter = Limiter::default();
+        for n in 0..MAX_REPORTS_PER_MINUTE {
+            assert!(
+                limiter
+                    .admit(&report(&format!("Error {n}"), None))
+                    .is_some()
+            );
+        }
+        assert_eq!(limiter.admit(&report("Error A", None)), None);
+        assert_eq!(limiter.admit(&report("Error B", None)), None);
+
+        // A repeat of an earlier report doesn't count as rate limited
+        assert_eq!(limiter.admit(&report("Error 0", None)), None);
+
+        // Free up the minute
+        limiter.sent.clear();
+        assert_eq!(
+            limiter.admit(&report("Error C", None)),
+            Some(HeldBack {
+                repeats: 0,
+                rate_limited: 2
+            })
+        );
+    }
+}
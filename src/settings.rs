@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use poise::ChoiceParameter;
+use serde_json::{Map, Value};
+
+use crate::bridge::EventType;
+
+/// Per-link toggles controlling which kinds of events are mirrored across a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkSettings {
+    pub mirror_edits: bool,
+    pub mirror_deletes: bool,
+    pub reactions: bool,
+    pub threads: bool,
+    pub attachments: bool,
+    pub pins: bool,
+    pub membership_notices: bool,
+    pub long_messages: LongMessages,
+    pub failure_feedback: FailureFeedback,
+}
+
+impl Default for LinkSettings {
+    fn default() -> Self {
+        Self {
+            mirror_edits: true,
+            mirror_deletes: true,
+            reactions: true,
+            threads: true,
+            attachments: true,
+            pins: true,
+            membership_notices: false,
+            long_messages: LongMessages::default(),
+            failure_feedback: FailureFeedback::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum Setting {
+    #[name = "Mirror edits"]
+    MirrorEdits,
+    #[name = "Mirror deletes"]
+    MirrorDeletes,
+    #[name = "Reactions"]
+    Reactions,
+    #[name = "Threads"]
+    Threads,
+    #[name = "Attachments"]
+    Attachments,
+    #[name = "Pins"]
+    Pins,
+    #[name = "Join/leave notices"]
+    MembershipNotices,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 7] = [
+        Setting::MirrorEdits,
+        Setting::MirrorDeletes,
+        Setting::Reactions,
+        Setting::Threads,
+        Setting::Attachments,
+        Setting::Pins,
+        Setting::MembershipNotices,
+    ];
+
+    /// Field name used for this setting in the link's Redis hash.
+    pub fn key(self) -> &'static str {
+        match self {
+            Setting::MirrorEdits => "mirror_edits",
+            Setting::MirrorDeletes => "mirror_deletes",
+            Setting::Reactions => "reactions",
+            Setting::Threads => "threads",
+            Setting::Attachments => "attachments",
+            Setting::Pins => "pins",
+            Setting::MembershipNotices => "membership_notices",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|setting| setting.key() == key)
+    }
+}
+
+/// What to do with messages longer than Discord allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ChoiceParameter)]
+pub enum LongMessages {
+    /// Send several messages, cut at paragraph or code block boundaries.
+    #[default]
+    #[name = "Split into parts"]
+    Split,
+    /// Send the start of the message with a link to the original.
+    #[name = "Truncate with link"]
+    Truncate,
+    /// Send a preview with the full message attached as a text file.
+    #[name = "Attach as file"]
+    File,
+}
+
+impl LongMessages {
+    pub const ALL: [LongMessages; 3] = [
+        LongMessages::Split,
+        LongMessages::Truncate,
+        LongMessages::File,
+    ];
+
+    /// Field name used for the policy in the link's Redis hash.
+    pub const FIELD: &str = "long_messages";
+
+    pub fn key(self) -> &'static str {
+        match self {
+            LongMessages::Split => "split",
+            LongMessages::Truncate => "truncate",
+            LongMessages::File => "file",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.key() == key)
+    }
+}
+
+/// How the author of a message that couldn't be bridged finds out. Unless this is off,
+/// Slack authors get an ephemeral message instead, since Slack can show one to just them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ChoiceParameter)]
+pub enum FailureFeedback {
+    /// Nobody is told, on either platform.
+    #[default]
+    #[name = "Off"]
+    Off,
+    /// Discord messages get a ⚠️ reaction.
+    #[name = "Reaction"]
+    Reaction,
+    /// Discord messages get a reply with the reason, removed after a minute.
+    #[name = "Reply"]
+    Reply,
+}
+
+impl FailureFeedback {
+    pub const ALL: [FailureFeedback; 3] = [
+        FailureFeedback::Off,
+        FailureFeedback::Reaction,
+        FailureFeedback::Reply,
+    ];
+
+    /// Field name used for the mode in the link's Redis hash.
+    pub const FIELD: &str = "failure_feedback";
+
+    pub fn key(self) -> &'static str {
+        match self {
+            FailureFeedback::Off => "off",
+            FailureFeedback::Reaction => "reaction",
+            FailureFeedback::Reply => "reply",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+}
+
+impl LinkSettings {
+    pub fn get(&self, setting: Setting) -> bool {
+        match setting {
+            Setting::MirrorEdits => self.mirror_edits,
+            Setting::MirrorDeletes => self.mirror_deletes,
+            Setting::Reactions => self.reactions,
+            Setting::Threads => self.threads,
+            Setting::Attachments => self.attachments,
+            Setting::Pins => self.pins,
+            Setting::MembershipNotices => self.membership_notices,
+        }
+    }
+
+    pub fn set(&mut self, setting: Setting, enabled: bool) {
+        match setting {
+            Setting::MirrorEdits => self.mirror_edits = enabled,
+            Setting::MirrorDeletes => self.mirror_deletes = enabled,
+            Setting::Reactions => self.reactions = enabled,
+            Setting::Threads => self.threads = enabled,
+            Setting::Attachments => self.attachments = enabled,
+            Setting::Pins => self.pins = enabled,
+            Setting::MembershipNotices => self.membership_notices = enabled,
+        }
+    }
+
+    /// Builds settings from a Redis hash, falling back to defaults for missing fields.
+    pub fn from_fields(fields: &HashMap<String, String>) -> Self {
+        let mut settings = Self::default();
+
+        for (key, value) in fields {
+            if let Some(setting) = Setting::from_key(key) {
+                settings.set(setting, value == "1");
+            } else if key == LongMessages::FIELD
+                && let Some(policy) = LongMessages::from_key(value)
+            {
+                settings.long_messages = policy;
+            } else if key == FailureFeedback::FIELD
+                && let Some(mode) = FailureFeedback::from_key(value)
+            {
+                settings.failure_feedback = mode;
+            }
+        }
+
+        settings
+    }
+
+    pub fn to_fields(self) -> Vec<(&'static str, &'static str)> {
+        Setting::ALL
+            .into_iter()
+            .map(|setting| (setting.key(), if self.get(setting) { "1" } else { "0" }))
+            .chain([
+                (LongMessages::FIELD, self.long_messages.key()),
+                (FailureFeedback::FIELD, self.failure_feedback.key()),
+            ])
+            .collect()
+    }
+
+    /// As the admin API and exports show it, with each setting under its field name.
+    pub fn to_json(self) -> Map<String, Value> {
+        let mut fields: Map<String, Value> = Setting::ALL
+            .into_iter()
+            .map(|setting| (setting.key().to_string(), self.get(setting).into()))
+            .collect();
+        fields.insert(
+            LongMessages::FIELD.to_string(),
+            self.long_messages.key().into(),
+        );
+        fields.insert(
+            FailureFeedback::FIELD.to_string(),
+            self.failure_feedback.key().into(),
+        );
+        fields
+    }
+
+    /// Applies the fields present in `changes`, rejecting any it doesn't know.
+    pub fn apply_json(&mut self, changes: &Map<String, Value>) -> Result<(), String> {
+        for (key, value) in changes {
+            if let Some(setting) = Setting::from_key(key) {
+                let enabled = value
+                    .as_bool()
+                    .ok_or(format!("{key}: expected a boolean"))?;
+                self.set(setting, enabled);
+            } else if key == LongMessages::FIELD {
+                let policy = value.as_str().and_then(LongMessages::from_key);
+                self.long_messages =
+                    policy.ok_or(format!("{key}: expected split, truncate or file"))?;
+            } else if key == FailureFeedback::FIELD {
+                let mode = value.as_str().and_then(FailureFeedback::from_key);
+                self.failure_feedback =
+                    mode.ok_or(format!("{key}: expected off, reaction or reply"))?;
+            } else {
+                return Err(format!("unknown setting {key}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether an event of this type should be delivered across the link.
+    pub fn allows(&self, event_type: &EventType) -> bool {
+        match event_type {
+            EventType::MessageSent { thread_id, .. } => thread_id.is_none() || self.threads,
+            EventType::MessageEdited { .. } => self.mirror_edits,
+            EventType::MessageDeleted { .. } => self.mirror_deletes,
+            EventType::MessagePinned { .. } | EventType::MessageUnpinned { .. } => self.pins,
+            EventType::ReactionAdded { .. } | EventType::ReactionRemoved { .. } => self.reactions,
+            EventType::MembershipNotice { .. } => self.membership_notices,
+            EventType::ChannelNotice { .. } => true,
+        }
+    }
+
+    /// Human-readable summary, one setting per line.
+    pub fn describe(&self) -> String {
+        Setting::ALL
+            .into_iter()
+            .map(|setting| {
+                let marker = if self.get(setting) { "✅" } else { "❌" };
+                format!("{marker} {}", setting.name())
+            })
+            .chain([
+                format!("📏 Long messages: {}", self.long_messages.name()),
+                format!("⚠️ Failure feedback: {}", self.failure_feedback.name()),
+            ])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Whether events currently flow across a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkState {
+    #[default]
+    Active,
+    /// Events are dropped until the link is resumed.
+    Paused,
+    /// Events are held in a queue and delivered once the link is resumed.
+    Queueing,
+    /// One side of the link went away and the channels were unlinked.
+    Dead,
+}
+
+impl LinkState {
+    pub fn key(self) -> &'static str {
+        match self {
+            LinkState::Active => "active",
+            LinkState::Paused => "paused",
+            LinkState::Queueing => "queueing",
+            LinkState::Dead => "dead",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "active" => Some(LinkState::Active),
+            "paused" => Some(LinkState::Paused),
+            "queueing" => Some(LinkState::Queueing),
+            "dead" => Some(LinkState::Dead),
+            _ => None,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            LinkState::Active => "active",
+            LinkState::Paused => "paused",
+            LinkState::Queueing => "paused (queueing)",
+            LinkState::Dead => "dead",
+        }
+    }
+}
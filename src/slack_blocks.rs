@@ -0,0 +1,472 @@
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+use serde_json::Value;
+use slack_morphism::prelude::*;
+
+use crate::bridge::{Embed, EmbedField};
+use crate::split::clip;
+
+/// Discord shows at most this many embeds on a message.
+const MAX_EMBEDS: usize = 10;
+const MAX_FIELDS: usize = 25;
+const MAX_TITLE: usize = 256;
+const MAX_DESCRIPTION: usize = 4096;
+const MAX_FIELD_NAME: usize = 256;
+const MAX_FIELD_VALUE: usize = 1024;
+/// Discord's limit on the text of all the embeds on a message together.
+const MAX_TOTAL: usize = 6000;
+
+/// Slack links in mrkdwn, e.g. `<https://example.com|label>` or `<https://example.com>`.
+static MRKDWN_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<((?:https?|mailto):[^|>]+)(?:\|([^>]+))?>").unwrap());
+
+/// Single-asterisk bold, which Discord writes with two.
+static MRKDWN_BOLD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w*])\*([^*\n]+)\*($|[^\w*])").unwrap());
+
+/// Single-tilde strikethrough, which Discord writes with two.
+static MRKDWN_STRIKE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w~])~([^~\n]+)~($|[^\w~])").unwrap());
+
+/// Renders a Slack message for Discord. Plain messages keep their text. Messages built
+/// from layout blocks or legacy attachments, as apps and workflows send them, become
+/// embeds and the text, which is only a notification fallback for those, is dropped.
+pub fn render_message(content: &SlackMessageContent) -> (String, Vec<Embed>) {
+    let text = content.text.clone().unwrap_or_default();
+    let blocks = content.blocks.as_deref().unwrap_or_default();
+    let attachments = content.attachments.as_deref().unwrap_or_default();
+
+    let is_layout = blocks
+        .iter()
+        .any(|block| !matches!(block, SlackBlock::RichText(_)));
+
+    let (text, mut embeds) = if is_layout {
+        (String::new(), render_blocks(blocks))
+    } else if text.trim().is_empty() {
+        (render_rich_text_blocks(blocks), Vec::new())
+    } else {
+        (text, Vec::new())
+    };
+
+    embeds.extend(attachments.iter().flat_map(render_attachment));
+    embeds.truncate(MAX_EMBEDS);
+    fit_total(&mut embeds);
+
+    (text, embeds)
+}
+
+/// Lays blocks out as embeds. Headers and dividers start a new embed, as does a second
+/// image since an embed only shows one.
+fn render_blocks(blocks: &[SlackBlock]) -> Vec<Embed> {
+    let mut embeds = Vec::new();
+    let mut current = Embed::default();
+
+    for block in blocks {
+        match block {
+            SlackBlock::Header(header) => {
+                finish_embed(&mut embeds, std::mem::take(&mut current));
+                current.title = Some(clip(&plain_text(header.text.clone()), MAX_TITLE));
+            }
+            SlackBlock::Divider(_) => {
+                finish_embed(&mut embeds, std::mem::take(&mut current));
+            }
+            SlackBlock::Section(section) => {
+                if let Some(text) = &section.text {
+                    push_line(&mut current, &block_text(text));
+                }
+                for field in section.fields.iter().flatten() {
+                    let (name, value) = split_field(&block_text(field));
+                    push_field(&mut current, name, value, true);
+                }
+                if let Some(SlackSectionBlockElement::Image(image)) = &section.accessory {
+                    current.thumbnail_url =
+                        image.image_url_or_file.image_url().map(ToString::to_string);
+                }
+            }
+            SlackBlock::Context(context) => {
+                let line = context
+                    .elements
+                    .iter()
+                    .filter_map(|element| match element {
+                        SlackContextBlockElement::Plain(text) => Some(text.text.clone()),
+                        SlackContextBlockElement::MarkDown(text) => Some(mrkdwn(&text.text)),
+                        SlackContextBlockElement::Image(_) => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" · ");
+                if !line.is_empty() {
+                    push_line(&mut current, &format!("-# {line}"));
+                }
+            }
+            SlackBlock::Image(image) => {
+                if current.image_url.is_some() {
+                    finish_embed(&mut embeds, std::mem::take(&mut current));
+                }
+                if let Some(title) = image.title.clone() {
+                    push_line(&mut current, &plain_text(title));
+                }
+                current.image_url = image.image_url_or_file.image_url().map(ToString::to_string);
+            }
+            SlackBlock::RichText(value) => {
+                push_line(&mut current, &render_rich_text(value));
+            }
+            SlackBlock::Markdown(markdown) => {
+                push_line(&mut current, &markdown.text);
+            }
+            // Buttons, inputs and the like can't be used from Discord
+            _ => {}
+        }
+    }
+    finish_embed(&mut embeds, current);
+
+    embeds
+}
+
+/// Renders a legacy attachment, including any blocks it carries, as embeds.
+fn render_attachment(attachment: &SlackMessageAttachment) -> Vec<Embed> {
+    let mut embed = Embed {
+        title: attachment
+            .title
+            .as_deref()
+            .map(|title| clip(&mrkdwn(title), MAX_TITLE)),
+        color: attachment.color.as_deref().and_then(parse_color),
+        ..Embed::default()
+    };
+
+    match attachment.text.as_deref().filter(|text| !text.is_empty()) {
+        Some(text) => push_line(&mut embed, &mrkdwn(text)),
+        None if attachment.fields.is_none() && attachment.blocks.is_none() => {
+            if let Some(fallback) = &attachment.fallback {
+                push_line(&mut embed, &mrkdwn(fallback));
+            }
+        }
+        None => {}
+    }
+
+    for field in attachment.fields.iter().flatten() {
+        push_field(
+            &mut embed,
+            field.title.as_deref().map(mrkdwn).unwrap_or_default(),
+            field.value.as_deref().map(mrkdwn).unwrap_or_default(),
+            field.short.unwrap_or(false),
+        );
+    }
+
+    let mut embeds = Vec::new();
+    let block_embeds = render_blocks(attachment.blocks.as_deref().unwrap_or_default());
+    finish_embed(&mut embeds, embed);
+    embeds.extend(block_embeds.into_iter().map(|block_embed| {
+        Embed {
+            color: block_embed
+                .color
+                .or(attachment.color.as_deref().and_then(parse_color)),
+            ..block_embed
+        }
+    }));
+
+    embeds
+}
+
+/// Keeps the embeds within Discord's limit on their text together. Text past the limit is
+/// clipped, and embeds after it are dropped.
+fn fit_total(embeds: &mut Vec<Embed>) {
+    let mut remaining = MAX_TOTAL;
+
+    embeds.retain_mut(|embed| {
+        if remaining == 0 {
+            return false;
+        }
+
+        spend(&mut embed.title, &mut remaining);
+        spend(&mut embed.description, &mut remaining);
+        embed.fields.retain_mut(|field| {
+            // A field needs both a name and a value
+            let name_len = field.name.chars().count();
+            if name_len >= remaining {
+                return false;
+            }
+            field.value = clip(&field.value, remaining - name_len);
+            remaining -= name_len + field.value.chars().count();
+            true
+        });
+        spend(&mut embed.footer, &mut remaining);
+        spend(&mut embed.author, &mut remaining);
+        true
+    });
+}
+
+/// Clips `text` to what's left of the total, or drops it once nothing is.
+fn spend(text: &mut Option<String>, remaining: &mut usize) {
+    let Some(value) = text else {
+        return;
+    };
+    if *remaining == 0 {
+        *text = None;
+        return;
+    }
+
+    *value = clip(value, *remaining);
+    *remaining -= value.chars().count();
+}
+
+fn finish_embed(embeds: &mut Vec<Embed>, embed: Embed) {
+    let is_empty = embed.title.is_none()
+        && embed.description.is_none()
+        && embed.fields.is_empty()
+        && embed.image_url.is_none()
+        && embed.thumbnail_url.is_none();
+
+    if !is_empty {
+        embeds.push(embed);
+    }
+}
+
+fn push_line(embed: &mut Embed, line: &str) {
+    if line.trim().is_empty() {
+        return;
+    }
+
+    let description = match embed.description.take() {
+        Some(description) => format!("{description}\n{line}"),
+        None => line.to_string(),
+    };
+    embed.description = Some(clip(&description, MAX_DESCRIPTION));
+}
+
+fn push_field(embed: &mut Embed, name: String, value: String, inline: bool) {
+    if embed.fields.len() >= MAX_FIELDS || (name.is_empty() && value.is_empty()) {
+        return;
+    }
+
+    // Discord requires both a name and a value, a zero width space stands in for either
+    let blank = || "\u{200B}".to_string();
+    embed.fields.push(EmbedField {
+        name: if name.is_empty() {
+            blank()
+        } else {
+            clip(&name, MAX_FIELD_NAME)
+        },
+        value: if value.is_empty() {
+            blank()
+        } else {
+            clip(&value, MAX_FIELD_VALUE)
+        },
+        inline,
+    });
+}
+
+/// Section fields are usually written as a bold label over the value, which maps
+/// nicely onto an embed field's name and value.
+fn split_field(text: &str) -> (String, String) {
+    match text.split_once('\n') {
+        Some((label, value)) => (label.trim_matches('*').to_string(), value.to_string()),
+        None => (String::new(), text.to_string()),
+    }
+}
+
+fn block_text(text: &SlackBlockText) -> String {
+    match text {
+        SlackBlockText::Plain(text) => text.text.clone(),
+        SlackBlockText::MarkDown(text) => mrkdwn(&text.text),
+    }
+}
+
+fn plain_text(text: SlackBlockPlainTextOnly) -> String {
+    block_text(&text.into())
+}
+
+/// Converts Slack mrkdwn links and emphasis to Discord markdown. Mentions, channels and
+/// shortcodes are left for the rest of the pipeline.
+fn mrkdwn(text: &str) -> String {
+    let text = MRKDWN_LINK.replace_all(text, |captures: &Captures| match captures.get(2) {
+        Some(label) => format!("[{}](<{}>)", label.as_str(), &captures[1]),
+        None => format!("<{}>", &captures[1]),
+    });
+
+    let text = MRKDWN_BOLD.replace_all(&text, "$1**$2**$3");
+    MRKDWN_STRIKE.replace_all(&text, "$1~~$2~~$3").into_owned()
+}
+
+fn render_rich_text_blocks(blocks: &[SlackBlock]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            SlackBlock::RichText(value) => Some(render_rich_text(value)),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders a `rich_text` block, which slack-morphism leaves as raw JSON, as Discord markdown.
+fn render_rich_text(block: &Value) -> String {
+    elements(block)
+        .iter()
+        .map(|element| match element["type"].as_str() {
+            Some("rich_text_section") => render_inline(elements(element)),
+            Some("rich_text_preformatted") => {
+                format!("```\n{}\n```", render_plain(elements(element)))
+            }
+            Some("rich_text_quote") => render_inline(elements(element))
+                .lines()
+                .map(|line| format!("> {line}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Some("rich_text_list") => render_list(element),
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_list(list: &Value) -> String {
+    let ordered = list["style"].as_str() == Some("ordered");
+    let indent = "  ".repeat(list["indent"].as_u64().unwrap_or(0) as usize);
+
+    elements(list)
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let marker = if ordered {
+                format!("{}.", index + 1)
+            } else {
+                "-".to_string()
+            };
+            format!("{indent}{marker} {}", render_inline(elements(item)))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_inline(elements: &[Value]) -> String {
+    elements.iter().map(render_element).collect()
+}
+
+/// Code blocks take their text as is, without styling.
+fn render_plain(elements: &[Value]) -> String {
+    elements
+        .iter()
+        .map(|element| match element["type"].as_str() {
+            Some("link") => element["url"].as_str().unwrap_or_default().to_string(),
+            _ => element["text"].as_str().unwrap_or_default().to_string(),
+        })
+        .collect()
+}
+
+fn render_element(element: &Value) -> String {
+    let text = |key: &str| element[key].as_str().unwrap_or_default().to_string();
+
+    let rendered = match element["type"].as_str() {
+        Some("text") => text("text"),
+        Some("link") => match element["text"].as_str() {
+            Some(label) if !label.is_empty() => format!("[{label}](<{}>)", text("url")),
+            _ => format!("<{}>", text("url")),
+        },
+        Some("user") => format!("<@{}>", text("user_id")),
+        Some("usergroup") => format!("<!subteam^{}>", text("usergroup_id")),
+        Some("channel") => format!("<#{}>", text("channel_id")),
+        Some("broadcast") => format!("@{}", text("range")),
+        Some("emoji") => format!(":{}:", text("name")),
+        Some("date") => text("fallback"),
+        Some("color") => text("value"),
+        _ => String::new(),
+    };
+
+    apply_style(rendered, &element["style"])
+}
+
+fn apply_style(text: String, style: &Value) -> String {
+    // Markers can't hug whitespace, so style only the trimmed text
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text;
+    }
+
+    let mut styled = trimmed.to_string();
+    for (key, marker) in [
+        ("code", "`"),
+        ("strike", "~~"),
+        ("italic", "_"),
+        ("bold", "**"),
+    ] {
+        if style[key].as_bool() == Some(true) {
+            styled = format!("{marker}{styled}{marker}");
+        }
+    }
+
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len();
+    format!("{}{styled}{}", &text[..start], &text[end..])
+}
+
+fn elements(value: &Value) -> &[Value] {
+    value["elements"].as_array().map_or(&[], Vec::as_slice)
+}
+
+/// Parses an attachment color, either hex or one of Slack's named colors.
+fn parse_color(color: &str) -> Option<u32> {
+    match color {
+        "good" => Some(0x2EB886),
+        "warning" => Some(0xDAA038),
+        "danger" => Some(0xA30200),
+        hex => u32::from_str_radix(hex.trim_start_matches('#'), 16).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total_len(embeds: &[Embed]) -> usize {
+        embeds
+            .iter()
+            .map(|embed| {
+                [
+                    &embed.title,
+                    &embed.description,
+                    &embed.footer,
+                    &embed.author,
+                ]
+                .into_iter()
+                .flatten()
+                .chain(
+                    embed
+                        .fields
+                        .iter()
+                        .flat_map(|field| [&field.name, &field.value]),
+                )
+                .map(|text| text.chars().count())
+                .sum::<usize>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn embeds_stay_within_the_total_limit() {
+        let attachments = (0..8)
+            .map(|index| SlackMessageAttachment {
+                title: Some(format!("Report {index}")),
+                text: Some("x".repeat(2000)),
+                fields: Some(vec![SlackMessageAttachmentFieldObject {
+                    title: Some("Status".to_string()),
+                    value: Some("y".repeat(500)),
+                    short: Some(true),
+                }]),
+                ..SlackMessageAttachment::new()
+            })
+            .collect();
+        let content = SlackMessageContent::new().with_attachments(attachments);
+
+        let (_, embeds) = render_message(&content);
+
+        assert_eq!(total_len(&embeds), MAX_TOTAL);
+        assert_eq!(embeds.len(), 3);
+        // Earlier embeds are left whole, only the one at the limit is clipped
+        assert_eq!(
+            embeds[0].description.as_deref(),
+            Some("x".repeat(2000).as_str())
+        );
+        assert!(embeds[2].description.as_deref().unwrap().ends_with('…'));
+    }
+}
@@ -0,0 +1,133 @@
+use poise::futures_util::FutureExt;
+use poise::futures_util::future::BoxFuture;
+use slack_morphism::errors::SlackClientError;
+use slack_morphism::multipart_form::FileMultipartData;
+use slack_morphism::prelude::*;
+use slack_morphism::{ClientResult, SlackClientApiCallContext, SlackClientHttpConnector};
+use url::Url;
+
+use crate::metrics;
+
+/// The Slack client used everywhere but the events listener, which needs the bare one.
+pub type SlackApiClient = SlackClient<MeteredConnector>;
+
+pub fn new_client() -> std::io::Result<SlackApiClient> {
+    Ok(SlackClient::new(MeteredConnector {
+        inner: SlackClientHyperConnector::new()?,
+    }))
+}
+
+/// Slack's hyper connector, counting every API call and rate limit as it goes.
+#[derive(Clone, Debug)]
+pub struct MeteredConnector {
+    inner: SlackClientHyperHttpsConnector,
+}
+
+/// Counts a call to the method at the end of `uri`, like `chat.postMessage`.
+fn count<RS>(uri: &Url, result: ClientResult<RS>) -> ClientResult<RS> {
+    let method = uri.path().rsplit('/').next().unwrap_or_default();
+    metrics::api_call("slack", method);
+
+    match &result {
+        Err(SlackClientError::RateLimitError(_)) => metrics::rate_limited("slack"),
+        Err(SlackClientError::HttpError(error)) if error.status_code.as_u16() == 429 => {
+            metrics::rate_limited("slack")
+        }
+        _ => {}
+    }
+    result
+}
+
+impl SlackClientHttpConnector for MeteredConnector {
+    fn http_get_uri<'a, RS>(
+        &'a self,
+        full_uri: Url,
+        context: SlackClientApiCallContext<'a>,
+    ) -> BoxFuture<'a, ClientResult<RS>>
+    where
+        RS: for<'de> serde::de::Deserialize<'de> + Send + 'a,
+    {
+        async move {
+            let result = self.inner.http_get_uri(full_uri.clone(), context).await;
+            count(&full_uri, result)
+        }
+        .boxed()
+    }
+
+    fn http_get_with_client_secret<'a, RS>(
+        &'a self,
+        full_uri: Url,
+        client_id: &'a SlackClientId,
+        client_secret: &'a SlackClientSecret,
+    ) -> BoxFuture<'a, ClientResult<RS>>
+    where
+        RS: for<'de> serde::de::Deserialize<'de> + Send + 'a,
+    {
+        async move {
+            let result = self
+                .inner
+                .http_get_with_client_secret(full_uri.clone(), client_id, client_secret)
+                .await;
+            count(&full_uri, result)
+        }
+        .boxed()
+    }
+
+    fn http_post_uri<'a, RQ, RS>(
+        &'a self,
+        full_uri: Url,
+        request_body: &'a RQ,
+        context: SlackClientApiCallContext<'a>,
+    ) -> BoxFuture<'a, ClientResult<RS>>
+    where
+        RQ: serde::ser::Serialize + Send + Sync,
+        RS: for<'de> serde::de::Deserialize<'de> + Send + 'a,
+    {
+        async move {
+            let result = self
+                .inner
+                .http_post_uri(full_uri.clone(), request_body, context)
+                .await;
+            count(&full_uri, result)
+        }
+        .boxed()
+    }
+
+    fn http_post_uri_multipart_form<'a, 'p, RS, PT, TS>(
+        &'a self,
+        full_uri: Url,
+        file: Option<FileMultipartData<'p>>,
+        params: &'p PT,
+        context: SlackClientApiCallContext<'a>,
+    ) -> BoxFuture<'a, ClientResult<RS>>
+    where
+        RS: for<'de> serde::de::Deserialize<'de> + Send + 'a,
+        PT: std::iter::IntoIterator<Item = (&'p str, Option<TS>)> + Clone,
+        TS: AsRef<str> + 'p + Send,
+    {
+        let call = self
+            .inner
+            .http_post_uri_multipart_form(full_uri.clone(), file, params, context);
+        async move { count(&full_uri, call.await) }.boxed()
+    }
+
+    fn http_post_uri_binary<'a, 'p, RS>(
+        &'a self,
+        full_uri: Url,
+        content_type: String,
+        data: &'a [u8],
+        context: SlackClientApiCallContext<'a>,
+    ) -> BoxFuture<'a, ClientResult<RS>>
+    where
+        RS: for<'de> serde::de::Deserialize<'de> + Send + 'a,
+    {
+        async move {
+            let result = self
+                .inner
+                .http_post_uri_binary(full_uri.clone(), content_type, data, context)
+                .await;
+            count(&full_uri, result)
+        }
+        .boxed()
+    }
+}
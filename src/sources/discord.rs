@@ -2,22 +2,64 @@ use std::sync::Arc;
 
 use poise::serenity_prelude::EditWebhookMessage;
 use poise::serenity_prelude::{
-    self as serenity, CreateWebhook, EventHandler, ExecuteWebhook, prelude::TypeMapKey,
+    self as serenity, EventHandler, ExecuteWebhook, prelude::TypeMapKey,
 };
-use slack_morphism::prelude::SlackHyperClient;
-use tokio::sync::mpsc;
+use slack_morphism::prelude::{
+    SlackApiChatGetPermalinkRequest, SlackApiChatPostEphemeralRequest,
+    SlackApiChatPostMessageRequest, SlackMessageContent,
+};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span, debug, error, info, info_span, warn};
 
-use crate::bridge::{BridgeChannels, BridgeEvent, EventType};
-use crate::commands::{general::help, link::link_channel, unlink::unlink_channel};
-use crate::redis::RedisClient;
+use crate::bridge::{
+    Attachment, BridgeChannels, BridgeEvent, BridgeReceiver, DeliveryFailure, Direction, Embed,
+    EventType, ReplyTo, check_link, content_hash, dead_letter, record_failure, retry_transient,
+};
+use crate::cache::discord_webhooks::{UNKNOWN_WEBHOOK, WebhookCache, is_discord_error};
+use crate::cache::slack_channels::SlackChannelCache;
+use crate::cache::slack_emoji::SlackEmojiCache;
+use crate::commands::{
+    general::help,
+    link::link_channel,
+    list::list_links,
+    pause::{pause_link, resume_link},
+    settings::settings,
+    unlink::unlink_channel,
+};
+use crate::config::Config;
+use crate::discord_embeds::bridge_embeds;
+use crate::emoji::{CustomEmoji, slack_text_to_discord, unicode_from_shortcode};
+use crate::logging::redact;
+use crate::message_links::{replace_ranges, slack_permalinks};
+use crate::metrics;
+use crate::redis::{RedisClient, parse_discord_message};
+use crate::settings::{FailureFeedback, LongMessages};
+use crate::slack_client::SlackApiClient;
+use crate::split::{DISCORD_MESSAGE_LIMIT, split_message, truncate_message};
 
 #[derive(Clone)]
 pub struct Data {
+    pub config: Arc<Config>,
     pub bridge: BridgeChannels,
     pub redis_client: RedisClient,
-    pub slack_client: Arc<SlackHyperClient>,
+    pub slack_client: Arc<SlackApiClient>,
+    pub slack_channels: Arc<SlackChannelCache>,
+    pub webhooks: Arc<WebhookCache>,
+    pub slack_emoji: Arc<SlackEmojiCache>,
+}
+
+/// The running Discord client, shared with the Slack side and the delivery loop once it's
+/// ready.
+#[derive(Clone)]
+pub struct DiscordHandle {
+    pub ctx: serenity::Context,
+    pub shard_manager: Arc<serenity::ShardManager>,
 }
 
+/// Follows the latest client, which changes whenever the supervisor restarts it.
+pub type DiscordClient = watch::Receiver<Option<DiscordHandle>>;
+
 impl TypeMapKey for Data {
     type Value = Data;
 }
@@ -27,10 +69,155 @@ pub type Context<'a> = poise::Context<'a, Data, Error>;
 
 struct Handler;
 
+async fn get_data(ctx: &serenity::Context) -> Option<Data> {
+    ctx.data.read().await.get::<Data>().cloned()
+}
+
+fn send_to_slack(bridge: &BridgeChannels, event: BridgeEvent) {
+    let _span = info_span!(
+        "discord_event",
+        guild = %event.team_id,
+        channel = %event.channel_id,
+        message = event.event_type.message_id(),
+        event_type = event.event_type.kind(),
+    )
+    .entered();
+    debug!("Forwarding Discord event");
+
+    metrics::event_received(Direction::ToSlack, &event.event_type);
+    if let Err(e) = bridge.to_slack.send(event) {
+        error!(error = %e, "Failed to send bridge event");
+    }
+}
+
+/// Messages from Carmine itself and from webhooks, which include our bridged copies, aren't
+/// bridged. Other bots' are, since their posts are mostly embeds worth mirroring.
+fn is_own_or_webhook(
+    ctx: &serenity::Context,
+    author_id: Option<serenity::UserId>,
+    webhook_id: Option<serenity::WebhookId>,
+) -> bool {
+    author_id == Some(ctx.cache.current_user().id) || webhook_id.is_some()
+}
+
+/// The bridge event for a new Discord message.
+fn message_event(guild_id: serenity::GuildId, msg: &serenity::Message) -> BridgeEvent {
+    let author_name = msg
+        .member
+        .as_ref()
+        .and_then(|m| m.nick.clone())
+        .unwrap_or_else(|| msg.author.display_name().to_string());
+
+    // Replies keep a copy of the message they point at
+    let reply_to = msg.referenced_message.as_ref().map(|referenced| ReplyTo {
+        message_id: referenced.id.to_string(),
+        author_name: referenced.author.display_name().to_string(),
+        content: referenced.content.clone(),
+        url: format!(
+            "https://discord.com/channels/{guild_id}/{}/{}",
+            referenced.channel_id, referenced.id
+        ),
+    });
+
+    let message_url = format!(
+        "https://discord.com/channels/{guild_id}/{}/{}",
+        msg.channel_id, msg.id
+    );
+    let embeds = bridge_embeds(
+        &msg.embeds,
+        &msg.sticker_items,
+        msg.poll.as_deref(),
+        &message_url,
+    );
+
+    let attachments = msg
+        .attachments
+        .iter()
+        .map(|a| Attachment {
+            filename: a.filename.clone(),
+            url: a.url.clone(),
+        })
+        .collect();
+
+    BridgeEvent {
+        event_type: EventType::MessageSent {
+            message_id: msg.id.to_string(),
+            content: msg.content.clone(),
+            attachments,
+            thread_id: None,
+            reply_to,
+            embeds,
+        },
+        author_name,
+        author_avatar: msg.author.face(),
+        author_id: msg.author.id.to_string(),
+        channel_id: msg.channel_id.to_string(),
+        team_id: guild_id.to_string(),
+    }
+}
+
+/// Marks the link for a Discord channel that went away as dead and tells the Slack side.
+async fn retire_discord_channel(data: &Data, discord_channel_id: u64, reason: &str) {
+    let slack_channel_id = match data
+        .redis_client
+        .get_linked_slack_channel(discord_channel_id)
+        .await
+    {
+        Ok(Some(slack_channel_id)) => slack_channel_id,
+        Ok(None) => return,
+        Err(e) => {
+            error!(error = %e, "Error fetching Slack channel ID");
+            return;
+        }
+    };
+
+    if let Err(e) = data
+        .redis_client
+        .mark_link_dead(
+            discord_channel_id,
+            &slack_channel_id,
+            data.config.dead_link_retention,
+        )
+        .await
+    {
+        error!(error = %e, "Failed to mark link as dead");
+        return;
+    }
+
+    let slack_token = data.config.slack_token();
+    let session = data.slack_client.open_session(&slack_token);
+
+    let request = SlackApiChatPostMessageRequest::new(
+        slack_channel_id.into(),
+        SlackMessageContent::new().with_text(format!(
+            "The linked Discord channel {reason}, so this channel has been unlinked."
+        )),
+    );
+    if let Err(e) = session.chat_post_message(&request).await {
+        error!(error = %e, "Failed to notify Slack channel");
+    }
+}
+
 #[serenity::async_trait]
 impl EventHandler for Handler {
     async fn message(&self, ctx: serenity::Context, msg: serenity::Message) {
-        println!("Received message: {}", msg.content);
+        if is_own_or_webhook(&ctx, Some(msg.author.id), msg.webhook_id) {
+            return;
+        }
+
+        let (Some(guild_id), Some(data)) = (msg.guild_id, get_data(&ctx).await) else {
+            return;
+        };
+
+        debug!(
+            guild = %guild_id,
+            channel = %msg.channel_id,
+            message = %msg.id,
+            content = %redact(&msg.content),
+            "Received Discord message"
+        );
+
+        send_to_slack(&data.bridge, message_event(guild_id, &msg));
     }
 
     async fn message_delete(
@@ -40,20 +227,176 @@ impl EventHandler for Handler {
         message_id: serenity::MessageId,
         guild_id: Option<serenity::GuildId>,
     ) {
-        println!("Message deleted: {message_id} in channel {channel_id}");
+        let (Some(guild_id), Some(data)) = (guild_id, get_data(&ctx).await) else {
+            return;
+        };
+
+        send_to_slack(
+            &data.bridge,
+            BridgeEvent {
+                event_type: EventType::MessageDeleted {
+                    message_id: message_id.to_string(),
+                },
+                author_name: String::new(),
+                author_avatar: String::new(),
+                author_id: String::new(),
+                channel_id: channel_id.to_string(),
+                team_id: guild_id.to_string(),
+            },
+        );
+    }
+
+    async fn ratelimit(&self, _data: serenity::RatelimitInfo) {
+        metrics::rate_limited("discord");
+    }
+
+    async fn reaction_add(&self, ctx: serenity::Context, reaction: serenity::Reaction) {
+        forward_reaction(&ctx, reaction, true).await;
+    }
+
+    async fn reaction_remove(&self, ctx: serenity::Context, reaction: serenity::Reaction) {
+        forward_reaction(&ctx, reaction, false).await;
+    }
+
+    async fn channel_delete(
+        &self,
+        ctx: serenity::Context,
+        channel: serenity::GuildChannel,
+        _messages: Option<Vec<serenity::Message>>,
+    ) {
+        let Some(data) = get_data(&ctx).await else {
+            return;
+        };
+
+        retire_discord_channel(&data, channel.id.into(), "was deleted").await;
+    }
+
+    async fn guild_delete(
+        &self,
+        ctx: serenity::Context,
+        incomplete: serenity::UnavailableGuild,
+        full: Option<serenity::Guild>,
+    ) {
+        // An unavailable guild is an outage, not a removal
+        if incomplete.unavailable {
+            return;
+        }
+
+        let (Some(guild), Some(data)) = (full, get_data(&ctx).await) else {
+            return;
+        };
+
+        for channel_id in guild.channels.keys() {
+            retire_discord_channel(
+                &data,
+                (*channel_id).into(),
+                "is no longer reachable because Carmine was removed from the server",
+            )
+            .await;
+        }
     }
 
     async fn message_update(
         &self,
         ctx: serenity::Context,
         old: Option<serenity::Message>,
-        new: Option<serenity::Message>,
-        _event: serenity::MessageUpdateEvent,
+        _new: Option<serenity::Message>,
+        event: serenity::MessageUpdateEvent,
     ) {
-        println!("Message updated: {new:?}");
+        if is_own_or_webhook(
+            &ctx,
+            event.author.as_ref().map(|a| a.id),
+            event.webhook_id.flatten(),
+        ) {
+            return;
+        }
+
+        let (Some(guild_id), Some(data)) = (event.guild_id, get_data(&ctx).await) else {
+            return;
+        };
+
+        let (author_name, author_avatar, author_id) = event
+            .author
+            .as_ref()
+            .map(|a| (a.display_name().to_string(), a.face(), a.id.to_string()))
+            .unwrap_or_default();
+        let message_id = event.id.to_string();
+
+        // Pin state changes only show up as a difference from the cached message
+        let pin_change = match (&old, event.pinned) {
+            (Some(old), Some(pinned)) if old.pinned != pinned => Some(pinned),
+            _ => None,
+        };
+
+        let event_type = match (pin_change, event.content) {
+            (Some(true), _) => EventType::MessagePinned { message_id },
+            (Some(false), _) => EventType::MessageUnpinned { message_id },
+            (None, Some(new_content))
+                if old.as_ref().is_none_or(|old| old.content != new_content) =>
+            {
+                let embeds =
+                    bridge_embeds(event.embeds.as_deref().unwrap_or_default(), &[], None, "");
+                EventType::MessageEdited {
+                    message_id,
+                    new_content,
+                    embeds,
+                }
+            }
+            _ => return,
+        };
+
+        send_to_slack(
+            &data.bridge,
+            BridgeEvent {
+                event_type,
+                author_name,
+                author_avatar,
+                author_id,
+                channel_id: event.channel_id.to_string(),
+                team_id: guild_id.to_string(),
+            },
+        );
     }
 }
 
+async fn forward_reaction(ctx: &serenity::Context, reaction: serenity::Reaction, added: bool) {
+    // Ignore our own reactions, which are bridged copies
+    if reaction.user_id == Some(ctx.cache.current_user().id) {
+        return;
+    }
+
+    let (Some(guild_id), Some(data)) = (reaction.guild_id, get_data(ctx).await) else {
+        return;
+    };
+
+    let author_name = reaction
+        .member
+        .as_ref()
+        .map(|m| m.display_name().to_string())
+        .unwrap_or_default();
+    let message_id = reaction.message_id.to_string();
+    let emoji = reaction.emoji.to_string();
+
+    send_to_slack(
+        &data.bridge,
+        BridgeEvent {
+            event_type: if added {
+                EventType::ReactionAdded { message_id, emoji }
+            } else {
+                EventType::ReactionRemoved { message_id, emoji }
+            },
+            author_name,
+            author_avatar: String::new(),
+            author_id: reaction
+                .user_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            channel_id: reaction.channel_id.to_string(),
+            team_id: guild_id.to_string(),
+        },
+    );
+}
+
 async fn get_discord_channel_id(slack_channel_id: &str, redis_client: &RedisClient) -> Option<u64> {
     match redis_client
         .get_linked_discord_channel(slack_channel_id)
@@ -62,166 +405,583 @@ async fn get_discord_channel_id(slack_channel_id: &str, redis_client: &RedisClie
         Ok(Some(channel_str)) => match channel_str.parse::<u64>() {
             Ok(id) => Some(id),
             Err(_) => {
-                eprintln!("Invalid Discord channel ID format: {channel_str}");
+                warn!(channel = %channel_str, "Invalid Discord channel ID format");
                 None
             }
         },
         Ok(None) => {
-            eprintln!("No linked Discord channel found for Slack channel: {slack_channel_id}");
+            debug!(
+                channel = slack_channel_id,
+                "No linked Discord channel found for Slack channel"
+            );
             None
         }
         Err(e) => {
-            eprintln!("Error fetching Discord channel ID: {e}");
+            error!(error = %e, "Error fetching Discord channel ID");
             None
         }
     }
 }
 
-async fn get_or_create_webhook(
+/// A Slack message laid out for Discord according to the link's long message policy.
+struct DiscordMessage {
+    parts: Vec<String>,
+    file: Option<serenity::CreateAttachment>,
+    /// Shown under the first part.
+    embeds: Vec<serenity::CreateEmbed>,
+}
+
+fn discord_embed(embed: &Embed) -> serenity::CreateEmbed {
+    let mut builder = serenity::CreateEmbed::new().fields(
+        embed
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), field.value.clone(), field.inline)),
+    );
+
+    if let Some(title) = &embed.title {
+        builder = builder.title(title);
+    }
+    if let Some(url) = &embed.url {
+        builder = builder.url(url);
+    }
+    if let Some(description) = &embed.description {
+        builder = builder.description(description);
+    }
+    if let Some(color) = embed.color {
+        builder = builder.color(color);
+    }
+    if let Some(image_url) = &embed.image_url {
+        builder = builder.image(image_url);
+    }
+    if let Some(thumbnail_url) = &embed.thumbnail_url {
+        builder = builder.thumbnail(thumbnail_url);
+    }
+    if let Some(footer) = &embed.footer {
+        builder = builder.footer(serenity::CreateEmbedFooter::new(footer));
+    }
+    if let Some(author) = &embed.author {
+        builder = builder.author(serenity::CreateEmbedAuthor::new(author));
+    }
+
+    builder
+}
+
+async fn prepare_for_discord(
     ctx: &serenity::Context,
-    channel_id: u64,
-) -> Option<serenity::Webhook> {
-    let channel = serenity::ChannelId::new(channel_id);
+    content: &str,
+    embeds: &[Embed],
+    policy: LongMessages,
+    slack_channel_id: &str,
+    slack_message_ts: &str,
+) -> DiscordMessage {
+    let embeds = embeds.iter().map(discord_embed).collect();
 
-    let webhooks = match channel.webhooks(&ctx.http).await {
-        Ok(webhooks) => webhooks,
-        Err(e) => {
-            eprintln!("Failed to get webhooks: {e}");
-            return None;
-        }
-    };
+    if content.chars().count() <= DISCORD_MESSAGE_LIMIT {
+        return DiscordMessage {
+            parts: vec![content.to_string()],
+            file: None,
+            embeds,
+        };
+    }
 
-    // Check for existing webhook
-    if let Some(existing) = webhooks
-        .iter()
-        .find(|w| w.name.as_deref() == Some("carmine"))
-    {
-        return Some(existing.clone());
+    match policy {
+        LongMessages::Split => DiscordMessage {
+            parts: split_message(content, DISCORD_MESSAGE_LIMIT),
+            file: None,
+            embeds,
+        },
+        LongMessages::Truncate => {
+            let suffix = match slack_permalink(ctx, slack_channel_id, slack_message_ts).await {
+                Some(permalink) => format!("\n… [Read more on Slack](<{permalink}>)"),
+                None => "\n… (truncated)".to_string(),
+            };
+
+            DiscordMessage {
+                parts: vec![truncate_message(content, DISCORD_MESSAGE_LIMIT, &suffix)],
+                file: None,
+                embeds,
+            }
+        }
+        LongMessages::File => DiscordMessage {
+            parts: vec![truncate_message(
+                content,
+                DISCORD_MESSAGE_LIMIT,
+                "\n… (full message attached)",
+            )],
+            file: Some(serenity::CreateAttachment::bytes(
+                content.as_bytes().to_vec(),
+                "message.txt",
+            )),
+            embeds,
+        },
     }
+}
 
-    // Create new webhook
-    match channel
-        .create_webhook(&ctx.http, CreateWebhook::new("carmine"))
-        .await
-    {
-        Ok(webhook) => Some(webhook),
+async fn slack_permalink(
+    ctx: &serenity::Context,
+    slack_channel_id: &str,
+    slack_message_ts: &str,
+) -> Option<String> {
+    let data = get_data(ctx).await?;
+
+    let slack_token = data.config.slack_token();
+    let session = data.slack_client.open_session(&slack_token);
+
+    let request = SlackApiChatGetPermalinkRequest::new(
+        slack_channel_id.into(),
+        slack_message_ts.to_string().into(),
+    );
+    match session.chat_get_permalink(&request).await {
+        Ok(response) => Some(response.permalink.to_string()),
         Err(e) => {
-            eprintln!("Failed to create webhook: {e}");
+            error!(error = %e, "Failed to get Slack permalink");
             None
         }
     }
 }
 
-async fn send_message_to_discord(
+/// Points Slack permalinks to bridged messages at their Discord counterparts.
+async fn rewrite_slack_links(
     ctx: &serenity::Context,
-    event: &BridgeEvent,
     content: &str,
     redis_client: &RedisClient,
-) -> Option<serenity::Message> {
-    // Find linked Discord channel
-    let channel_id = match get_discord_channel_id(&event.channel_id, redis_client).await {
-        Some(id) => id,
-        None => return None,
+) -> String {
+    let mut replacements = Vec::new();
+
+    for link in slack_permalinks(content) {
+        if let Ok(Some(discord_info)) = redis_client.get_discord_message(&link.message_id).await
+            && let Some((channel_id, message_ids)) = parse_discord_message(&discord_info)
+            && let Some(message_id) = message_ids.first()
+            && let Some(guild_id) = guild_of(ctx, channel_id)
+        {
+            replacements.push((
+                link.range,
+                format!("https://discord.com/channels/{guild_id}/{channel_id}/{message_id}"),
+            ));
+        }
+    }
+
+    replace_ranges(content, replacements)
+}
+
+/// Replaces Slack shortcodes with unicode, a guild emoji of the same name, or a link to
+/// the Slack custom emoji's image.
+async fn translate_slack_emoji(ctx: &serenity::Context, content: &str, channel_id: u64) -> String {
+    let Some(data) = get_data(ctx).await else {
+        return content.to_string();
     };
 
-    // Get or create webhook
-    let webhook = match get_or_create_webhook(ctx, channel_id).await {
-        Some(webhook) => webhook,
-        None => return None,
+    let slack_token = data.config.slack_token();
+    let slack_emoji = data
+        .slack_emoji
+        .emoji(&data.slack_client, &slack_token)
+        .await;
+    let guild_emoji = guild_emoji(ctx, channel_id);
+
+    slack_text_to_discord(content, |name| {
+        if let Some(emoji) = guild_emoji.iter().find(|emoji| emoji.name == name) {
+            Some(CustomEmoji::Discord(emoji.to_string()))
+        } else {
+            slack_emoji.get(name).cloned().map(CustomEmoji::Image)
+        }
+    })
+}
+
+/// The custom emoji of the guild a channel belongs to.
+fn guild_emoji(ctx: &serenity::Context, channel_id: u64) -> Vec<serenity::Emoji> {
+    guild_of(ctx, channel_id)
+        .and_then(|guild_id| ctx.cache.guild(guild_id))
+        .map(|guild| guild.emojis.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The Discord reaction for a Slack reaction name, if there is one.
+fn discord_reaction(
+    ctx: &serenity::Context,
+    name: &str,
+    channel_id: u64,
+) -> Option<serenity::ReactionType> {
+    if let Some(unicode) = unicode_from_shortcode(name) {
+        return Some(serenity::ReactionType::Unicode(unicode));
+    }
+
+    guild_emoji(ctx, channel_id)
+        .into_iter()
+        .find(|emoji| emoji.name == name)
+        .map(serenity::ReactionType::from)
+}
+
+/// Mirrors a reaction onto the first part of a bridged message.
+async fn handle_reaction(
+    ctx: &serenity::Context,
+    linked_channel_id: u64,
+    slack_message_ts: &str,
+    name: &str,
+    added: bool,
+    redis_client: &RedisClient,
+) {
+    // Look up Discord message from Slack timestamp
+    let Ok(Some(discord_info)) = redis_client.get_discord_message(slack_message_ts).await else {
+        return;
+    };
+    let Some((channel_id, message_ids)) = parse_discord_message(&discord_info) else {
+        warn!(mapping = %discord_info, "Invalid Discord message mapping");
+        return;
     };
 
-    // Send message via webhook
-    match webhook
-        .execute(
-            &ctx.http,
-            true,
-            ExecuteWebhook::new()
-                .username(&event.author_name)
-                .avatar_url(&event.author_avatar)
-                .content(content),
-        )
+    // Ignore mappings kept from an earlier link of the Slack channel
+    let Some(&message_id) = message_ids
+        .first()
+        .filter(|_| channel_id == linked_channel_id)
+    else {
+        return;
+    };
+
+    let Some(reaction) = discord_reaction(ctx, name, channel_id) else {
+        debug!(emoji = name, "No Discord emoji for Slack reaction");
+        return;
+    };
+
+    let channel = serenity::ChannelId::new(channel_id);
+    let result = if added {
+        metrics::api_call("discord", "create_reaction");
+        channel
+            .create_reaction(&ctx.http, message_id, reaction)
+            .await
+    } else {
+        metrics::api_call("discord", "delete_reaction");
+        channel
+            .delete_reaction(&ctx.http, message_id, None, reaction)
+            .await
+    };
+
+    if let Err(e) = result {
+        record_failed_call(&e);
+        error!(error = %e, "Failed to mirror reaction");
+    }
+}
+
+fn guild_of(ctx: &serenity::Context, channel_id: u64) -> Option<serenity::GuildId> {
+    let channel_id = serenity::ChannelId::new(channel_id);
+
+    ctx.cache.guilds().into_iter().find(|guild_id| {
+        ctx.cache
+            .guild(*guild_id)
+            .is_some_and(|guild| guild.channels.contains_key(&channel_id))
+    })
+}
+
+/// Sends through the channel's webhook, recreating it once if someone deleted it.
+async fn execute_webhook(
+    ctx: &serenity::Context,
+    webhooks: &WebhookCache,
+    channel_id: u64,
+    builder: ExecuteWebhook,
+) -> Result<serenity::Message, DeliveryFailure> {
+    let no_webhook = || {
+        metrics::send_failed(Direction::ToDiscord, "channel_gone");
+        DeliveryFailure::permanent("channel_gone", "Carmine can't post in the Discord channel")
+    };
+
+    let webhook = webhooks
+        .get(&ctx.http, channel_id)
         .await
-    {
-        Ok(Some(message)) => Some(message),
+        .ok_or_else(no_webhook)?;
+    let result = match webhook.execute(&ctx.http, builder.clone()).await {
+        // Someone deleted our webhook, make a new one and try again
+        Err(e) if is_discord_error(&e, UNKNOWN_WEBHOOK) => {
+            webhooks.invalidate(channel_id).await;
+            let webhook = webhooks
+                .get(&ctx.http, channel_id)
+                .await
+                .ok_or_else(no_webhook)?;
+            webhook.execute(&ctx.http, builder).await
+        }
+        result => result,
+    };
+
+    match result {
+        Ok(Some(message)) => Ok(message),
         Ok(None) => {
-            eprintln!("Webhook execution returned no message");
-            None
+            warn!("Webhook execution returned no message");
+            metrics::send_failed(Direction::ToDiscord, "rejected");
+            Err(DeliveryFailure::permanent(
+                "rejected",
+                "Discord didn't confirm the message",
+            ))
         }
         Err(e) => {
-            eprintln!("Failed to send webhook message: {e}");
-            None
+            let failure = delivery_failure(&e);
+            metrics::send_failed(Direction::ToDiscord, failure.kind);
+            if failure.permanent {
+                error!(error = %e, "Failed to send webhook message");
+            }
+            Err(failure)
+        }
+    }
+}
+
+/// Why Discord refused a message, and whether it would be refused again. Requests Discord
+/// turned down, other than for a rate limit, fail the same way every time.
+fn delivery_failure(error: &serenity::Error) -> DeliveryFailure {
+    match error {
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+            if response.status_code.is_client_error() && response.status_code.as_u16() != 429 =>
+        {
+            DeliveryFailure::permanent(
+                "rejected",
+                format!("Discord rejected it ({})", response.error.message),
+            )
+        }
+        _ => DeliveryFailure::transient("unavailable", "Discord couldn't be reached"),
+    }
+}
+
+/// Counts a Discord call that failed while delivering an event, and marks the delivery failed.
+fn record_failed_call(error: &serenity::Error) {
+    metrics::send_failed(Direction::ToDiscord, delivery_failure(error).kind);
+    record_failure();
+}
+
+/// Parts of a message that didn't get through, after the ones that did.
+struct UnsentParts {
+    sent: Vec<serenity::Message>,
+    unsent: Vec<String>,
+    reason: String,
+}
+
+/// Sends every part of a message, stopping at the first part that fails for good.
+async fn send_message_to_discord(
+    ctx: &serenity::Context,
+    event: &BridgeEvent,
+    message: DiscordMessage,
+    redis_client: &RedisClient,
+    webhooks: &WebhookCache,
+) -> Result<Vec<serenity::Message>, UnsentParts> {
+    // Find linked Discord channel
+    let Some(channel_id) = get_discord_channel_id(&event.channel_id, redis_client).await else {
+        return Ok(Vec::new());
+    };
+
+    let mut sent = Vec::with_capacity(message.parts.len());
+    let mut file = message.file;
+    let mut embeds = Some(message.embeds);
+    let mut parts = message.parts.into_iter();
+    while let Some(part) = parts.next() {
+        let mut builder = ExecuteWebhook::new()
+            .username(&event.author_name)
+            .avatar_url(&event.author_avatar)
+            .content(&part);
+        if let Some(file) = file.take() {
+            builder = builder.add_file(file);
+        }
+        if let Some(embeds) = embeds.take() {
+            builder = builder.embeds(embeds);
+        }
+
+        match retry_transient(|| execute_webhook(ctx, webhooks, channel_id, builder.clone())).await
+        {
+            Ok(message) => sent.push(message),
+            Err(DeliveryFailure { reason, .. }) => {
+                return Err(UnsentParts {
+                    sent,
+                    unsent: std::iter::once(part).chain(parts).collect(),
+                    reason,
+                });
+            }
         }
     }
+
+    Ok(sent)
+}
+
+/// The event for the parts of a message that weren't sent. Attachments and embeds go with
+/// the first part, which was.
+fn unsent_remainder(event: &BridgeEvent, unsent: Vec<String>) -> BridgeEvent {
+    let mut remainder = event.clone();
+    if let EventType::MessageSent {
+        content,
+        attachments,
+        embeds,
+        ..
+    } = &mut remainder.event_type
+    {
+        *content = unsent.join("\n");
+        attachments.clear();
+        embeds.clear();
+    }
+    remainder
+}
+
+/// Lets the Slack author of a message that couldn't be bridged know why, in a message
+/// only they can see.
+async fn notify_slack_author(
+    ctx: &serenity::Context,
+    event: &BridgeEvent,
+    thread_ts: Option<&str>,
+    feedback: FailureFeedback,
+    reason: &str,
+) {
+    if feedback == FailureFeedback::Off || event.author_id.is_empty() {
+        return;
+    }
+    let Some(data) = get_data(ctx).await else {
+        return;
+    };
+
+    let slack_token = data.config.slack_token();
+    let session = data.slack_client.open_session(&slack_token);
+
+    let request = SlackApiChatPostEphemeralRequest::new(
+        event.channel_id.clone().into(),
+        event.author_id.clone().into(),
+        SlackMessageContent::new().with_text(format!(
+            ":warning: Your message couldn't be sent to Discord: {reason}"
+        )),
+    )
+    .opt_thread_ts(thread_ts.map(|ts| ts.to_string().into()));
+    if let Err(e) = session.chat_post_ephemeral(&request).await {
+        error!(error = %e, "Failed to tell Slack author about undelivered message");
+    }
 }
 
 async fn handle_message_deletion(
     ctx: &serenity::Context,
+    linked_channel_id: u64,
     slack_message_ts: &str,
     redis_client: &RedisClient,
 ) {
     // Look up Discord message from Slack timestamp
     if let Ok(Some(discord_info)) = redis_client.get_discord_message(slack_message_ts).await {
-        let [channel_id, message_id] = match discord_info
-            .split(':')
-            .map(str::parse::<u64>)
-            .collect::<Result<Vec<_>, _>>()
-        {
-            Ok(vec) if vec.len() == 2 => [vec[0], vec[1]],
-            _ => {
-                eprintln!("Invalid Discord message mapping: {discord_info}");
-                return;
-            }
+        let Some((channel_id, message_ids)) = parse_discord_message(&discord_info) else {
+            warn!(mapping = %discord_info, "Invalid Discord message mapping");
+            return;
         };
 
-        let channel = serenity::ChannelId::new(channel_id);
-        if let Err(e) = channel.delete_message(&ctx.http, message_id).await {
-            eprintln!("Failed to delete Discord message: {e}");
+        // Ignore mappings kept from an earlier link of the Slack channel
+        if channel_id != linked_channel_id {
+            return;
         }
 
-        // Clean up mapping
+        // Clean up mapping first so the resulting delete events aren't bridged back
         let _ = redis_client
             .delete_message_mapping_from_slack(slack_message_ts)
             .await;
+
+        let channel = serenity::ChannelId::new(channel_id);
+        for message_id in message_ids {
+            metrics::api_call("discord", "delete_message");
+            if let Err(e) = channel.delete_message(&ctx.http, message_id).await {
+                record_failed_call(&e);
+                error!(error = %e, "Failed to delete Discord message");
+            }
+        }
     }
 }
 
+/// Edits the parts of a bridged message in place, sending or deleting parts when the
+/// edit changed how many the message needs.
 async fn handle_message_edit(
     ctx: &serenity::Context,
+    event: &BridgeEvent,
+    linked_channel_id: u64,
     slack_message_ts: &str,
-    new_content: &str,
+    message: DiscordMessage,
     redis_client: &RedisClient,
+    webhooks: &WebhookCache,
 ) {
     // Look up Discord message from Slack timestamp
-    if let Ok(Some(discord_info)) = redis_client.get_discord_message(slack_message_ts).await {
-        let [channel_id, message_id] = match discord_info
-            .split(':')
-            .map(str::parse::<u64>)
-            .collect::<Result<Vec<_>, _>>()
-        {
-            Ok(vec) if vec.len() == 2 => [vec[0], vec[1]],
-            _ => {
-                eprintln!("Invalid Discord message mapping: {discord_info}");
-                return;
+    let Ok(Some(discord_info)) = redis_client.get_discord_message(slack_message_ts).await else {
+        return;
+    };
+    let Some((channel_id, message_ids)) = parse_discord_message(&discord_info) else {
+        warn!(mapping = %discord_info, "Invalid Discord message mapping");
+        return;
+    };
+
+    // Ignore mappings kept from an earlier link of the Slack channel
+    if channel_id != linked_channel_id {
+        return;
+    }
+
+    let Some(webhook) = webhooks.get(&ctx.http, channel_id).await else {
+        metrics::send_failed(Direction::ToDiscord, "channel_gone");
+        record_failure();
+        error!("Failed to get or create webhook for editing message");
+        return;
+    };
+
+    let part_count = message.parts.len();
+    let mut kept = Vec::with_capacity(part_count);
+    let mut file = message.file;
+    let mut embeds = Some(message.embeds);
+    for (index, part) in message.parts.into_iter().enumerate() {
+        if let Some(&message_id) = message_ids.get(index) {
+            let mut builder = EditWebhookMessage::new().content(part);
+            // Only the first part carries an attachment, and only a long message file
+            if index == 0 {
+                builder = builder.clear_attachments();
+            }
+            if let Some(embeds) = embeds.take() {
+                builder = builder.embeds(embeds);
+            }
+            if let Some(file) = file.take() {
+                builder = builder.new_attachment(file);
             }
-        };
 
-        let webhook = match get_or_create_webhook(ctx, channel_id).await {
-            Some(webhook) => webhook,
-            None => {
-                eprintln!("Failed to get or create webhook for editing message");
-                return;
+            if let Err(e) = webhook
+                .edit_message(&ctx.http, message_id.into(), builder)
+                .await
+            {
+                // Messages belong to the webhook that sent them, so this one can't be edited
+                // anymore, but later messages will go through a fresh webhook
+                if is_discord_error(&e, UNKNOWN_WEBHOOK) {
+                    webhooks.invalidate(channel_id).await;
+                }
+                record_failed_call(&e);
+                error!(error = %e, "Failed to edit Discord message via webhook");
             }
-        };
+            kept.push(message_id);
+        } else {
+            // The message grew, send the overflow as new parts
+            let builder = ExecuteWebhook::new()
+                .username(&event.author_name)
+                .avatar_url(&event.author_avatar)
+                .content(part);
 
-        if let Err(e) = webhook
-            .edit_message(
-                &ctx.http,
-                message_id.into(),
-                EditWebhookMessage::new().content(new_content),
-            )
-            .await
-        {
-            eprintln!("Failed to edit Discord message via webhook: {}", e);
+            match execute_webhook(ctx, webhooks, channel_id, builder).await {
+                Ok(sent) => kept.push(sent.id.get()),
+                Err(_) => record_failure(),
+            }
+        }
+    }
+
+    if kept == message_ids {
+        return;
+    }
+
+    // Remap before deleting leftover parts so their delete events aren't bridged back
+    if let Err(e) = redis_client
+        .delete_message_mapping_from_slack(slack_message_ts)
+        .await
+    {
+        error!(error = %e, "Failed to update message mapping");
+        return;
+    }
+    if let Err(e) = redis_client
+        .store_message_mapping(channel_id, &kept, &event.channel_id, slack_message_ts)
+        .await
+    {
+        error!(error = %e, "Failed to update message mapping");
+    }
+
+    let channel = serenity::ChannelId::new(channel_id);
+    for &message_id in message_ids.iter().skip(part_count) {
+        metrics::api_call("discord", "delete_message");
+        if let Err(e) = channel.delete_message(&ctx.http, message_id).await {
+            record_failed_call(&e);
+            error!(error = %e, "Failed to delete Discord message");
         }
     }
 }
@@ -231,71 +991,245 @@ async fn handle_bridge_event(
     team_id: &str,
     event: BridgeEvent,
     redis_client: &RedisClient,
+    webhooks: &WebhookCache,
+    max_queued_events: usize,
 ) {
     if event.team_id != team_id {
         return; // Ignore events from other workspaces
     }
 
+    let Some(discord_channel_id) = get_discord_channel_id(&event.channel_id, redis_client).await
+    else {
+        return;
+    };
+
+    let Some(settings) = check_link(
+        redis_client,
+        discord_channel_id,
+        &event.channel_id,
+        Direction::ToDiscord,
+        &event,
+        max_queued_events,
+    )
+    .await
+    else {
+        return;
+    };
+
     match &event.event_type {
         EventType::MessageSent {
             content,
             message_id,
+            attachments,
+            thread_id,
+            embeds,
+            ..
         } => {
-            if let Some(discord_message) =
-                send_message_to_discord(ctx, &event, content, &redis_client).await
-            {
+            let hash = content_hash(content, embeds);
+            let content = rewrite_slack_links(ctx, content, redis_client).await;
+            let mut content = translate_slack_emoji(ctx, &content, discord_channel_id).await;
+            if settings.attachments {
+                for attachment in attachments {
+                    content.push_str(&format!(
+                        "\n[{}](<{}>)",
+                        attachment.filename, attachment.url
+                    ));
+                }
+            }
+            let message = prepare_for_discord(
+                ctx,
+                &content,
+                embeds,
+                settings.long_messages,
+                &event.channel_id,
+                message_id,
+            )
+            .await;
+
+            let sent =
+                match send_message_to_discord(ctx, &event, message, redis_client, webhooks).await {
+                    Ok(sent) => sent,
+                    Err(UnsentParts {
+                        sent,
+                        unsent,
+                        reason,
+                    }) => {
+                        // Only what's missing is kept, so a replay doesn't repeat the rest
+                        let (undelivered, reason) = if sent.is_empty() {
+                            (event.clone(), reason)
+                        } else {
+                            (
+                                unsent_remainder(&event, unsent),
+                                format!("only part of it got through, then {reason}"),
+                            )
+                        };
+                        dead_letter(
+                            redis_client,
+                            Direction::ToDiscord,
+                            &undelivered,
+                            discord_channel_id,
+                            &event.channel_id,
+                            &reason,
+                        )
+                        .await;
+                        notify_slack_author(
+                            ctx,
+                            &event,
+                            thread_id.as_deref(),
+                            settings.failure_feedback,
+                            &reason,
+                        )
+                        .await;
+                        sent
+                    }
+                };
+            if !sent.is_empty() {
+                let message_ids: Vec<u64> = sent.iter().map(|message| message.id.get()).collect();
+
                 // Store message mapping in Redis
                 if let Err(e) = redis_client
                     .store_message_mapping(
-                        discord_message.channel_id.into(),
-                        discord_message.id.into(),
+                        discord_channel_id,
+                        &message_ids,
                         &event.channel_id,
                         message_id,
                     )
                     .await
                 {
-                    eprintln!("Failed to store message mapping: {e}");
+                    error!(error = %e, "Failed to store message mapping");
+                }
+                if let Err(e) = redis_client.set_content_hash(message_id, &hash).await {
+                    error!(error = %e, "Failed to store content hash");
                 }
             }
         }
         EventType::MessageDeleted { message_id } => {
-            handle_message_deletion(ctx, message_id, &redis_client).await;
+            handle_message_deletion(ctx, discord_channel_id, message_id, redis_client).await;
         }
         EventType::MessageEdited {
             message_id,
             new_content,
+            embeds,
         } => {
-            handle_message_edit(ctx, message_id, new_content, &redis_client).await;
+            // Skip edits that wouldn't change what the Discord copy shows
+            let hash = content_hash(new_content, embeds);
+            if let Ok(Some(previous)) = redis_client.get_content_hash(message_id).await
+                && previous == hash
+            {
+                Span::current().record("outcome", "unchanged");
+                return;
+            }
+
+            let new_content = rewrite_slack_links(ctx, new_content, redis_client).await;
+            let new_content = translate_slack_emoji(ctx, &new_content, discord_channel_id).await;
+            let message = prepare_for_discord(
+                ctx,
+                &new_content,
+                embeds,
+                settings.long_messages,
+                &event.channel_id,
+                message_id,
+            )
+            .await;
+
+            handle_message_edit(
+                ctx,
+                &event,
+                discord_channel_id,
+                message_id,
+                message,
+                redis_client,
+                webhooks,
+            )
+            .await;
+
+            // Only mapped messages were edited, and only they should get a hash
+            if let Ok(Some(_)) = redis_client.get_discord_message(message_id).await
+                && let Err(e) = redis_client.set_content_hash(message_id, &hash).await
+            {
+                error!(error = %e, "Failed to store content hash");
+            }
+        }
+        EventType::ReactionAdded { message_id, emoji } => {
+            handle_reaction(
+                ctx,
+                discord_channel_id,
+                message_id,
+                emoji,
+                true,
+                redis_client,
+            )
+            .await;
+        }
+        EventType::ReactionRemoved { message_id, emoji } => {
+            handle_reaction(
+                ctx,
+                discord_channel_id,
+                message_id,
+                emoji,
+                false,
+                redis_client,
+            )
+            .await;
+        }
+        EventType::MembershipNotice { content } | EventType::ChannelNotice { content } => {
+            let message = DiscordMessage {
+                parts: vec![content.clone()],
+                file: None,
+                embeds: Vec::new(),
+            };
+            let _ = send_message_to_discord(ctx, &event, message, redis_client, webhooks).await;
         }
         _ => {
-            println!("Unhandled event type: {:?}", event.event_type);
+            debug!(event_type = event.event_type.kind(), "Unhandled event type");
         }
     }
 }
 
-pub async fn start(
+/// A Discord HTTP client for use outside the gateway, counting the rate limits it hits the
+/// way the gateway's handler does.
+pub fn http_client(token: &str) -> serenity::Http {
+    let mut http = serenity::Http::new(token);
+    if let Some(ratelimiter) = http.ratelimiter.as_mut() {
+        ratelimiter.set_ratelimit_callback(Box::new(|_| metrics::rate_limited("discord")));
+    }
+    http
+}
+
+/// Runs the Discord client until `shutdown` is cancelled, then closes every shard.
+pub async fn run(
+    config: Arc<Config>,
     channels: BridgeChannels,
-    mut discord_rx: mpsc::UnboundedReceiver<BridgeEvent>,
     redis_client: RedisClient,
-    slack_client: Arc<SlackHyperClient>,
-) {
-    let discord_token = std::env::var("DISCORD_TOKEN").expect("DISCORD_TOKEN must be set");
-    let guild_id: u64 = std::env::var("DISCORD_GUILD_ID")
-        .expect("DISCORD_GUILD_ID must be set")
-        .parse()
-        .expect("DISCORD_GUILD_ID must be a valid u64");
-    let team_id = std::env::var("SLACK_TEAM_ID").expect("SLACK_TEAM_ID must be set");
+    slack_client: Arc<SlackApiClient>,
+    slack_emoji: Arc<SlackEmojiCache>,
+    discord_client: watch::Sender<Option<DiscordHandle>>,
+    shutdown: CancellationToken,
+) -> Result<(), Error> {
+    let guild_id = config.discord.guild_id;
 
     let intents = serenity::GatewayIntents::all();
     let data = Data {
+        config: config.clone(),
         bridge: channels,
         redis_client: redis_client.clone(),
         slack_client,
+        slack_channels: Arc::new(SlackChannelCache::default()),
+        webhooks: Arc::new(WebhookCache::new(redis_client)),
+        slack_emoji,
     };
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![help(), link_channel(), unlink_channel()],
+            commands: vec![
+                help(),
+                link_channel(),
+                unlink_channel(),
+                pause_link(),
+                resume_link(),
+                list_links(),
+                settings(),
+            ],
             prefix_options: poise::PrefixFrameworkOptions {
                 prefix: Some("c?".to_string()),
                 mention_as_prefix: true,
@@ -305,7 +1239,7 @@ pub async fn start(
         })
         .setup(move |ctx, ready, framework| {
             Box::pin(async move {
-                println!("Logged in as {}", ready.user.name);
+                info!(user = %ready.user.name, "Logged in");
 
                 poise::builtins::register_in_guild(
                     ctx,
@@ -314,21 +1248,129 @@ pub async fn start(
                 )
                 .await?;
 
-                let ctx_for_handler = ctx.clone();
-                tokio::spawn(async move {
-                    while let Some(event) = discord_rx.recv().await {
-                        handle_bridge_event(&ctx_for_handler, &team_id, event, &redis_client).await;
-                    }
-                });
+                // Make the framework data available to the raw event handler
+                ctx.data.write().await.insert::<Data>(data.clone());
+
+                // Let the Slack side and the delivery loop use this client
+                discord_client.send_replace(Some(DiscordHandle {
+                    ctx: ctx.clone(),
+                    shard_manager: framework.shard_manager().clone(),
+                }));
 
                 Ok(data)
             })
         })
         .build();
 
-    let client = serenity::ClientBuilder::new(discord_token, intents)
+    // Keep recent messages around so updates can be compared against them
+    let mut cache_settings = serenity::cache::Settings::default();
+    cache_settings.max_messages = 1000;
+
+    let mut client = serenity::ClientBuilder::new(&config.discord.token, intents)
+        .cache_settings(cache_settings)
         .event_handler(Handler)
         .framework(framework)
+        .await?;
+
+    let shard_manager = client.shard_manager.clone();
+    let closer = tokio::spawn(async move {
+        shutdown.cancelled().await;
+        info!("Closing Discord shards");
+        shard_manager.shutdown_all().await;
+    });
+
+    let result = client.start().await;
+    closer.abort();
+    Ok(result?)
+}
+
+/// Bridges up to `limit` recent messages in a Discord channel that haven't been bridged
+/// yet, oldest first. Returns how many were sent.
+pub async fn backfill(
+    http: &serenity::Http,
+    config: &Config,
+    bridge: &BridgeChannels,
+    redis_client: &RedisClient,
+    channel_id: u64,
+    limit: u8,
+) -> Result<usize, Error> {
+    metrics::api_call("discord", "get_current_user");
+    let current_user = http.get_current_user().await?;
+    let guild_id = serenity::GuildId::new(config.discord.guild_id);
+    metrics::api_call("discord", "get_messages");
+    let messages = serenity::ChannelId::new(channel_id)
+        .messages(http, serenity::GetMessages::new().limit(limit))
+        .await?;
+
+    let mut count = 0;
+    for msg in messages.into_iter().rev() {
+        // Skip what live messages skip, and anything already mapped either way
+        if msg.author.id == current_user.id
+            || msg.webhook_id.is_some()
+            || redis_client
+                .get_slack_message(msg.id.get())
+                .await?
+                .is_some()
+        {
+            continue;
+        }
+
+        send_to_slack(bridge, message_event(guild_id, &msg));
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Delivers bridged events to Discord until `drain` is cancelled, then finishes whatever
+/// is already queued.
+pub async fn deliver(
+    config: Arc<Config>,
+    discord_rx: BridgeReceiver,
+    mut discord_client: DiscordClient,
+    redis_client: RedisClient,
+    drain: CancellationToken,
+) -> Result<(), Error> {
+    let mut discord_rx = discord_rx.lock().await;
+
+    loop {
+        let event = if drain.is_cancelled() {
+            match discord_rx.try_recv() {
+                Ok(event) => event,
+                Err(_) => return Ok(()),
+            }
+        } else {
+            tokio::select! {
+                event = discord_rx.recv() => match event {
+                    Some(event) => event,
+                    None => return Ok(()),
+                },
+                () = drain.cancelled() => continue,
+            }
+        };
+        metrics::set_queue_depth(Direction::ToDiscord, discord_rx.len());
+
+        // Sending only needs HTTP, so a client whose shards have closed still works
+        let handle = discord_client
+            .wait_for(Option::is_some)
+            .await?
+            .clone()
+            .ok_or("Discord client went away")?;
+        let Some(data) = get_data(&handle.ctx).await else {
+            error!("Discord client has no framework data");
+            continue;
+        };
+
+        let span = event.delivery_span(Direction::ToDiscord);
+        handle_bridge_event(
+            &handle.ctx,
+            &config.slack.team_id,
+            event,
+            &redis_client,
+            &data.webhooks,
+            config.max_queued_events,
+        )
+        .instrument(span)
         .await;
-    client.unwrap().start().await.unwrap();
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::time::Duration;
 use std::{convert::Infallible, sync::Arc};
 
 use axum::Extension;
@@ -5,28 +7,57 @@ use axum::body::Bytes;
 use axum::http::Response;
 use http_body_util::combinators::BoxBody;
 use http_body_util::{BodyExt, Empty, Full};
+use poise::serenity_prelude as serenity;
+use serde::{Deserialize, Serialize};
+use slack_morphism::errors::SlackClientError;
 use slack_morphism::prelude::*;
 use slack_morphism::{
     SlackSigningSecret,
     prelude::{
-        SlackClientEventsListenerEnvironment, SlackEventsAxumListener, SlackHyperClient,
-        SlackHyperHttpsConnector, SlackHyperListenerEnvironment, SlackOAuthListenerConfig,
+        SlackClientEventsListenerEnvironment, SlackEventsAxumListener, SlackHyperHttpsConnector,
+        SlackHyperListenerEnvironment, SlackOAuthListenerConfig,
     },
 };
 use tokio::net::TcpListener;
-use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span, debug, error, field, info, info_span, warn};
 
-use crate::bridge::{BridgeChannels, BridgeEvent, EventType};
+use crate::admin;
+use crate::bridge::{
+    Attachment, BridgeChannels, BridgeEvent, BridgeReceiver, DeliveryFailure, Direction, Embed,
+    EventType, ReplyTo, check_link, dead_letter, record_failure, retry_transient,
+};
+use crate::cache::slack_channels::SlackChannelCache;
+use crate::cache::slack_emoji::SlackEmojiCache;
+use crate::cache::slack_users::{OwnBot, SlackUserCache};
 use crate::commands::link::handle_link_channel;
+use crate::commands::list::handle_list_links;
+use crate::commands::pause::{handle_pause_link, handle_resume_link};
+use crate::commands::settings::{
+    SETTINGS_CALLBACK_ID, handle_settings, handle_settings_submission,
+};
 use crate::commands::unlink::handle_unlink_channel;
+use crate::config::Config;
+use crate::dashboard;
+use crate::emoji::{discord_text_to_slack, slack_reaction_name};
+use crate::health;
+use crate::logging::redact;
+use crate::message_links::{discord_message_links, replace_ranges};
+use crate::metrics;
 use crate::redis::RedisClient;
+use crate::settings::FailureFeedback;
+use crate::slack_blocks::render_message;
+use crate::slack_client::{MeteredConnector, SlackApiClient};
+use crate::sources::discord::{self, DiscordClient, Error};
+use crate::split::clip;
 
 async fn oauth_install_function(
     resp: SlackOAuthV2AccessTokenResponse,
     _client: Arc<SlackHyperClient>,
     _states: SlackClientEventsUserState,
 ) {
-    println!("{resp:#?}");
+    // The response carries the access token, so only say who installed us
+    info!(team = %resp.team.id, "App installed");
 }
 
 async fn welcome_installed() -> String {
@@ -42,16 +73,79 @@ async fn error_install() -> String {
 }
 
 async fn push_event(
-    Extension(_environment): Extension<Arc<SlackHyperListenerEnvironment>>,
     Extension(bridge): Extension<Arc<BridgeChannels>>,
-    Extension(slack_client): Extension<Arc<SlackHyperClient>>,
-    Extension(event): Extension<SlackPushEvent>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(slack_client): Extension<Arc<SlackApiClient>>,
+    Extension(discord_http): Extension<Arc<serenity::Http>>,
+    Extension(user_cache): Extension<Arc<SlackUserCache>>,
+    Extension(config): Extension<Arc<Config>>,
+    body: String,
+) -> Response<BoxBody<Bytes, Infallible>> {
+    // Parsed here rather than by the events layer, which rejects events slack-morphism
+    // doesn't know, such as the bot leaving a channel
+    let event = match serde_json::from_str::<SlackPushEvent>(&body) {
+        Ok(event) => event,
+        Err(e) => {
+            match serde_json::from_str::<UnmodeledPushEvent>(&body) {
+                Ok(UnmodeledPushEvent {
+                    event:
+                        UnmodeledEvent::ChannelLeft { channel } | UnmodeledEvent::GroupLeft { channel },
+                }) => {
+                    retire_slack_channel(
+                        &redis_client,
+                        &discord_http,
+                        config.dead_link_retention,
+                        &channel,
+                        "removed Carmine",
+                    )
+                    .instrument(info_span!("slack_push", channel = %channel))
+                    .await;
+                }
+                Err(_) => debug!(error = %e, "Ignoring unhandled push event"),
+            }
+            return Response::new(Empty::new().boxed());
+        }
+    };
+
+    let span = match &event {
+        SlackPushEvent::EventCallback(callback) => info_span!(
+            "slack_push",
+            team = %callback.team_id,
+            event_id = %callback.event_id,
+            channel = field::Empty,
+            message = field::Empty,
+        ),
+        _ => info_span!("slack_push"),
+    };
+
+    route_push_event(
+        event,
+        bridge,
+        redis_client,
+        slack_client,
+        discord_http,
+        user_cache,
+        config,
+    )
+    .instrument(span)
+    .await
+}
+
+async fn route_push_event(
+    event: SlackPushEvent,
+    bridge: Arc<BridgeChannels>,
+    redis_client: Arc<RedisClient>,
+    slack_client: Arc<SlackApiClient>,
+    discord_http: Arc<serenity::Http>,
+    user_cache: Arc<SlackUserCache>,
+    config: Arc<Config>,
 ) -> Response<BoxBody<Bytes, Infallible>> {
-    println!("Received push event: {event:?}");
+    debug!("Received push event");
+    let slack_token = config.slack_token();
 
     match event {
         SlackPushEvent::UrlVerification(url_ver) => {
-            println!("URL verification challenge received");
+            debug!("URL verification challenge received");
             Response::new(Full::new(url_ver.challenge.into()).boxed())
         }
         SlackPushEvent::EventCallback(SlackPushEventCallback {
@@ -59,150 +153,1205 @@ async fn push_event(
             team_id,
             ..
         }) => {
-            if let Some(bridge_event) =
-                create_bridge_event(message_event, team_id, slack_client).await
-                && let Err(e) = bridge.to_discord.send(bridge_event)
+            let span = Span::current();
+            if let Some(channel) = &message_event.origin.channel {
+                span.record("channel", field::display(channel));
+            }
+            span.record("message", field::display(&message_event.origin.ts));
+            debug!(
+                subtype = ?message_event.subtype,
+                content = %redact(message_event.content.as_ref().and_then(|c| c.text.as_deref()).unwrap_or_default()),
+                "Received Slack message"
+            );
+
+            if let Some(bridge_event) = create_bridge_event(
+                message_event,
+                team_id,
+                &slack_client,
+                &slack_token,
+                &user_cache,
+            )
+            .await
             {
-                eprintln!("Failed to send bridge event: {e}");
+                metrics::event_received(Direction::ToDiscord, &bridge_event.event_type);
+                if let Err(e) = bridge.to_discord.send(bridge_event) {
+                    error!(error = %e, "Failed to send bridge event");
+                }
             }
 
             Response::new(Empty::new().boxed())
         }
+        SlackPushEvent::EventCallback(SlackPushEventCallback {
+            event: SlackEventCallbackBody::ReactionAdded(reaction_event),
+            team_id,
+            ..
+        }) => {
+            let reaction = Reaction {
+                user: reaction_event.user,
+                name: reaction_event.reaction,
+                item: reaction_event.item,
+                added: true,
+            };
+            forward_reaction(
+                reaction,
+                team_id,
+                &bridge,
+                &slack_client,
+                &slack_token,
+                &user_cache,
+            )
+            .await;
+
+            Response::new(Empty::new().boxed())
+        }
+        SlackPushEvent::EventCallback(SlackPushEventCallback {
+            event: SlackEventCallbackBody::ReactionRemoved(reaction_event),
+            team_id,
+            ..
+        }) => {
+            let reaction = Reaction {
+                user: reaction_event.user,
+                name: reaction_event.reaction,
+                item: reaction_event.item,
+                added: false,
+            };
+            forward_reaction(
+                reaction,
+                team_id,
+                &bridge,
+                &slack_client,
+                &slack_token,
+                &user_cache,
+            )
+            .await;
+
+            Response::new(Empty::new().boxed())
+        }
+        SlackPushEvent::EventCallback(SlackPushEventCallback {
+            event: SlackEventCallbackBody::ChannelArchive(archive_event),
+            ..
+        }) => {
+            retire_slack_channel(
+                &redis_client,
+                &discord_http,
+                config.dead_link_retention,
+                archive_event.channel.as_ref(),
+                "was archived",
+            )
+            .await;
+
+            Response::new(Empty::new().boxed())
+        }
+        SlackPushEvent::EventCallback(SlackPushEventCallback {
+            event: SlackEventCallbackBody::ChannelDeleted(deleted_event),
+            ..
+        }) => {
+            retire_slack_channel(
+                &redis_client,
+                &discord_http,
+                config.dead_link_retention,
+                deleted_event.channel.as_ref(),
+                "was deleted",
+            )
+            .await;
+
+            Response::new(Empty::new().boxed())
+        }
+        SlackPushEvent::EventCallback(SlackPushEventCallback {
+            event: SlackEventCallbackBody::MemberLeftChannel(left_event),
+            ..
+        }) => {
+            let own_bot = user_cache.own_bot(&slack_client, &slack_token).await;
+            if is_own_user(own_bot, &left_event.user) {
+                retire_slack_channel(
+                    &redis_client,
+                    &discord_http,
+                    config.dead_link_retention,
+                    left_event.channel.as_ref(),
+                    "removed Carmine",
+                )
+                .await;
+            }
+
+            Response::new(Empty::new().boxed())
+        }
+        SlackPushEvent::EventCallback(SlackPushEventCallback {
+            event: SlackEventCallbackBody::UserChange(change_event),
+            team_id,
+            ..
+        }) => {
+            user_cache
+                .invalidate(team_id.as_ref(), &change_event.user.id)
+                .await;
+
+            Response::new(Empty::new().boxed())
+        }
         _ => {
-            println!("Other event type: {event:?}");
+            debug!("Ignoring unhandled push event");
             Response::new(Empty::new().boxed())
         }
     }
 }
 
+/// Push events slack-morphism has no types for, read from the raw payload.
+#[derive(Deserialize)]
+struct UnmodeledPushEvent {
+    event: UnmodeledEvent,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum UnmodeledEvent {
+    /// The bot left or was removed from a public channel.
+    ChannelLeft { channel: String },
+    /// The same, for a private channel.
+    GroupLeft { channel: String },
+}
+
+/// A reaction added to or removed from a Slack message.
+struct Reaction {
+    user: SlackUserId,
+    name: SlackReactionName,
+    item: SlackReactionsItem,
+    added: bool,
+}
+
+async fn forward_reaction(
+    reaction: Reaction,
+    team_id: SlackTeamId,
+    bridge: &BridgeChannels,
+    slack_client: &SlackApiClient,
+    slack_token: &SlackApiToken,
+    user_cache: &SlackUserCache,
+) {
+    // Only messages are bridged, not files
+    let SlackReactionsItem::Message(message) = reaction.item else {
+        return;
+    };
+    let Some(channel_id) = message.origin.channel else {
+        return;
+    };
+
+    // Ignore our own reactions, which are bridged copies
+    let own_bot = user_cache.own_bot(slack_client, slack_token).await;
+    if is_own_user(own_bot, &reaction.user) {
+        return;
+    }
+
+    let team_id = team_id.to_string();
+    let author_id = reaction.user.to_string();
+    let (author_name, author_avatar) = get_user_info(
+        Some(reaction.user),
+        &team_id,
+        slack_client,
+        slack_token,
+        user_cache,
+    )
+    .await;
+    let message_id = message.origin.ts.to_string();
+    let emoji = reaction.name.to_string();
+
+    let bridge_event = BridgeEvent {
+        event_type: if reaction.added {
+            EventType::ReactionAdded { message_id, emoji }
+        } else {
+            EventType::ReactionRemoved { message_id, emoji }
+        },
+        author_name,
+        author_avatar,
+        author_id,
+        channel_id: channel_id.to_string(),
+        team_id,
+    };
+    metrics::event_received(Direction::ToDiscord, &bridge_event.event_type);
+    if let Err(e) = bridge.to_discord.send(bridge_event) {
+        error!(error = %e, "Failed to send bridge event");
+    }
+}
+
+/// Whether a message was posted by our own app, as all our bridged copies are.
+fn is_own_bot(own_bot: Option<&OwnBot>, bot_id: Option<&SlackBotId>) -> bool {
+    own_bot
+        .and_then(|own_bot| own_bot.bot_id.as_ref())
+        .is_some_and(|own_bot_id| Some(own_bot_id) == bot_id)
+}
+
+/// The name a bot message was posted under.
+fn bot_name(sender: &SlackMessageSender) -> String {
+    sender
+        .username
+        .clone()
+        .or_else(|| {
+            sender
+                .bot_profile
+                .as_ref()
+                .map(|profile| profile.name.clone())
+        })
+        .unwrap_or_else(|| "Bot".to_string())
+}
+
+/// The app's bot user, whose avatar is the app's icon. slack-morphism drops
+/// `bot_profile.icons`, so this is the only way to the icon.
+fn bot_user(sender: &SlackMessageSender) -> Option<SlackUserId> {
+    let profile = sender.bot_profile.as_ref()?;
+    Some(profile.user_id.clone()?.into())
+}
+
+/// Whether a user is our own bot user, which adds our bridged reactions.
+fn is_own_user(own_bot: Option<&OwnBot>, user_id: &SlackUserId) -> bool {
+    own_bot.is_some_and(|own_bot| own_bot.user_id == *user_id)
+}
+
+/// Marks the link for a Slack channel that went away as dead and tells the Discord side.
+async fn retire_slack_channel(
+    redis_client: &RedisClient,
+    discord_http: &serenity::Http,
+    retention: Duration,
+    slack_channel_id: &str,
+    reason: &str,
+) {
+    let Ok(Some(discord_channel_str)) = redis_client
+        .get_linked_discord_channel(slack_channel_id)
+        .await
+    else {
+        return;
+    };
+    let Ok(discord_channel_id) = discord_channel_str.trim().parse::<u64>() else {
+        warn!(channel = %discord_channel_str, "Invalid Discord channel ID format");
+        return;
+    };
+
+    if let Err(e) = redis_client
+        .mark_link_dead(discord_channel_id, slack_channel_id, retention)
+        .await
+    {
+        error!(error = %e, "Failed to mark link as dead");
+        return;
+    }
+
+    metrics::api_call("discord", "create_message");
+    if let Err(e) = serenity::ChannelId::new(discord_channel_id)
+        .say(
+            discord_http,
+            format!(
+                "The linked Slack channel **`{slack_channel_id}`** {reason}, so this channel has been unlinked."
+            ),
+        )
+        .await
+    {
+        error!(error = %e, "Failed to notify Discord channel");
+    }
+}
+
 async fn get_user_info(
     user_id: Option<SlackUserId>,
-    slack_client: Arc<SlackHyperClient>,
+    team_id: &str,
+    slack_client: &SlackApiClient,
+    slack_token: &SlackApiToken,
+    user_cache: &SlackUserCache,
 ) -> (String, String) {
-    let oauth_token = std::env::var("SLACK_OAUTH_TOKEN").expect("SLACK_OAUTH_TOKEN must be set");
-    let slack_token = SlackApiToken::new(oauth_token.into());
-
-    let session = slack_client.open_session(&slack_token);
-
     if let Some(ref user_id) = user_id
-        && let Ok(response) = session
-            .users_info(&SlackApiUsersInfoRequest::new(user_id.clone()))
+        && let Some(profile) = user_cache
+            .profile(slack_client, slack_token, team_id, user_id)
             .await
-        && let Some(ref profile) = response.user.profile
     {
-        let name = response
-            .user
-            .name
-            .or(response.user.real_name)
-            .or(profile.display_name.clone())
-            .or(profile.real_name.clone())
-            .unwrap_or(format!("User-{}", user_id.to_string()));
-
-        let avatar = profile
-            .icon
-            .as_ref()
-            .and_then(|i| i.image_original.clone())
-            .unwrap_or_default();
-
-        (name, avatar)
+        (profile.name, profile.avatar)
     } else {
         ("Unknown User".to_string(), "".to_string())
     }
 }
 
+/// Turns a Slack message event into a bridge event, skipping our own bridged copies.
 async fn create_bridge_event(
     message_event: SlackMessageEvent,
     team_id: SlackTeamId,
-    slack_client: Arc<SlackHyperClient>,
+    slack_client: &SlackApiClient,
+    slack_token: &SlackApiToken,
+    user_cache: &SlackUserCache,
 ) -> Option<BridgeEvent> {
-    // Extract common metadata
-    let message_ts = message_event.origin.ts.to_string();
-    let channel_id = message_event.origin.channel?.to_string();
-    let team_id_str = team_id.to_string();
+    let channel_id = message_event.origin.channel.as_ref()?.to_string();
+    let team_id = team_id.to_string();
+    let sender = message_sender(&message_event)?;
 
-    // Extract user info
-    let message_edited = message_event.message.clone();
-    let user_id = match message_event.subtype {
-        Some(SlackMessageEventType::MessageChanged) => {
-            // For edits, get user from message.sender
-            message_edited.as_ref().and_then(|m| m.sender.user.clone())
+    // Slack reports our bridged copies as bot messages
+    let own_bot = user_cache.own_bot(slack_client, slack_token).await;
+    if is_own_bot(own_bot, sender.bot_id.as_ref()) {
+        return None;
+    }
+
+    let event_type = message_event_type(&message_event)?;
+
+    let author_id = sender
+        .user
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    let (author_name, author_avatar) = match message_event.subtype {
+        Some(SlackMessageEventType::BotMessage) => {
+            let (_, icon) = get_user_info(
+                bot_user(sender),
+                &team_id,
+                slack_client,
+                slack_token,
+                user_cache,
+            )
+            .await;
+            (bot_name(sender), icon)
         }
         _ => {
-            // For regular messages, use main sender
-            message_event.sender.user
+            get_user_info(
+                sender.user.clone(),
+                &team_id,
+                slack_client,
+                slack_token,
+                user_cache,
+            )
+            .await
         }
     };
-    let (author_name, author_avatar) = get_user_info(user_id, slack_client).await;
+
+    Some(BridgeEvent {
+        event_type,
+        author_name,
+        author_avatar,
+        author_id,
+        channel_id,
+        team_id,
+    })
+}
+
+/// Who sent a message. Edits carry the edited message, including its sender, in `message`.
+fn message_sender(message_event: &SlackMessageEvent) -> Option<&SlackMessageSender> {
+    match message_event.subtype {
+        Some(SlackMessageEventType::MessageChanged) => {
+            Some(&message_event.message.as_ref()?.sender)
+        }
+        _ => Some(&message_event.sender),
+    }
+}
+
+/// What a Slack message event bridges as, if anything. Subtypes map as follows:
+///
+/// - none and `thread_broadcast`: a message. Broadcasts already show in the channel, so
+///   they go across as top-level messages rather than thread replies.
+/// - `bot_message`: a message under the bot's name and icon, except our own bridged copies
+/// - `me_message`: a message in italics, like `/me` on Discord
+/// - `file_share`: a message with links to the shared files
+/// - `message_changed` and `message_deleted`: an edit or delete of the bridged copy
+/// - `channel_join` and `channel_leave`: a membership notice, if the link enables them
+/// - `channel_topic` and `channel_purpose`: a channel notice with the new text
+///
+/// slack-morphism has no `message_replied` subtype, so those events fail to parse and are
+/// ignored with the other push events it doesn't know. Slack also sends every thread
+/// reply as a plain message with a `thread_ts`, which covers them. Other subtypes are
+/// ignored.
+fn message_event_type(message_event: &SlackMessageEvent) -> Option<EventType> {
+    use SlackMessageEventType as Subtype;
+
+    let message_ts = message_event.origin.ts.to_string();
+    let message_edited = message_event.message.as_ref();
 
     let event_type = match message_event.subtype {
-        None => {
-            // Regular message
-            let content = message_event
-                .content?
-                .text
-                .unwrap_or_else(|| "Failed to get message content".to_string());
+        None
+        | Some(Subtype::BotMessage)
+        | Some(Subtype::MeMessage)
+        | Some(Subtype::FileShare)
+        | Some(Subtype::ThreadBroadcast) => {
+            // Regular message, or an app post made of blocks and attachments
+            let message_content = message_event.content.as_ref()?;
+            let (mut content, embeds) = render_message(message_content);
+            if message_event.subtype == Some(Subtype::MeMessage) {
+                content = format!("_{content}_");
+            }
+
+            let attachments: Vec<Attachment> = message_content
+                .files
+                .iter()
+                .flatten()
+                .filter_map(|file| {
+                    Some(Attachment {
+                        filename: file
+                            .name
+                            .clone()
+                            .or_else(|| file.title.clone())
+                            .unwrap_or_else(|| "file".to_string()),
+                        url: file.permalink.as_ref()?.to_string(),
+                    })
+                })
+                .collect();
+
+            if content.trim().is_empty() && embeds.is_empty() && attachments.is_empty() {
+                return None;
+            }
+
+            // Replies carry the parent's timestamp as thread_ts
+            let thread_id = message_event
+                .origin
+                .thread_ts
+                .as_ref()
+                .map(|ts| ts.to_string())
+                .filter(|ts| *ts != message_ts)
+                .filter(|_| message_event.subtype != Some(Subtype::ThreadBroadcast));
 
             EventType::MessageSent {
-                message_id: message_ts.clone(),
+                message_id: message_ts,
                 content,
+                attachments,
+                thread_id,
+                reply_to: None,
+                embeds,
             }
         }
-        Some(SlackMessageEventType::MessageChanged) => {
+        Some(Subtype::MessageChanged) => {
+            // Slack also reports unfurls being attached as edits, so only text and block
+            // changes count
+            let edited_content = message_edited?.content.as_ref()?;
+            let previous_content = message_event
+                .previous_message
+                .as_ref()
+                .and_then(|previous| previous.content.as_ref());
+            if previous_content.is_some_and(|previous| {
+                previous.text == edited_content.text && previous.blocks == edited_content.blocks
+            }) {
+                return None;
+            }
+
             // For edited messages, use the original message's timestamp
-            let original_message_ts = message_edited.as_ref()?.ts.to_string();
+            let original_message_ts = message_edited?.ts.to_string();
 
             // For edited messages, the new content is in message.content, not the content field
-            let new_content = message_edited
-                .as_ref()?
-                .content
-                .as_ref()?
-                .text
-                .clone()
-                .unwrap_or_else(|| "Failed to get message content".to_string());
+            let (new_content, embeds) = render_message(edited_content);
 
             EventType::MessageEdited {
                 message_id: original_message_ts,
                 new_content,
+                embeds,
             }
         }
-        Some(SlackMessageEventType::MessageDeleted) => {
-            let deleted_message_id = message_event
-                .deleted_ts
-                .map(|ts| ts.to_string())
-                .expect("Deleted message did not have a timestamp");
+        Some(Subtype::MessageDeleted) => EventType::MessageDeleted {
+            message_id: message_event.deleted_ts.as_ref()?.to_string(),
+        },
+        Some(Subtype::ChannelJoin) => EventType::MembershipNotice {
+            content: "_joined the channel_".to_string(),
+        },
+        Some(Subtype::ChannelLeave) => EventType::MembershipNotice {
+            content: "_left the channel_".to_string(),
+        },
+        Some(Subtype::ChannelTopic) | Some(Subtype::ChannelPurpose) => {
+            // Slack writes these as "<@U123> set the channel topic: ..."
+            let text = message_event.content.as_ref()?.text.as_deref()?;
+            let text = match text.strip_prefix("<@") {
+                Some(rest) => rest.split_once("> ").map_or(rest, |(_, rest)| rest),
+                None => text,
+            };
 
-            EventType::MessageDeleted {
-                message_id: deleted_message_id,
+            EventType::ChannelNotice {
+                content: format!("_{text}_"),
             }
         }
         _ => {
-            println!("Unhandled message subtype: {:?}", message_event.subtype);
+            debug!(subtype = ?message_event.subtype, "Unhandled message subtype");
             return None;
         }
     };
 
-    Some(BridgeEvent {
-        event_type,
-        author_name,
-        author_avatar,
-        channel_id,
-        team_id: team_id_str,
-    })
+    Some(event_type)
+}
+
+/// How much of a replied-to message is quoted above a reply.
+const REPLY_SNIPPET_LENGTH: usize = 200;
+
+#[derive(Serialize)]
+struct SlackApiPinsRequest {
+    channel: SlackChannelId,
+    timestamp: SlackTs,
+}
+
+#[derive(Deserialize)]
+struct SlackApiPinsResponse {}
+
+async fn get_slack_channel_id(
+    discord_channel_id: &str,
+    redis_client: &RedisClient,
+) -> Option<String> {
+    let discord_channel_id = match discord_channel_id.parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => {
+            warn!(channel = %discord_channel_id, "Invalid Discord channel ID format");
+            return None;
+        }
+    };
+
+    match redis_client
+        .get_linked_slack_channel(discord_channel_id)
+        .await
+    {
+        Ok(Some(channel_id)) => Some(channel_id),
+        Ok(None) => {
+            debug!(
+                channel = discord_channel_id,
+                "No linked Slack channel found for Discord channel"
+            );
+            None
+        }
+        Err(e) => {
+            error!(error = %e, "Error fetching Slack channel ID");
+            None
+        }
+    }
+}
+
+async fn get_slack_message(
+    linked_channel_id: &str,
+    discord_message_id: &str,
+    redis_client: &RedisClient,
+) -> Option<(SlackChannelId, SlackTs)> {
+    let discord_message_id = discord_message_id.parse::<u64>().ok()?;
+
+    match redis_client.get_slack_message(discord_message_id).await {
+        Ok(Some(slack_info)) => match slack_info.split_once(':') {
+            // Ignore mappings kept from an earlier link of the Discord channel
+            Some((channel_id, _)) if channel_id != linked_channel_id => None,
+            Some((channel_id, ts)) => Some((channel_id.into(), ts.into())),
+            None => {
+                warn!(mapping = %slack_info, "Invalid Slack message mapping");
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(e) => {
+            error!(error = %e, "Error fetching Slack message mapping");
+            None
+        }
+    }
+}
+
+/// Lays out an embed as a legacy attachment, which is the only way Slack shows a color
+/// bar, holding sections for the text and fields and an image block.
+fn slack_attachment(embed: &Embed) -> SlackMessageAttachment {
+    let mut blocks: Vec<SlackBlock> = Vec::new();
+    let mut lines = Vec::new();
+
+    if let Some(author) = &embed.author {
+        lines.push(author.clone());
+    }
+    match (&embed.title, &embed.url) {
+        (Some(title), Some(url)) => lines.push(format!("*<{url}|{title}>*")),
+        (Some(title), None) => lines.push(format!("*{title}*")),
+        _ => {}
+    }
+    lines.extend(embed.description.clone());
+    if !lines.is_empty() {
+        let mut section = SlackSectionBlock::new().with_text(md!(clip(&lines.join("\n"), 3000)));
+        if let Some(thumbnail) = embed
+            .thumbnail_url
+            .as_deref()
+            .and_then(|url| url.parse().ok())
+        {
+            section = section.with_accessory(
+                SlackBlockImageElement::new(
+                    SlackImageUrlOrFile::ImageUrl {
+                        image_url: thumbnail,
+                    },
+                    "thumbnail".into(),
+                )
+                .into(),
+            );
+        }
+        blocks.push(section.into());
+    }
+
+    // Sections hold at most ten fields
+    for fields in embed.fields.chunks(10) {
+        let fields = fields
+            .iter()
+            .map(|field| md!(clip(&format!("*{}*\n{}", field.name, field.value), 2000)))
+            .collect();
+        blocks.push(SlackSectionBlock::new().with_fields(fields).into());
+    }
+
+    if let Some(image_url) = embed.image_url.as_deref().and_then(|url| url.parse().ok()) {
+        let alt_text = embed.title.clone().unwrap_or_else(|| "image".to_string());
+        blocks.push(
+            SlackImageBlock::new(SlackImageUrlOrFile::ImageUrl { image_url }, alt_text).into(),
+        );
+    }
+
+    if let Some(footer) = &embed.footer {
+        blocks.push(
+            SlackContextBlock::new(vec![SlackContextBlockElement::MarkDown(
+                SlackBlockMarkDownText::new(footer.clone()),
+            )])
+            .into(),
+        );
+    }
+
+    let fallback = embed
+        .title
+        .clone()
+        .or_else(|| embed.description.clone())
+        .unwrap_or_else(|| "Embed".to_string());
+
+    SlackMessageAttachment::new()
+        .with_fallback(fallback)
+        .with_blocks(blocks)
+        .opt_color(embed.color.map(|color| format!("#{color:06x}")))
+}
+
+async fn send_message_to_slack(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    slack_channel_id: &str,
+    event: &BridgeEvent,
+    content: &str,
+    embeds: &[Embed],
+    thread_ts: Option<SlackTs>,
+) -> Result<SlackTs, DeliveryFailure> {
+    let mut message = SlackMessageContent::new().with_text(content.to_string());
+    if !embeds.is_empty() {
+        message = message.with_attachments(embeds.iter().map(slack_attachment).collect());
+    }
+
+    let request = SlackApiChatPostMessageRequest::new(slack_channel_id.into(), message)
+        .with_username(event.author_name.clone())
+        .with_icon_url(event.author_avatar.clone())
+        .opt_thread_ts(thread_ts);
+
+    match session.chat_post_message(&request).await {
+        Ok(response) => Ok(response.ts),
+        Err(e) => {
+            let failure = delivery_failure(&e);
+            metrics::send_failed(Direction::ToSlack, failure.kind);
+            if failure.permanent {
+                error!(error = %e, "Failed to send Slack message");
+            }
+            Err(failure)
+        }
+    }
+}
+
+/// How long a reply about an undelivered message stays up on Discord.
+const FEEDBACK_REPLY_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Why Slack refused a message, in words for the person who sent it, and whether it would
+/// be refused again.
+fn delivery_failure(error: &SlackClientError) -> DeliveryFailure {
+    match error {
+        SlackClientError::ApiError(error) => match error.code.as_str() {
+            "msg_too_long" => DeliveryFailure::permanent("too_long", "it's too long for Slack"),
+            "channel_not_found" | "is_archived" | "not_in_channel" => DeliveryFailure::permanent(
+                "channel_gone",
+                "the Slack channel is gone or Carmine isn't in it",
+            ),
+            // Slack's own trouble, which passes
+            "ratelimited"
+            | "internal_error"
+            | "fatal_error"
+            | "service_unavailable"
+            | "request_timeout" => DeliveryFailure::transient(
+                "unavailable",
+                format!("Slack had a problem ({})", error.code),
+            ),
+            code => DeliveryFailure::permanent("rejected", format!("Slack rejected it ({code})")),
+        },
+        SlackClientError::RateLimitError(error) => DeliveryFailure {
+            retry_after: error.retry_after,
+            ..DeliveryFailure::transient("rate_limited", "Slack is rate limiting Carmine")
+        },
+        SlackClientError::HttpError(error)
+            if error.status_code.is_client_error() && error.status_code.as_u16() != 429 =>
+        {
+            DeliveryFailure::permanent(
+                "rejected",
+                format!("Slack rejected it ({})", error.status_code),
+            )
+        }
+        _ => DeliveryFailure::transient("unavailable", "Slack couldn't be reached"),
+    }
+}
+
+/// Counts a Slack call that failed while delivering an event, and marks the delivery failed.
+fn record_failed_call(error: &SlackClientError) {
+    metrics::send_failed(Direction::ToSlack, delivery_failure(error).kind);
+    record_failure();
+}
+
+/// Lets the Discord author of a message that couldn't be bridged know, the way the link
+/// is set up to.
+async fn notify_discord_author(
+    discord_http: &Arc<serenity::Http>,
+    channel_id: u64,
+    message_id: &str,
+    feedback: FailureFeedback,
+    reason: &str,
+) {
+    let Ok(message_id) = message_id.parse::<u64>() else {
+        return;
+    };
+    let channel = serenity::ChannelId::new(channel_id);
+
+    match feedback {
+        FailureFeedback::Off => {}
+        FailureFeedback::Reaction => {
+            let reaction = serenity::ReactionType::Unicode("⚠️".to_string());
+            metrics::api_call("discord", "create_reaction");
+            if let Err(e) = channel
+                .create_reaction(discord_http, message_id, reaction)
+                .await
+            {
+                error!(error = %e, "Failed to flag undelivered message");
+            }
+        }
+        FailureFeedback::Reply => {
+            let reply = serenity::CreateMessage::new()
+                .content(format!(
+                    "⚠️ This message couldn't be sent to Slack: {reason}"
+                ))
+                .reference_message((channel, serenity::MessageId::new(message_id)));
+
+            metrics::api_call("discord", "create_message");
+            match channel.send_message(discord_http, reply).await {
+                // Discord has no ephemeral messages outside interactions, so the reply
+                // cleans itself up instead
+                Ok(reply) => {
+                    let discord_http = discord_http.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(FEEDBACK_REPLY_LIFETIME).await;
+                        metrics::api_call("discord", "delete_message");
+                        if let Err(e) = channel.delete_message(&discord_http, reply.id).await {
+                            warn!(error = %e, "Failed to remove failure reply");
+                        }
+                    });
+                }
+                Err(e) => error!(error = %e, "Failed to reply about undelivered message"),
+            }
+        }
+    }
+}
+
+async fn slack_permalink(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    channel_id: SlackChannelId,
+    ts: SlackTs,
+) -> Option<String> {
+    match session
+        .chat_get_permalink(&SlackApiChatGetPermalinkRequest::new(channel_id, ts))
+        .await
+    {
+        Ok(response) => Some(response.permalink.to_string()),
+        Err(e) => {
+            error!(error = %e, "Failed to get Slack permalink");
+            None
+        }
+    }
+}
+
+/// Points links to bridged Discord messages at their Slack counterparts.
+async fn rewrite_discord_links(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    content: &str,
+    redis_client: &RedisClient,
+) -> String {
+    let mut replacements = Vec::new();
+
+    for link in discord_message_links(content) {
+        let Ok(discord_message_id) = link.message_id.parse::<u64>() else {
+            continue;
+        };
+
+        if let Ok(Some(slack_info)) = redis_client.get_slack_message(discord_message_id).await
+            && let Some((channel_id, ts)) = slack_info.split_once(':')
+            && let Some(permalink) = slack_permalink(session, channel_id.into(), ts.into()).await
+        {
+            replacements.push((link.range, permalink));
+        }
+    }
+
+    replace_ranges(content, replacements)
+}
+
+/// Threads a reply under its parent when the parent was bridged and the link mirrors
+/// threads, otherwise quotes the parent above the reply.
+async fn render_reply(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    slack_channel_id: &str,
+    reply_to: &ReplyTo,
+    content: String,
+    threads: bool,
+    redis_client: &RedisClient,
+) -> (String, Option<SlackTs>) {
+    let parent = get_slack_message(slack_channel_id, &reply_to.message_id, redis_client).await;
+
+    if threads && let Some((_, ts)) = parent {
+        return (content, Some(ts));
+    }
+
+    let permalink = match parent {
+        Some((channel_id, ts)) => slack_permalink(session, channel_id, ts).await,
+        None => None,
+    }
+    .unwrap_or_else(|| reply_to.url.clone());
+
+    let snippet: String = reply_to
+        .content
+        .chars()
+        .take(REPLY_SNIPPET_LENGTH)
+        .collect();
+    let ellipsis = if snippet.len() < reply_to.content.len() {
+        "…"
+    } else {
+        ""
+    };
+    let quote = format!("*{}*: {snippet}{ellipsis}", reply_to.author_name)
+        .lines()
+        .map(|line| format!("> {line}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    (
+        format!("{quote}\n> <{permalink}|View message>\n{content}"),
+        None,
+    )
+}
+
+async fn handle_message_deletion(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    linked_channel_id: &str,
+    discord_message_id: &str,
+    redis_client: &RedisClient,
+) {
+    // Look up Slack message from Discord message ID
+    let Some((channel_id, ts)) =
+        get_slack_message(linked_channel_id, discord_message_id, redis_client).await
+    else {
+        return;
+    };
+
+    // Clean up mapping first so the resulting delete event isn't bridged back
+    if let Ok(discord_message_id) = discord_message_id.parse::<u64>() {
+        let _ = redis_client
+            .delete_message_mapping_from_discord(discord_message_id)
+            .await;
+    }
+
+    if let Err(e) = session
+        .chat_delete(&SlackApiChatDeleteRequest::new(channel_id, ts))
+        .await
+    {
+        record_failed_call(&e);
+        error!(error = %e, "Failed to delete Slack message");
+    }
+}
+
+async fn handle_message_edit(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    linked_channel_id: &str,
+    discord_message_id: &str,
+    new_content: &str,
+    embeds: &[Embed],
+    redis_client: &RedisClient,
+) {
+    // Look up Slack message from Discord message ID
+    let Some((channel_id, ts)) =
+        get_slack_message(linked_channel_id, discord_message_id, redis_client).await
+    else {
+        return;
+    };
+
+    // Leaving attachments out keeps the ones already on the message
+    let mut message = SlackMessageContent::new().with_text(new_content.to_string());
+    if !embeds.is_empty() {
+        message = message.with_attachments(embeds.iter().map(slack_attachment).collect());
+    }
+
+    let request = SlackApiChatUpdateRequest::new(channel_id, message, ts);
+
+    if let Err(e) = session.chat_update(&request).await {
+        record_failed_call(&e);
+        error!(error = %e, "Failed to edit Slack message");
+    }
+}
+
+async fn handle_pin_change(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    linked_channel_id: &str,
+    discord_message_id: &str,
+    pinned: bool,
+    redis_client: &RedisClient,
+) {
+    // Look up Slack message from Discord message ID
+    let Some((channel, timestamp)) =
+        get_slack_message(linked_channel_id, discord_message_id, redis_client).await
+    else {
+        return;
+    };
+
+    let method = if pinned { "pins.add" } else { "pins.remove" };
+    let result: ClientResult<SlackApiPinsResponse> = session
+        .http_session_api
+        .http_post(method, &SlackApiPinsRequest { channel, timestamp }, None)
+        .await;
+
+    if let Err(e) = result {
+        record_failed_call(&e);
+        error!(error = %e, method, "Failed to call Slack API");
+    }
+}
+
+/// Mirrors a reaction onto a bridged message. Reactions with a Discord custom emoji the
+/// workspace has no match for are dropped.
+async fn handle_reaction(
+    session: &SlackClientSession<'_, MeteredConnector>,
+    linked_channel_id: &str,
+    discord_message_id: &str,
+    emoji: &str,
+    added: bool,
+    redis_client: &RedisClient,
+    slack_emoji: &HashMap<String, String>,
+) {
+    // Look up Slack message from Discord message ID
+    let Some((channel, timestamp)) =
+        get_slack_message(linked_channel_id, discord_message_id, redis_client).await
+    else {
+        return;
+    };
+
+    let Some(name) = slack_reaction_name(emoji, |name| slack_emoji.contains_key(name)) else {
+        debug!(emoji, "No Slack emoji for Discord reaction");
+        return;
+    };
+
+    let result = if added {
+        session
+            .reactions_add(&SlackApiReactionsAddRequest::new(
+                channel,
+                name.into(),
+                timestamp,
+            ))
+            .await
+            .map(|_| ())
+    } else {
+        session
+            .reactions_remove(
+                &SlackApiReactionsRemoveRequest::new(name.into())
+                    .with_channel(channel)
+                    .with_timestamp(timestamp),
+            )
+            .await
+            .map(|_| ())
+    };
+
+    if let Err(e) = result {
+        record_failed_call(&e);
+        error!(error = %e, "Failed to mirror reaction");
+    }
+}
+
+async fn handle_bridge_event(
+    slack_client: &SlackApiClient,
+    slack_token: &SlackApiToken,
+    config: &Config,
+    event: BridgeEvent,
+    redis_client: &RedisClient,
+    slack_emoji: &SlackEmojiCache,
+    discord_http: &Arc<serenity::Http>,
+) {
+    if event.team_id != config.discord.guild_id.to_string() {
+        return; // Ignore events from other guilds
+    }
+
+    let Some(slack_channel_id) = get_slack_channel_id(&event.channel_id, redis_client).await else {
+        return;
+    };
+    let Ok(discord_channel_id) = event.channel_id.parse::<u64>() else {
+        return;
+    };
+
+    let Some(settings) = check_link(
+        redis_client,
+        discord_channel_id,
+        &slack_channel_id,
+        Direction::ToSlack,
+        &event,
+        config.max_queued_events,
+    )
+    .await
+    else {
+        return;
+    };
+
+    let session = slack_client.open_session(slack_token);
+    let slack_emoji = slack_emoji.emoji(slack_client, slack_token).await;
+    let slack_has = |name: &str| slack_emoji.contains_key(name);
+
+    match &event.event_type {
+        EventType::MessageSent {
+            message_id,
+            content,
+            attachments,
+            reply_to,
+            embeds,
+            ..
+        } => {
+            let content = rewrite_discord_links(&session, content, redis_client).await;
+            let mut content = discord_text_to_slack(&content, slack_has);
+            if settings.attachments {
+                for attachment in attachments {
+                    content.push_str(&format!("\n<{}|{}>", attachment.url, attachment.filename));
+                }
+            }
+
+            let mut thread_ts = None;
+            if let Some(reply_to) = reply_to {
+                (content, thread_ts) = render_reply(
+                    &session,
+                    &slack_channel_id,
+                    reply_to,
+                    content,
+                    settings.threads,
+                    redis_client,
+                )
+                .await;
+            }
+
+            let slack_message_ts = match retry_transient(|| {
+                send_message_to_slack(
+                    &session,
+                    &slack_channel_id,
+                    &event,
+                    &content,
+                    embeds,
+                    thread_ts.clone(),
+                )
+            })
+            .await
+            {
+                Ok(slack_message_ts) => slack_message_ts,
+                Err(DeliveryFailure { reason, .. }) => {
+                    dead_letter(
+                        redis_client,
+                        Direction::ToSlack,
+                        &event,
+                        discord_channel_id,
+                        &slack_channel_id,
+                        &reason,
+                    )
+                    .await;
+                    notify_discord_author(
+                        discord_http,
+                        discord_channel_id,
+                        message_id,
+                        settings.failure_feedback,
+                        &reason,
+                    )
+                    .await;
+                    return;
+                }
+            };
+
+            if let Ok(discord_message_id) = message_id.parse::<u64>() {
+                // Store message mapping in Redis
+                if let Err(e) = redis_client
+                    .store_message_mapping(
+                        discord_channel_id,
+                        &[discord_message_id],
+                        &slack_channel_id,
+                        slack_message_ts.as_ref(),
+                    )
+                    .await
+                {
+                    error!(error = %e, "Failed to store message mapping");
+                }
+            }
+        }
+        EventType::MessageDeleted { message_id } => {
+            handle_message_deletion(&session, &slack_channel_id, message_id, redis_client).await;
+        }
+        EventType::MessageEdited {
+            message_id,
+            new_content,
+            embeds,
+        } => {
+            let new_content = rewrite_discord_links(&session, new_content, redis_client).await;
+            let new_content = discord_text_to_slack(&new_content, slack_has);
+            handle_message_edit(
+                &session,
+                &slack_channel_id,
+                message_id,
+                &new_content,
+                embeds,
+                redis_client,
+            )
+            .await;
+        }
+        EventType::MessagePinned { message_id } => {
+            handle_pin_change(&session, &slack_channel_id, message_id, true, redis_client).await;
+        }
+        EventType::MessageUnpinned { message_id } => {
+            handle_pin_change(&session, &slack_channel_id, message_id, false, redis_client).await;
+        }
+        EventType::ReactionAdded { message_id, emoji } => {
+            handle_reaction(
+                &session,
+                &slack_channel_id,
+                message_id,
+                emoji,
+                true,
+                redis_client,
+                &slack_emoji,
+            )
+            .await;
+        }
+        EventType::ReactionRemoved { message_id, emoji } => {
+            handle_reaction(
+                &session,
+                &slack_channel_id,
+                message_id,
+                emoji,
+                false,
+                redis_client,
+                &slack_emoji,
+            )
+            .await;
+        }
+        EventType::MembershipNotice { content } | EventType::ChannelNotice { content } => {
+            let _ = send_message_to_slack(&session, &slack_channel_id, &event, content, &[], None)
+                .await;
+        }
+    }
 }
 
 async fn command_event(
     Extension(_environment): Extension<Arc<SlackHyperListenerEnvironment>>,
+    Extension(bridge): Extension<Arc<BridgeChannels>>,
     Extension(redis_client): Extension<Arc<RedisClient>>,
+    Extension(slack_client): Extension<Arc<SlackApiClient>>,
+    Extension(discord_client): Extension<DiscordClient>,
+    Extension(config): Extension<Arc<Config>>,
     Extension(event): Extension<SlackCommandEvent>,
 ) -> axum::Json<SlackCommandEventResponse> {
-    println!("Received command event: {event:?}");
+    info!(
+        command = %event.command,
+        team = %event.team_id,
+        channel = %event.channel_id,
+        user = %event.user_id,
+        "Received command"
+    );
 
     let response = match event.command.0.as_str() {
-        "/link-channel" => handle_link_channel(event, redis_client).await,
+        "/link-channel" => {
+            handle_link_channel(event, redis_client, slack_client, discord_client, config).await
+        }
         "/unlink-channel" => handle_unlink_channel(event, redis_client).await,
+        "/pause-link" => handle_pause_link(event, redis_client).await,
+        "/resume-link" => handle_resume_link(event, redis_client, bridge).await,
+        "/list-links" => handle_list_links(event, redis_client).await,
+        "/carmine" => carmine_command(event, redis_client, slack_client, config).await,
         "/help" => SlackCommandEventResponse::new(
-            SlackMessageContent::new()
-                .with_text("Available commands: /link-channel, /unlink-channel, /help".into()),
+            SlackMessageContent::new().with_text(
+                "Available commands: /link-channel <#channel | server/channel | ID>, /unlink-channel [purge], /pause-link [queue], \
+             /resume-link, /list-links, /carmine settings, /help"
+                    .into(),
+            ),
         ),
         _ => SlackCommandEventResponse::new(
             SlackMessageContent::new().with_text("Unknown command".into()),
@@ -212,11 +1361,42 @@ async fn command_event(
     axum::Json(response)
 }
 
+async fn carmine_command(
+    event: SlackCommandEvent,
+    redis_client: Arc<RedisClient>,
+    slack_client: Arc<SlackApiClient>,
+    config: Arc<Config>,
+) -> SlackCommandEventResponse {
+    let subcommand = event
+        .text
+        .as_deref()
+        .and_then(|text| text.split_whitespace().next())
+        .unwrap_or_default();
+
+    match subcommand {
+        "settings" => handle_settings(event, redis_client, slack_client, config).await,
+        _ => SlackCommandEventResponse::new(
+            SlackMessageContent::new().with_text("Usage: /carmine settings".into()),
+        ),
+    }
+}
+
 async fn interaction_event(
     Extension(_environment): Extension<Arc<SlackHyperListenerEnvironment>>,
+    Extension(redis_client): Extension<Arc<RedisClient>>,
     Extension(event): Extension<SlackInteractionEvent>,
 ) {
-    println!("Received interaction event: {event:?}");
+    debug!("Received interaction event");
+
+    if let SlackInteractionEvent::ViewSubmission(submission) = event
+        && let SlackView::Modal(modal) = &submission.view.view
+        && modal
+            .callback_id
+            .as_ref()
+            .is_some_and(|id| id.0 == SETTINGS_CALLBACK_ID)
+    {
+        handle_settings_submission(submission, redis_client).await;
+    }
 }
 
 fn error_handler(
@@ -224,63 +1404,66 @@ fn error_handler(
     _client: Arc<SlackHyperClient>,
     _states: SlackClientEventsUserState,
 ) -> HttpStatusCode {
-    println!("{err:#?}");
+    warn!(error = %err, "Failed to handle Slack request");
 
     // Defines what we return Slack server
     HttpStatusCode::BAD_REQUEST
 }
 
-pub async fn start(
+/// Serves the Slack endpoints until `shutdown` is cancelled, letting in-flight requests
+/// finish.
+pub async fn serve(
+    config: Arc<Config>,
     channels: BridgeChannels,
-    slack_rx: mpsc::UnboundedReceiver<BridgeEvent>,
     redis_client: RedisClient,
-    slack_client: Arc<SlackHyperClient>,
-) {
-    let slack_client_id = std::env::var("SLACK_CLIENT_ID").expect("SLACK_CLIENT_ID must be set");
-    let slack_client_secret =
-        std::env::var("SLACK_CLIENT_SECRET").expect("SLACK_CLIENT_SECRET must be set");
-    let slack_bot_scope = std::env::var("SLACK_BOT_SCOPE").expect("SLACK_BOT_SCOPE must be set");
-    let slack_redirect_host =
-        std::env::var("SLACK_REDIRECT_HOST").expect("SLACK_REDIRECT_HOST must be set");
-    let slack_signing_secret =
-        std::env::var("SLACK_SIGNING_SECRET").expect("SLACK_SIGNING_SECRET must be set");
-
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 8080));
-    println!("Loading server: {addr}");
+    slack_client: Arc<SlackApiClient>,
+    discord_client: DiscordClient,
+    shutdown: CancellationToken,
+) -> Result<(), Error> {
+    let addr = config.listen_addr;
+    info!(%addr, "Loading server");
 
     let oauth_listener_config = SlackOAuthListenerConfig::new(
-        slack_client_id.into(),
-        slack_client_secret.into(),
-        slack_bot_scope,
-        slack_redirect_host,
+        config.slack.client_id.clone().into(),
+        config.slack.client_secret.clone().into(),
+        config.slack.bot_scope.clone(),
+        config.slack.redirect_host.clone(),
     );
 
+    // The listener only takes the bare client, whose calls aren't counted. It only makes
+    // them to finish an install.
+    let listener_client = Arc::new(SlackClient::new(SlackClientHyperConnector::new()?));
     let listener_environment: Arc<SlackHyperListenerEnvironment> = Arc::new(
-        SlackClientEventsListenerEnvironment::new(slack_client.clone())
+        SlackClientEventsListenerEnvironment::new(listener_client)
             .with_error_handler(error_handler),
     );
-    let signing_secret: SlackSigningSecret = slack_signing_secret.into();
+    let signing_secret: SlackSigningSecret = config.slack.signing_secret.clone().into();
 
     let listener: SlackEventsAxumListener<SlackHyperHttpsConnector> =
         SlackEventsAxumListener::new(listener_environment.clone());
     let bridge_channels = Arc::new(channels);
+    let user_cache = Arc::new(SlackUserCache::new(redis_client.clone()));
+
+    // Look up our own bot ID now rather than on the first message
+    user_cache
+        .own_bot(&slack_client, &config.slack_token())
+        .await;
 
     // Build application route with OAuth nested router and Push/Command/Interaction events
-    let app = axum::routing::Router::new()
+    let mut app = axum::routing::Router::new()
         .nest(
             "/auth",
             listener.oauth_router("/auth", &oauth_listener_config, oauth_install_function),
         )
         .route("/installed", axum::routing::get(welcome_installed))
+        .route("/metrics", axum::routing::get(metrics::serve))
+        .route("/healthz", axum::routing::get(health::healthz))
+        .route("/readyz", axum::routing::get(health::readyz))
         .route("/cancelled", axum::routing::get(cancelled_install))
         .route("/error", axum::routing::get(error_install))
         .route(
             "/push",
-            axum::routing::post(push_event).layer(
-                listener
-                    .events_layer(&signing_secret)
-                    .with_event_extractor(SlackEventsExtractors::push_event()),
-            ),
+            axum::routing::post(push_event).layer(listener.events_layer(&signing_secret)),
         )
         .route(
             "/command",
@@ -297,12 +1480,475 @@ pub async fn start(
                     .events_layer(&signing_secret)
                     .with_event_extractor(SlackEventsExtractors::interaction_event()),
             ),
-        )
+        );
+    if config.admin.api_token.is_some() {
+        app = app.nest("/admin", admin::router());
+    }
+    if config.dashboard.enabled() {
+        app = app.nest("/dashboard", dashboard::router());
+    }
+
+    let app = app
         .layer(Extension(bridge_channels))
-        .layer(Extension(redis_client))
-        .layer(Extension(slack_client));
+        .layer(Extension(Arc::new(redis_client)))
+        .layer(Extension(Arc::new(discord::http_client(
+            &config.discord.token,
+        ))))
+        .layer(Extension(discord_client))
+        .layer(Extension(user_cache))
+        .layer(Extension(Arc::new(SlackChannelCache::default())))
+        .layer(Extension(slack_client))
+        .layer(Extension(config));
+
+    axum::serve(TcpListener::bind(&addr).await?, app)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await?;
+    Ok(())
+}
+
+/// Bridges up to `limit` recent messages in a Slack channel that haven't been bridged yet,
+/// oldest first. Returns how many were sent.
+pub async fn backfill(
+    slack_client: &SlackApiClient,
+    config: &Config,
+    bridge: &BridgeChannels,
+    redis_client: &RedisClient,
+    user_cache: &SlackUserCache,
+    channel_id: &str,
+    limit: u16,
+) -> Result<usize, Error> {
+    let slack_token = config.slack_token();
+    let session = slack_client.open_session(&slack_token);
+
+    let request = SlackApiConversationsHistoryRequest::new()
+        .with_channel(channel_id.into())
+        .with_limit(limit);
+    let history = session.conversations_history(&request).await?;
+
+    let mut count = 0;
+    for message in history.messages.into_iter().rev() {
+        // Anything mapped was either bridged already or came from Discord
+        if redis_client
+            .get_discord_message(message.origin.ts.as_ref())
+            .await?
+            .is_some()
+        {
+            continue;
+        }
 
-    axum::serve(TcpListener::bind(&addr).await.unwrap(), app)
+        // History leaves out the channel, which live events carry
+        let mut origin = message.origin;
+        origin.channel = Some(channel_id.into());
+        let message_event = SlackMessageEvent::new(origin, message.sender)
+            .with_content(message.content)
+            .opt_subtype(message.subtype);
+
+        if let Some(bridge_event) = create_bridge_event(
+            message_event,
+            config.slack.team_id.clone().into(),
+            slack_client,
+            &slack_token,
+            user_cache,
+        )
         .await
-        .unwrap();
+        {
+            metrics::event_received(Direction::ToDiscord, &bridge_event.event_type);
+            bridge.to_discord.send(bridge_event)?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Delivers bridged events to Slack until `drain` is cancelled, then finishes whatever is
+/// already queued.
+pub async fn deliver(
+    config: Arc<Config>,
+    slack_rx: BridgeReceiver,
+    redis_client: RedisClient,
+    slack_client: Arc<SlackApiClient>,
+    slack_emoji: Arc<SlackEmojiCache>,
+    drain: CancellationToken,
+) -> Result<(), Error> {
+    let slack_token = config.slack_token();
+    let discord_http = Arc::new(discord::http_client(&config.discord.token));
+    let mut slack_rx = slack_rx.lock().await;
+
+    loop {
+        let event = if drain.is_cancelled() {
+            match slack_rx.try_recv() {
+                Ok(event) => event,
+                Err(_) => return Ok(()),
+            }
+        } else {
+            tokio::select! {
+                event = slack_rx.recv() => match event {
+                    Some(event) => event,
+                    None => return Ok(()),
+                },
+                () = drain.cancelled() => continue,
+            }
+        };
+        metrics::set_queue_depth(Direction::ToSlack, slack_rx.len());
+
+        let span = event.delivery_span(Direction::ToSlack);
+        handle_bridge_event(
+            &slack_client,
+            &slack_token,
+            &config,
+            event,
+            &redis_client,
+            &slack_emoji,
+            &discord_http,
+        )
+        .instrument(span)
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use slack_morphism::errors::{SlackClientApiError, SlackClientHttpError, SlackRateLimitError};
+
+    use super::*;
+
+    fn message_event(fields: serde_json::Value) -> SlackMessageEvent {
+        let mut event = json!({
+            "type": "message",
+            "channel": "C123",
+            "user": "U123",
+            "ts": "1700000000.000200",
+        });
+        event
+            .as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+
+        serde_json::from_value(event).expect("fixture should parse as a message event")
+    }
+
+    fn sent_content(event_type: Option<EventType>) -> (String, Option<String>) {
+        match event_type {
+            Some(EventType::MessageSent {
+                content, thread_id, ..
+            }) => (content, thread_id),
+            other => panic!("expected a sent message, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plain_message_is_sent() {
+        let event = message_event(json!({ "text": "hello" }));
+
+        assert_eq!(
+            sent_content(message_event_type(&event)),
+            ("hello".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn thread_reply_keeps_its_thread() {
+        let event = message_event(json!({
+            "text": "in a thread",
+            "thread_ts": "1700000000.000100",
+        }));
+
+        assert_eq!(
+            sent_content(message_event_type(&event)).1.as_deref(),
+            Some("1700000000.000100")
+        );
+    }
+
+    #[test]
+    fn bot_message_uses_the_bot_name_and_icon() {
+        let event = message_event(json!({
+            "subtype": "bot_message",
+            "text": "Deploy finished",
+            "bot_id": "B123",
+            "bot_profile": {
+                "id": "B123",
+                "name": "Deploy Bot",
+                "app_id": "A123",
+                "user_id": "U0BOT",
+                "icons": {
+                    "image_36": "https://example.com/36.png",
+                    "image_72": "https://example.com/72.png",
+                },
+            },
+        }));
+
+        assert_eq!(
+            sent_content(message_event_type(&event)).0,
+            "Deploy finished"
+        );
+        assert_eq!(bot_name(&event.sender), "Deploy Bot");
+        assert_eq!(bot_user(&event.sender), Some("U0BOT".into()));
+
+        // A username set on the post wins over the app's name
+        let event = message_event(json!({
+            "subtype": "bot_message",
+            "text": "Deploy finished",
+            "bot_id": "B123",
+            "username": "Release train",
+        }));
+        assert_eq!(bot_name(&event.sender), "Release train");
+        assert_eq!(bot_user(&event.sender), None);
+    }
+
+    #[test]
+    fn own_bot_messages_are_recognised() {
+        let own_bot = OwnBot {
+            user_id: "U999".into(),
+            bot_id: Some("B999".into()),
+        };
+
+        assert!(is_own_bot(Some(&own_bot), Some(&"B999".into())));
+        assert!(!is_own_bot(Some(&own_bot), Some(&"B123".into())));
+        assert!(!is_own_bot(Some(&own_bot), None));
+        assert!(!is_own_bot(None, Some(&"B999".into())));
+
+        assert!(is_own_user(Some(&own_bot), &"U999".into()));
+        assert!(!is_own_user(Some(&own_bot), &"U123".into()));
+        assert!(!is_own_user(None, &"U999".into()));
+    }
+
+    #[test]
+    fn me_message_is_italic() {
+        let event = message_event(json!({ "subtype": "me_message", "text": "waves" }));
+
+        assert_eq!(sent_content(message_event_type(&event)).0, "_waves_");
+    }
+
+    #[test]
+    fn file_share_links_the_files() {
+        let event = message_event(json!({
+            "subtype": "file_share",
+            "text": "",
+            "files": [{
+                "id": "F123",
+                "name": "report.pdf",
+                "permalink": "https://example.slack.com/files/U123/F123/report.pdf",
+            }],
+        }));
+
+        let Some(EventType::MessageSent { attachments, .. }) = message_event_type(&event) else {
+            panic!("expected a sent message");
+        };
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].filename, "report.pdf");
+        assert_eq!(
+            attachments[0].url,
+            "https://example.slack.com/files/U123/F123/report.pdf"
+        );
+    }
+
+    #[test]
+    fn thread_broadcast_is_sent_top_level() {
+        let event = message_event(json!({
+            "subtype": "thread_broadcast",
+            "text": "also in the channel",
+            "thread_ts": "1700000000.000100",
+        }));
+
+        assert_eq!(
+            sent_content(message_event_type(&event)),
+            ("also in the channel".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn joins_and_leaves_are_membership_notices() {
+        for (subtype, text, expected) in [
+            (
+                "channel_join",
+                "<@U123> has joined the channel",
+                "_joined the channel_",
+            ),
+            (
+                "channel_leave",
+                "<@U123> has left the channel",
+                "_left the channel_",
+            ),
+        ] {
+            let event = message_event(json!({ "subtype": subtype, "text": text }));
+
+            match message_event_type(&event) {
+                Some(EventType::MembershipNotice { content }) => assert_eq!(content, expected),
+                other => panic!("expected a membership notice for {subtype}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn topic_and_purpose_are_channel_notices() {
+        for (subtype, text, expected) in [
+            (
+                "channel_topic",
+                "<@U123> set the channel topic: Release planning",
+                "_set the channel topic: Release planning_",
+            ),
+            (
+                "channel_purpose",
+                "<@U123> set the channel purpose: Ship it",
+                "_set the channel purpose: Ship it_",
+            ),
+        ] {
+            let event = message_event(json!({ "subtype": subtype, "text": text }));
+
+            match message_event_type(&event) {
+                Some(EventType::ChannelNotice { content }) => assert_eq!(content, expected),
+                other => panic!("expected a channel notice for {subtype}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn message_changed_is_an_edit_of_the_original() {
+        let event = message_event(json!({
+            "subtype": "message_changed",
+            "hidden": true,
+            "message": {
+                "type": "message",
+                "user": "U456",
+                "text": "fixed typo",
+                "ts": "1700000000.000100",
+            },
+            "previous_message": {
+                "type": "message",
+                "user": "U456",
+                "text": "fixd typo",
+                "ts": "1700000000.000100",
+            },
+        }));
+
+        assert_eq!(
+            message_sender(&event).and_then(|sender| sender.user.as_ref()),
+            Some(&"U456".into())
+        );
+        match message_event_type(&event) {
+            Some(EventType::MessageEdited {
+                message_id,
+                new_content,
+                ..
+            }) => {
+                assert_eq!(message_id, "1700000000.000100");
+                assert_eq!(new_content, "fixed typo");
+            }
+            other => panic!("expected an edit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unfurls_reported_as_edits_are_skipped() {
+        let event = message_event(json!({
+            "subtype": "message_changed",
+            "hidden": true,
+            "message": {
+                "type": "message",
+                "user": "U456",
+                "text": "see https://example.com",
+                "ts": "1700000000.000100",
+                "attachments": [{ "title": "Example", "title_link": "https://example.com" }],
+            },
+            "previous_message": {
+                "type": "message",
+                "user": "U456",
+                "text": "see https://example.com",
+                "ts": "1700000000.000100",
+            },
+        }));
+
+        assert!(message_event_type(&event).is_none());
+    }
+
+    #[test]
+    fn message_deleted_deletes_the_copy() {
+        let event = message_event(json!({
+            "subtype": "message_deleted",
+            "hidden": true,
+            "deleted_ts": "1700000000.000100",
+        }));
+
+        match message_event_type(&event) {
+            Some(EventType::MessageDeleted { message_id }) => {
+                assert_eq!(message_id, "1700000000.000100")
+            }
+            other => panic!("expected a delete, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn message_replied_is_ignored() {
+        // Slack sends this to the parent when a thread gets a reply. slack-morphism doesn't
+        // model it, so the push handler logs and drops it.
+        let push = json!({
+            "type": "event_callback",
+            "team_id": "T123",
+            "api_app_id": "A123",
+            "event_id": "Ev123",
+            "event_time": 1700000000,
+            "event": {
+                "type": "message",
+                "subtype": "message_replied",
+                "channel": "C123",
+                "hidden": true,
+                "ts": "1700000000.000300",
+                "message": {
+                    "type": "message",
+                    "user": "U123",
+                    "text": "parent",
+                    "ts": "1700000000.000100",
+                    "thread_ts": "1700000000.000100",
+                    "reply_count": 1,
+                },
+            },
+        });
+
+        assert!(serde_json::from_value::<SlackPushEvent>(push).is_err());
+    }
+
+    #[test]
+    fn only_failures_that_would_repeat_are_permanent() {
+        let api_error =
+            |code: &str| SlackClientError::ApiError(SlackClientApiError::new(code.into()));
+
+        for code in [
+            "msg_too_long",
+            "channel_not_found",
+            "is_archived",
+            "not_in_channel",
+            "invalid_blocks",
+        ] {
+            assert!(delivery_failure(&api_error(code)).permanent, "{code}");
+        }
+        for code in ["ratelimited", "internal_error", "service_unavailable"] {
+            assert!(!delivery_failure(&api_error(code)).permanent, "{code}");
+        }
+
+        let rate_limit = SlackClientError::RateLimitError(
+            SlackRateLimitError::new().with_retry_after(Duration::from_secs(30)),
+        );
+        let failure = delivery_failure(&rate_limit);
+        assert!(!failure.permanent);
+        assert_eq!(failure.retry_after, Some(Duration::from_secs(30)));
+
+        let http_error = |status: u16| {
+            SlackClientError::HttpError(SlackClientHttpError::new(status.try_into().unwrap()))
+        };
+        assert!(delivery_failure(&http_error(400)).permanent);
+        assert!(!delivery_failure(&http_error(429)).permanent);
+        assert!(!delivery_failure(&http_error(503)).permanent);
+    }
+
+    #[test]
+    fn other_subtypes_are_ignored() {
+        let event = message_event(json!({
+            "subtype": "channel_name",
+            "text": "<@U123> renamed the channel",
+        }));
+
+        assert!(message_event_type(&event).is_none());
+    }
 }
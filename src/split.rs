@@ -0,0 +1,351 @@
+/// Discord rejects message content longer than this many characters.
+pub const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// Slack truncates message text well past this, but asks for no more than this many
+/// characters per message.
+pub const SLACK_MESSAGE_LIMIT: usize = 4000;
+
+const FENCE: &str = "```";
+
+/// Splits a message into parts of at most `limit` characters, preferring paragraph and
+/// code block boundaries. Code blocks that have to be cut are closed and reopened so
+/// every part renders on its own.
+pub fn split_message(content: &str, limit: usize) -> Vec<String> {
+    if char_len(content) <= limit {
+        return vec![content.to_string()];
+    }
+
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for block in blocks(content) {
+        if char_len(&current) + char_len(block) <= limit {
+            current.push_str(block);
+            continue;
+        }
+
+        push_part(&mut parts, std::mem::take(&mut current));
+
+        if char_len(block) <= limit {
+            current.push_str(block);
+        } else {
+            let mut pieces = split_block(block, limit);
+            current = pieces.pop().unwrap_or_default();
+            // Keep the separator the block ended with so the next block doesn't run into it
+            current.push_str(&block[block.trim_end().len()..]);
+            parts.extend(pieces);
+        }
+    }
+    push_part(&mut parts, current);
+
+    parts
+}
+
+/// Cuts a message down to `limit` characters including `suffix`, closing any code block
+/// left open by the cut.
+pub fn truncate_message(content: &str, limit: usize, suffix: &str) -> String {
+    if char_len(content) <= limit {
+        return content.to_string();
+    }
+
+    // Leave room for the suffix and a closing fence
+    let budget = limit.saturating_sub(char_len(suffix) + FENCE.len() + 1);
+    let mut truncated = hard_wrap(content, budget)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .trim_end()
+        .to_string();
+
+    if fence_count(&truncated) % 2 == 1 {
+        truncated.push('\n');
+        truncated.push_str(FENCE);
+    }
+    truncated.push_str(suffix);
+
+    truncated
+}
+
+/// Cuts plain text down to `limit` characters, ending it with an ellipsis if it was cut.
+pub fn clip(text: &str, limit: usize) -> String {
+    if char_len(text) <= limit {
+        return text.to_string();
+    }
+
+    let mut clipped: String = text.chars().take(limit.saturating_sub(1)).collect();
+    clipped.push('…');
+    clipped
+}
+
+fn push_part(parts: &mut Vec<String>, part: String) {
+    let part = part.trim_end();
+    if !part.trim().is_empty() {
+        parts.push(part.to_string());
+    }
+}
+
+/// Breaks content into paragraphs and whole code blocks, each keeping its trailing newlines.
+fn blocks(content: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut block_start = 0;
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let line_end = offset + line.len();
+        let is_fence = line.trim_start().starts_with(FENCE);
+
+        // Code blocks start a block of their own
+        if !in_fence && is_fence && offset > block_start {
+            blocks.push(&content[block_start..offset]);
+            block_start = offset;
+        }
+
+        // A line like ```code``` opens and closes on its own
+        if is_fence && fence_count(line) % 2 == 1 {
+            in_fence = !in_fence;
+            if !in_fence {
+                blocks.push(&content[block_start..line_end]);
+                block_start = line_end;
+            }
+        } else if !in_fence && line.trim().is_empty() {
+            blocks.push(&content[block_start..line_end]);
+            block_start = line_end;
+        }
+
+        offset = line_end;
+    }
+
+    if block_start < content.len() {
+        blocks.push(&content[block_start..]);
+    }
+
+    blocks
+}
+
+/// Splits a single block that doesn't fit in one part.
+fn split_block(block: &str, limit: usize) -> Vec<String> {
+    let Some((opener, body)) = block
+        .trim_start()
+        .starts_with(FENCE)
+        .then(|| block.split_once('\n'))
+        .flatten()
+    else {
+        return pack(block.split_inclusive('\n'), limit);
+    };
+
+    // Every part of a code block gets its own opening and closing fence
+    let opener = opener.trim();
+    let body = body.trim_end();
+    let body = body
+        .strip_suffix(FENCE)
+        .filter(|body| body.is_empty() || body.ends_with('\n'))
+        .unwrap_or(body);
+    let overhead = char_len(opener) + FENCE.len() + 2;
+
+    pack(body.split_inclusive('\n'), limit.saturating_sub(overhead))
+        .into_iter()
+        .map(|part| format!("{opener}\n{part}\n{FENCE}"))
+        .collect()
+}
+
+/// Greedily joins lines into parts of at most `limit` characters, wrapping long lines.
+fn pack<'a>(lines: impl Iterator<Item = &'a str>, limit: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        if char_len(&current) + char_len(line) > limit {
+            push_part(&mut parts, std::mem::take(&mut current));
+        }
+        if char_len(line) <= limit {
+            current.push_str(line);
+            continue;
+        }
+
+        // The pieces of a wrapped line lost the space they were cut at, so each gets a
+        // part of its own rather than running into the next. The last still ends the line.
+        let mut pieces = hard_wrap(line, limit);
+        current = pieces.pop().unwrap_or_default().to_string();
+        for piece in pieces {
+            push_part(&mut parts, piece.to_string());
+        }
+    }
+    push_part(&mut parts, current);
+
+    parts
+}
+
+/// Wraps text into pieces of at most `limit` characters, breaking at whitespace when possible.
+fn hard_wrap(text: &str, limit: usize) -> Vec<&str> {
+    let limit = limit.max(1);
+    let mut pieces = Vec::new();
+    let mut rest = text;
+
+    while let Some((cut, _)) = rest.char_indices().nth(limit) {
+        // Break at the last space past the indentation, dropping only that space
+        let indent = rest.len() - rest.trim_start().len();
+        match rest[..cut]
+            .char_indices()
+            .rev()
+            .find(|&(index, c)| index > indent && c.is_whitespace())
+        {
+            Some((space, c)) => {
+                pieces.push(&rest[..space]);
+                rest = &rest[space + c.len_utf8()..];
+            }
+            None => {
+                pieces.push(&rest[..cut]);
+                rest = &rest[cut..];
+            }
+        }
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+
+    pieces
+}
+
+fn fence_count(text: &str) -> usize {
+    text.matches(FENCE).count()
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_fits(parts: &[String], limit: usize) {
+        for part in parts {
+            assert!(
+                char_len(part) <= limit,
+                "part is {} characters, over {limit}",
+                char_len(part)
+            );
+        }
+    }
+
+    #[test]
+    fn short_messages_are_left_alone() {
+        assert_eq!(split_message("hello", 10), vec!["hello"]);
+        assert_eq!(truncate_message("hello", 10, "…"), "hello");
+    }
+
+    #[test]
+    fn splits_at_paragraphs() {
+        let content = format!("{}\n\n{}", "a".repeat(15), "b".repeat(15));
+        let parts = split_message(&content, 20);
+
+        assert_eq!(parts, vec!["a".repeat(15), "b".repeat(15)]);
+    }
+
+    #[test]
+    fn code_blocks_are_closed_and_reopened_across_parts() {
+        let body: String = (0..50).map(|i| format!("let x{i} = {i};\n")).collect();
+        let content = format!("Before\n\n```rust\n{body}```\n\nAfter");
+        let parts = split_message(&content, 200);
+        assert_fits(&parts, 200);
+
+        let code: Vec<_> = parts.iter().filter(|part| part.contains(FENCE)).collect();
+        assert!(code.len() > 1);
+        for part in &code {
+            assert!(part.starts_with("```rust\n"), "{part:?}");
+            assert_eq!(fence_count(part), 2, "{part:?}");
+        }
+
+        let lines: Vec<_> = code
+            .iter()
+            .flat_map(|part| part.lines().take_while(|line| *line != FENCE).skip(1))
+            .collect();
+        assert_eq!(lines, body.lines().collect::<Vec<_>>());
+        assert_eq!(parts.first().map(String::as_str), Some("Before"));
+        assert!(parts.last().is_some_and(|part| part.ends_with("After")));
+    }
+
+    #[test]
+    fn multibyte_characters_at_the_cut() {
+        let content = "日本語🦀".repeat(750);
+        let parts = split_message(&content, DISCORD_MESSAGE_LIMIT);
+        assert_fits(&parts, DISCORD_MESSAGE_LIMIT);
+        assert_eq!(parts.concat(), content);
+
+        let truncated = truncate_message(&content, 100, "…");
+        assert!(char_len(&truncated) <= 100);
+        assert!(content.starts_with(truncated.trim_end_matches('…')));
+    }
+
+    #[test]
+    fn hard_wraps_a_single_line_over_the_limit() {
+        let content = "word ".repeat(1000);
+        let parts = split_message(&content, DISCORD_MESSAGE_LIMIT);
+        assert!(parts.len() > 1);
+        assert_fits(&parts, DISCORD_MESSAGE_LIMIT);
+        // Cut at the spaces, which are dropped
+        assert_eq!(parts.join(" "), content.trim_end());
+
+        let content = "x".repeat(4500);
+        let lengths: Vec<_> = split_message(&content, DISCORD_MESSAGE_LIMIT)
+            .iter()
+            .map(|part| char_len(part))
+            .collect();
+        assert_eq!(lengths, vec![2000, 2000, 500]);
+    }
+
+    #[test]
+    fn truncation_leaves_room_for_the_suffix() {
+        let suffix = "\n-# Message truncated";
+        let truncated = truncate_message(&"a ".repeat(2000), DISCORD_MESSAGE_LIMIT, suffix);
+
+        assert!(char_len(&truncated) <= DISCORD_MESSAGE_LIMIT);
+        assert!(truncated.ends_with(suffix));
+    }
+
+    #[test]
+    fn truncation_closes_an_open_code_block() {
+        let content = format!("```\n{}```", "line\n".repeat(1000));
+        let suffix = " (truncated)";
+        let truncated = truncate_message(&content, DISCORD_MESSAGE_LIMIT, suffix);
+
+        assert!(char_len(&truncated) <= DISCORD_MESSAGE_LIMIT);
+        assert_eq!(fence_count(&truncated), 2);
+        assert!(truncated.ends_with(&format!("\n```{suffix}")));
+    }
+
+    #[test]
+    fn clip_ends_with_an_ellipsis() {
+        assert_eq!(clip("short", 10), "short");
+        assert_eq!(clip("ééééé", 3), "éé…");
+    }
+
+    #[test]
+    fn wrapped_words_are_not_run_together() {
+        assert_eq!(split_message("aaaa bbbbb", 9), vec!["aaaa", "bbbbb"]);
+    }
+
+    #[test]
+    fn line_one_character_over_the_limit() {
+        let line = format!("{} {}", "a".repeat(1995), "b".repeat(5));
+        assert_eq!(char_len(&line), DISCORD_MESSAGE_LIMIT + 1);
+
+        let parts = split_message(&format!("{line}\nnext line"), DISCORD_MESSAGE_LIMIT);
+        assert_eq!(
+            parts,
+            vec!["a".repeat(1995), "bbbbb\nnext line".to_string()]
+        );
+    }
+
+    #[test]
+    fn wrapped_code_keeps_its_indentation() {
+        let line = format!("    {}", "x".repeat(30));
+        let content = format!("```\n{line}\n    short\n```");
+
+        let parts = split_message(&content, 30);
+        assert_fits(&parts, 30);
+        assert!(parts[0].starts_with("```\n    xxx"));
+        assert!(parts.iter().any(|part| part.contains("\n    short\n")));
+    }
+}
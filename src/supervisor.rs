@@ -0,0 +1,85 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::sources::discord::Error;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A subsystem that stays up this long is considered healthy again, resetting its backoff.
+const STABLE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// How long shutdown waits for queued events to be delivered.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Shutdown happens in two steps: ingress stops taking new events first, then the
+/// consumers deliver what's already queued and stop.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    pub ingress: CancellationToken,
+    pub drain: CancellationToken,
+}
+
+/// Runs a subsystem until `stop` is cancelled, restarting it with exponential backoff
+/// whenever it fails, panics or exits early.
+pub async fn supervise<F, Fut>(name: &'static str, stop: CancellationToken, mut run: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let started = Instant::now();
+        let result = tokio::spawn(run()).await;
+
+        if stop.is_cancelled() {
+            if let Ok(Err(e)) = &result {
+                warn!(subsystem = name, error = %e, "Subsystem failed while stopping");
+            }
+            info!(subsystem = name, "Subsystem stopped");
+            return;
+        }
+
+        match result {
+            Ok(Ok(())) => warn!(subsystem = name, "Subsystem exited unexpectedly"),
+            Ok(Err(e)) => error!(subsystem = name, error = %e, "Subsystem failed"),
+            Err(e) => error!(subsystem = name, error = %e, "Subsystem panicked"),
+        }
+
+        if started.elapsed() >= STABLE_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+        info!(
+            subsystem = name,
+            backoff_secs = backoff.as_secs(),
+            "Restarting subsystem"
+        );
+
+        tokio::select! {
+            () = tokio::time::sleep(backoff) => {}
+            () = stop.cancelled() => return,
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}